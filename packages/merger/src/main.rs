@@ -0,0 +1,27 @@
+//! Compatibility binary, same as `reports merge`
+
+use clap::Parser;
+use reports::commands::merge::{MergeArgs, run};
+use reports::errors::ReportsError;
+
+#[derive(Debug, Parser)]
+#[command(version, about, next_line_help = true)]
+struct CliArgs {
+  #[command(flatten)]
+  args: MergeArgs,
+}
+
+fn main() -> Result<(), ReportsError> {
+  run(CliArgs::parse().args)
+}
+
+#[cfg(test)]
+mod test_merger {
+  use crate::CliArgs;
+  use clap::CommandFactory;
+
+  #[test]
+  fn test_cli_args() {
+    CliArgs::command().debug_assert();
+  }
+}
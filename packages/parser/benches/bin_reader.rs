@@ -0,0 +1,69 @@
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufReader, BufWriter, Write};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use parser::mmap::MmapBinReader;
+use parser::parsers::BinRecord;
+use parser::record::{BankRecord, BankRecordParser, Status, TxType};
+
+const RECORDS_COUNT: u64 = 100_000;
+
+fn write_records(file: &File) {
+  let mut writer = BufWriter::new(file);
+
+  for tx_id in 0..RECORDS_COUNT {
+    BinRecord(BankRecord {
+      tx_id,
+      tx_type: TxType::Transfer,
+      from_user_id: tx_id % 100,
+      to_user_id: tx_id % 7,
+      amount: tx_id * 10,
+      timestamp: 1633036860000 + tx_id,
+      status: Status::Success,
+      description: format!("Record number {tx_id}"),
+    })
+    .write_to(&mut writer)
+    .unwrap();
+  }
+
+  writer.flush().unwrap();
+}
+
+fn bin_reader_benchmark(c: &mut Criterion) {
+  let file = tempfile::NamedTempFile::new().unwrap();
+  write_records(file.as_file());
+
+  let mut group = c.benchmark_group("bin_reader");
+
+  group.bench_function("buffered", |b| {
+    b.iter(|| {
+      let mut reader = BufReader::new(File::open(file.path()).unwrap());
+      let mut amount = 0u64;
+
+      while let Ok(record) = BinRecord::from_read(&mut reader) {
+        amount += black_box(record).amount;
+      }
+
+      amount
+    })
+  });
+
+  group.bench_function("mmap", |b| {
+    b.iter(|| {
+      let reader = MmapBinReader::open(file.path()).unwrap();
+      let mut amount = 0u64;
+
+      for record in reader.records().map_while(Result::ok) {
+        amount += black_box(record).amount;
+      }
+
+      amount
+    })
+  });
+
+  group.finish();
+}
+
+criterion_group!(benches, bin_reader_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,204 @@
+//! Tokio codecs for reading and writing reports with
+//! [AsyncRead](tokio::io::AsyncRead) and [AsyncWrite](tokio::io::AsyncWrite)
+//! sources.
+//!
+//! [FramedRead] with any of the codecs is a
+//! `Stream<Item = Result<BankRecord, ParsingError>>`, [FramedWrite] is a
+//! `Sink<BankRecord, Error = SerializeError>`.
+
+mod bin;
+mod csv;
+mod txt;
+
+pub use bin::BinCodec;
+pub use csv::CsvCodec;
+pub use tokio_util::codec::{FramedRead, FramedWrite};
+pub use txt::TxtCodec;
+
+use crate::errors::SerializeError;
+use crate::record::BankRecordParser;
+use bytes::{BufMut, BytesMut};
+
+/// Serializes record with the blocking parser into the codec buffer, the
+/// buffer is in memory so writing never blocks
+fn encode_record<P: BankRecordParser>(
+  mut record: P,
+  dst: &mut BytesMut,
+) -> Result<(), SerializeError> {
+  record.write_to(&mut dst.writer())
+}
+
+#[cfg(test)]
+mod codec_test {
+  use crate::codec::{BinCodec, CsvCodec, FramedRead, FramedWrite, TxtCodec};
+  use crate::errors::{ParsingError, SerializeError};
+  use crate::parsers::{BinRecord, CVS_RECORD_HEADER};
+  use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+  use futures::{SinkExt, StreamExt};
+  use tokio::io::{AsyncWriteExt, duplex};
+  use tokio_util::codec::{Decoder, Encoder};
+
+  fn records() -> Vec<BankRecord> {
+    (0..20)
+      .map(|tx_id| BankRecord {
+        tx_id,
+        tx_type: TxType::Transfer,
+        from_user_id: tx_id,
+        to_user_id: 9223372036854775807,
+        amount: tx_id * 100,
+        timestamp: 1633036860000 + tx_id,
+        status: Status::Pending,
+        description: format!("Record number {tx_id}"),
+      })
+      .collect()
+  }
+
+  /// Small pipe capacity makes records arrive in several parts
+  async fn round_trip<C>(codec: C) -> Vec<Result<BankRecord, ParsingError>>
+  where
+    C: Decoder<Item = BankRecord, Error = ParsingError>
+      + Encoder<BankRecord, Error = SerializeError>
+      + Clone,
+  {
+    let (writer, reader) = duplex(16);
+    let mut sink = FramedWrite::new(writer, codec.clone());
+    let stream = FramedRead::new(reader, codec);
+
+    let write = async move {
+      for record in records() {
+        sink.send(record).await.unwrap();
+      }
+      sink.close().await.unwrap();
+    };
+
+    let ((), records) = tokio::join!(write, stream.collect::<Vec<_>>());
+
+    records
+  }
+
+  async fn read_bytes<C>(
+    codec: C,
+    data: Vec<u8>,
+  ) -> Vec<Result<BankRecord, ParsingError>>
+  where
+    C: Decoder<Item = BankRecord, Error = ParsingError>,
+  {
+    let (mut writer, reader) = duplex(16);
+    let stream = FramedRead::new(reader, codec);
+
+    // Stream stops at the first error, the rest of data is not read
+    let write = async move {
+      let _ = writer.write_all(&data).await;
+      let _ = writer.shutdown().await;
+    };
+
+    let ((), records) = tokio::join!(write, stream.collect::<Vec<_>>());
+
+    records
+  }
+
+  #[tokio::test]
+  async fn test_bin_round_trip() {
+    let records: Vec<BankRecord> = round_trip(BinCodec::new())
+      .await
+      .into_iter()
+      .map(Result::unwrap)
+      .collect();
+
+    assert_eq!(records, self::records());
+  }
+
+  #[tokio::test]
+  async fn test_checked_bin_round_trip() {
+    let records: Vec<BankRecord> = round_trip(BinCodec::checked())
+      .await
+      .into_iter()
+      .map(Result::unwrap)
+      .collect();
+
+    assert_eq!(records, self::records());
+  }
+
+  #[tokio::test]
+  async fn test_csv_round_trip() {
+    let records: Vec<BankRecord> = round_trip(CsvCodec::with_header())
+      .await
+      .into_iter()
+      .map(Result::unwrap)
+      .collect();
+
+    assert_eq!(records, self::records());
+  }
+
+  #[tokio::test]
+  async fn test_txt_round_trip() {
+    let records: Vec<BankRecord> = round_trip(TxtCodec::new())
+      .await
+      .into_iter()
+      .map(Result::unwrap)
+      .collect();
+
+    assert_eq!(records, self::records());
+  }
+
+  #[tokio::test]
+  async fn test_bin_skips_junk_data() {
+    let mut data: Vec<u8> = b"junk".to_vec();
+    for record in records().into_iter().take(2) {
+      BinRecord(record).write_to(&mut data).unwrap();
+      data.extend_from_slice(b"more junk");
+    }
+
+    let records: Vec<BankRecord> = read_bytes(BinCodec::new(), data)
+      .await
+      .into_iter()
+      .map(Result::unwrap)
+      .collect();
+
+    assert_eq!(records, self::records()[..2]);
+  }
+
+  #[tokio::test]
+  async fn test_bin_truncated_record() {
+    let mut data: Vec<u8> = vec![];
+    for record in records().into_iter().take(2) {
+      BinRecord(record).write_to(&mut data).unwrap();
+    }
+    data.truncate(data.len() - 5);
+
+    let records = read_bytes(BinCodec::new(), data).await;
+
+    assert_eq!(records.len(), 2);
+    assert!(records[0].is_ok());
+    assert!(records[1].is_err());
+  }
+
+  #[tokio::test]
+  async fn test_csv_without_trailing_new_line() {
+    let data = format!(
+      "{CVS_RECORD_HEADER}\n1,DEPOSIT,0,1,100,1633036860000,SUCCESS,\"Record number 1\""
+    );
+
+    let records = read_bytes(CsvCodec::new(), data.into_bytes()).await;
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].as_ref().unwrap().description, "Record number 1");
+  }
+
+  #[tokio::test]
+  async fn test_txt_broken_record() {
+    let data = String::from(
+      "# Record 1 (DEPOSIT)
+TX_TYPE: DEPOSIT
+TO_USER_ID: 9223372036854775807
+
+FROM_USER_ID: 0
+",
+    );
+
+    let records = read_bytes(TxtCodec::new(), data.into_bytes()).await;
+
+    assert_eq!(records.len(), 1);
+    assert!(records[0].is_err());
+  }
+}
@@ -0,0 +1,100 @@
+use crate::codec::encode_record;
+use crate::errors::{ParsingError, SerializeError};
+use crate::parsers::bin::{decode_record, is_record_header, record_len};
+use crate::parsers::{BinRecord, CheckedBinRecord};
+use crate::record::BankRecord;
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Binary records codec, junk data between records is skipped the same way
+/// as [BinRecord] does it
+#[derive(Debug, Default, Clone)]
+pub struct BinCodec {
+  checksum: bool,
+}
+
+impl BinCodec {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Codec which appends CRC32C checksum to every encoded record
+  pub fn checked() -> Self {
+    Self { checksum: true }
+  }
+}
+
+fn find_header(src: &[u8]) -> Option<usize> {
+  src
+    .windows(4)
+    .position(|window| is_record_header(window.try_into().unwrap()))
+}
+
+impl Decoder for BinCodec {
+  type Item = BankRecord;
+  type Error = ParsingError;
+
+  fn decode(
+    &mut self,
+    src: &mut BytesMut,
+  ) -> Result<Option<Self::Item>, Self::Error> {
+    let Some(header_offset) = find_header(src) else {
+      // Last bytes may be the beginning of the next header
+      src.advance(src.len().saturating_sub(3));
+
+      return Ok(None);
+    };
+
+    src.advance(header_offset);
+
+    let len = match record_len(src) {
+      Ok(len) => len,
+      // Rest of the record is not received yet
+      Err(ParsingError::TruncatedRecord { .. }) => return Ok(None),
+      Err(err) => {
+        src.advance(1);
+
+        return Err(err);
+      }
+    };
+
+    let raw_record = src.split_to(len);
+
+    decode_record(&raw_record).map(|(record, _extension)| Some(record))
+  }
+
+  fn decode_eof(
+    &mut self,
+    src: &mut BytesMut,
+  ) -> Result<Option<Self::Item>, Self::Error> {
+    if let Some(record) = self.decode(src)? {
+      return Ok(Some(record));
+    }
+
+    if let Some(header_offset) = find_header(src) {
+      return Err(ParsingError::TruncatedRecord {
+        read: src.len() - header_offset,
+      });
+    }
+
+    src.clear();
+
+    Ok(None)
+  }
+}
+
+impl Encoder<BankRecord> for BinCodec {
+  type Error = SerializeError;
+
+  fn encode(
+    &mut self,
+    item: BankRecord,
+    dst: &mut BytesMut,
+  ) -> Result<(), Self::Error> {
+    if self.checksum {
+      encode_record(CheckedBinRecord(item), dst)
+    } else {
+      encode_record(BinRecord(item), dst)
+    }
+  }
+}
@@ -0,0 +1,87 @@
+use crate::codec::encode_record;
+use crate::errors::{ParsingError, SerializeError};
+use crate::parsers::{CVS_RECORD_HEADER, CsvRecord};
+use crate::record::{BankRecord, BankRecordParser};
+use bytes::{BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// CSV rows codec, the headers line is skipped while decoding
+#[derive(Debug, Default, Clone)]
+pub struct CsvCodec {
+  write_header: bool,
+}
+
+impl CsvCodec {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Codec which writes the headers line before the first encoded record
+  pub fn with_header() -> Self {
+    Self { write_header: true }
+  }
+}
+
+fn decode_line(line: &[u8]) -> Option<Result<BankRecord, ParsingError>> {
+  if line.trim_ascii_end() == CVS_RECORD_HEADER.as_bytes() {
+    return None;
+  }
+
+  Some(CsvRecord::from_read(&mut &line[..]))
+}
+
+impl Decoder for CsvCodec {
+  type Item = BankRecord;
+  type Error = ParsingError;
+
+  fn decode(
+    &mut self,
+    src: &mut BytesMut,
+  ) -> Result<Option<Self::Item>, Self::Error> {
+    while let Some(line_end) = src.iter().position(|&byte| byte == b'\n') {
+      let line = src.split_to(line_end + 1);
+
+      if let Some(record) = decode_line(&line) {
+        return record.map(Some);
+      }
+    }
+
+    Ok(None)
+  }
+
+  fn decode_eof(
+    &mut self,
+    src: &mut BytesMut,
+  ) -> Result<Option<Self::Item>, Self::Error> {
+    if let Some(record) = self.decode(src)? {
+      return Ok(Some(record));
+    }
+
+    if src.is_empty() {
+      return Ok(None);
+    }
+
+    // Last line has no trailing new line
+    let line = src.split();
+
+    decode_line(&line).transpose()
+  }
+}
+
+impl Encoder<BankRecord> for CsvCodec {
+  type Error = SerializeError;
+
+  fn encode(
+    &mut self,
+    item: BankRecord,
+    dst: &mut BytesMut,
+  ) -> Result<(), Self::Error> {
+    if self.write_header {
+      dst.put_slice(CVS_RECORD_HEADER.as_bytes());
+      dst.put_u8(b'\n');
+      self.write_header = false;
+    }
+
+    encode_record(CsvRecord(item), dst)
+  }
+}
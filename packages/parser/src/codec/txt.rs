@@ -0,0 +1,113 @@
+use crate::codec::encode_record;
+use crate::constants::RECORD_LINES_NUMBER;
+use crate::errors::{ParsingError, SerializeError};
+use crate::parsers::TxtRecord;
+use crate::record::{BankRecord, BankRecordParser};
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Multi-line text records codec
+#[derive(Debug, Default, Clone)]
+pub struct TxtCodec;
+
+impl TxtCodec {
+  pub fn new() -> Self {
+    Self
+  }
+}
+
+/// Lines of the buffer without line endings, each with the offset of the
+/// next line
+fn lines(src: &[u8]) -> impl Iterator<Item = (&[u8], usize)> {
+  src
+    .iter()
+    .enumerate()
+    .filter(|&(_, &byte)| byte == b'\n')
+    .scan(0, |start, (end, _)| {
+      let line = &src[*start..end];
+      *start = end + 1;
+
+      Some((line.strip_suffix(b"\r").unwrap_or(line), end + 1))
+    })
+}
+
+/// Length of the first record frame, `None` if the record is not complete.
+/// Empty line within the record ends the frame, so the parser reports it.
+fn frame_len(src: &[u8]) -> Option<usize> {
+  let mut record_lines_count = 0;
+
+  for (line, next_line) in lines(src) {
+    if line.starts_with(b"#") {
+      continue;
+    }
+
+    if line.is_empty() {
+      if record_lines_count == 0 {
+        continue;
+      }
+
+      return Some(next_line);
+    }
+
+    record_lines_count += 1;
+
+    if record_lines_count == RECORD_LINES_NUMBER {
+      return Some(next_line);
+    }
+  }
+
+  None
+}
+
+impl Decoder for TxtCodec {
+  type Item = BankRecord;
+  type Error = ParsingError;
+
+  fn decode(
+    &mut self,
+    src: &mut BytesMut,
+  ) -> Result<Option<Self::Item>, Self::Error> {
+    let Some(len) = frame_len(src) else {
+      return Ok(None);
+    };
+
+    let frame = src.split_to(len);
+
+    TxtRecord::from_read(&mut &frame[..]).map(Some)
+  }
+
+  fn decode_eof(
+    &mut self,
+    src: &mut BytesMut,
+  ) -> Result<Option<Self::Item>, Self::Error> {
+    if let Some(record) = self.decode(src)? {
+      return Ok(Some(record));
+    }
+
+    // Only comments and empty lines are left
+    if src[..]
+      .split(|&byte| byte == b'\n')
+      .all(|line| line.trim_ascii().is_empty() || line.starts_with(b"#"))
+    {
+      src.clear();
+
+      return Ok(None);
+    }
+
+    let frame = src.split();
+
+    TxtRecord::from_read(&mut &frame[..]).map(Some)
+  }
+}
+
+impl Encoder<BankRecord> for TxtCodec {
+  type Error = SerializeError;
+
+  fn encode(
+    &mut self,
+    item: BankRecord,
+    dst: &mut BytesMut,
+  ) -> Result<(), Self::Error> {
+    encode_record(TxtRecord(item), dst)
+  }
+}
@@ -0,0 +1,632 @@
+//! Arrow record batches, Arrow IPC files and Parquet files of records.
+//!
+//! Records are stored in columns named after record fields, transaction
+//! types and statuses are dictionary-encoded text columns. Readers accept any
+//! column types which can be cast to the written ones, so files produced by
+//! other tools can be read as long as they have the same column names.
+
+use crate::constants::{status, tx_type};
+use crate::errors::ColumnarError;
+use crate::record::{BankRecord, Status, TxType};
+use arrow::array::{
+  Array, ArrayBuilder, ArrayRef, AsArray, RecordBatch, StringArray,
+  StringBuilder, StringDictionaryBuilder, UInt64Array, UInt64Builder,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Int8Type, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use std::io::{Read, Seek, Write};
+use std::mem;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(feature = "parquet")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parquet")]
+use parquet::arrow::arrow_reader::{
+  ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder,
+};
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+#[cfg(feature = "parquet")]
+use parquet::file::reader::ChunkReader;
+
+/// Number of records in a written record batch or Parquet row group page
+pub const RECORD_BATCH_SIZE: usize = 8192;
+
+/// Columns in the order of record fields
+pub const COLUMNS: [&str; 8] = [
+  "tx_id",
+  "tx_type",
+  "from_user_id",
+  "to_user_id",
+  "amount",
+  "timestamp",
+  "status",
+  "description",
+];
+
+/// First bytes of every Arrow IPC file
+pub const ARROW_IPC_HEADER: &[u8] = b"ARROW1";
+
+/// First bytes of every Parquet file
+pub const PARQUET_HEADER: &[u8] = b"PAR1";
+
+/// Schema of record batches
+pub fn record_schema() -> SchemaRef {
+  let dictionary =
+    DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8));
+
+  Arc::new(Schema::new(vec![
+    Field::new(COLUMNS[0], DataType::UInt64, false),
+    Field::new(COLUMNS[1], dictionary.clone(), false),
+    Field::new(COLUMNS[2], DataType::UInt64, false),
+    Field::new(COLUMNS[3], DataType::UInt64, false),
+    Field::new(COLUMNS[4], DataType::UInt64, false),
+    Field::new(COLUMNS[5], DataType::UInt64, false),
+    Field::new(COLUMNS[6], dictionary, false),
+    Field::new(COLUMNS[7], DataType::Utf8, false),
+  ]))
+}
+
+/// Collects records into a record batch column by column. Dictionaries of
+/// transaction types and statuses contain all values in the same order, so
+/// every batch has the same dictionaries as IPC files require.
+pub struct RecordBatchBuilder {
+  tx_id: UInt64Builder,
+  tx_type: StringDictionaryBuilder<Int8Type>,
+  from_user_id: UInt64Builder,
+  to_user_id: UInt64Builder,
+  amount: UInt64Builder,
+  timestamp: UInt64Builder,
+  status: StringDictionaryBuilder<Int8Type>,
+  description: StringBuilder,
+}
+
+fn dictionary_builder(values: &[&str]) -> StringDictionaryBuilder<Int8Type> {
+  StringDictionaryBuilder::new_with_dictionary(
+    0,
+    &StringArray::from_iter_values(values),
+  )
+  .expect("dictionary values are unique")
+}
+
+impl Default for RecordBatchBuilder {
+  fn default() -> Self {
+    Self {
+      tx_id: UInt64Builder::new(),
+      tx_type: dictionary_builder(&[
+        tx_type::DEPOSIT,
+        tx_type::TRANSFER,
+        tx_type::WITHDRAWAL,
+      ]),
+      from_user_id: UInt64Builder::new(),
+      to_user_id: UInt64Builder::new(),
+      amount: UInt64Builder::new(),
+      timestamp: UInt64Builder::new(),
+      status: dictionary_builder(&[
+        status::SUCCESS,
+        status::FAILURE,
+        status::PENDING,
+      ]),
+      description: StringBuilder::new(),
+    }
+  }
+}
+
+impl RecordBatchBuilder {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn push(&mut self, record: &BankRecord) -> Result<(), ColumnarError> {
+    self.tx_id.append_value(record.tx_id);
+    self.tx_type.append(record.tx_type.to_string())?;
+    self.from_user_id.append_value(record.from_user_id);
+    self.to_user_id.append_value(record.to_user_id);
+    self.amount.append_value(record.amount);
+    self.timestamp.append_value(record.timestamp);
+    self.status.append(record.status.to_string())?;
+    self.description.append_value(&record.description);
+
+    Ok(())
+  }
+
+  pub fn len(&self) -> usize {
+    self.tx_id.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Builds the batch of pushed records, the builder is reset
+  pub fn finish(&mut self) -> Result<RecordBatch, ColumnarError> {
+    let mut builder = mem::take(self);
+    let columns: Vec<ArrayRef> = vec![
+      Arc::new(builder.tx_id.finish()),
+      Arc::new(builder.tx_type.finish()),
+      Arc::new(builder.from_user_id.finish()),
+      Arc::new(builder.to_user_id.finish()),
+      Arc::new(builder.amount.finish()),
+      Arc::new(builder.timestamp.finish()),
+      Arc::new(builder.status.finish()),
+      Arc::new(builder.description.finish()),
+    ];
+
+    Ok(RecordBatch::try_new(record_schema(), columns)?)
+  }
+}
+
+fn column(
+  batch: &RecordBatch,
+  name: &'static str,
+  data_type: &DataType,
+) -> Result<ArrayRef, ColumnarError> {
+  let column = batch
+    .column_by_name(name)
+    .ok_or(ColumnarError::MissingColumn(name))?;
+
+  Ok(cast(column, data_type)?)
+}
+
+/// Columns of the batch cast to the types of record fields
+struct BatchColumns {
+  tx_id: UInt64Array,
+  tx_type: StringArray,
+  from_user_id: UInt64Array,
+  to_user_id: UInt64Array,
+  amount: UInt64Array,
+  timestamp: UInt64Array,
+  status: StringArray,
+  description: StringArray,
+}
+
+impl BatchColumns {
+  /// Columns are matched by `COLUMNS` names
+  fn new(batch: &RecordBatch) -> Result<Self, ColumnarError> {
+    let numbers = |name: &'static str| -> Result<UInt64Array, ColumnarError> {
+      Ok(
+        column(batch, name, &DataType::UInt64)?
+          .as_primitive()
+          .clone(),
+      )
+    };
+    let texts = |name: &'static str| -> Result<StringArray, ColumnarError> {
+      Ok(column(batch, name, &DataType::Utf8)?.as_string().clone())
+    };
+
+    Ok(Self {
+      tx_id: numbers("tx_id")?,
+      tx_type: texts("tx_type")?,
+      from_user_id: numbers("from_user_id")?,
+      to_user_id: numbers("to_user_id")?,
+      amount: numbers("amount")?,
+      timestamp: numbers("timestamp")?,
+      status: texts("status")?,
+      description: texts("description")?,
+    })
+  }
+
+  fn len(&self) -> usize {
+    self.tx_id.len()
+  }
+
+  fn record(&self, row: usize) -> Result<BankRecord, ColumnarError> {
+    let invalid =
+      |column: &'static str, value: &str| ColumnarError::InvalidValue {
+        column,
+        value: value.to_string(),
+      };
+    // Casting out of range values results in nulls as well
+    let number =
+      |name: &'static str, column: &UInt64Array| match column.is_valid(row) {
+        true => Ok(column.value(row)),
+        false => Err(invalid(name, "null")),
+      };
+    let text =
+      |name: &'static str, column: &StringArray| match column.is_valid(row) {
+        true => Ok(column.value(row).to_string()),
+        false => Err(invalid(name, "null")),
+      };
+
+    let tx_type = text("tx_type", &self.tx_type)?;
+    let status = text("status", &self.status)?;
+
+    Ok(BankRecord {
+      tx_id: number("tx_id", &self.tx_id)?,
+      tx_type: TxType::from_str(&tx_type)
+        .map_err(|_| invalid("tx_type", &tx_type))?,
+      from_user_id: number("from_user_id", &self.from_user_id)?,
+      to_user_id: number("to_user_id", &self.to_user_id)?,
+      amount: number("amount", &self.amount)?,
+      timestamp: number("timestamp", &self.timestamp)?,
+      status: Status::from_str(&status)
+        .map_err(|_| invalid("status", &status))?,
+      description: text("description", &self.description)?,
+    })
+  }
+}
+
+/// Reads records of the batch, columns are matched by `COLUMNS` names
+pub fn batch_records(
+  batch: &RecordBatch,
+) -> Result<Vec<BankRecord>, ColumnarError> {
+  let columns = BatchColumns::new(batch)?;
+
+  (0..columns.len()).map(|row| columns.record(row)).collect()
+}
+
+/// Position of a record read by `BatchRecords`, indexes are counted from 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchPosition {
+  pub batch: usize,
+  /// Row of the record in the batch, errors of the whole batch, e.g. a
+  /// missing column, have no row
+  pub row: Option<usize>,
+}
+
+/// Reads records of record batches one batch at a time, e.g. batches of
+/// Arrow IPC or Parquet file readers. Records which can't be converted are
+/// returned as errors and reading continues, batches which can't be read end
+/// the records.
+pub struct BatchRecords<I> {
+  batches: I,
+  /// Columns of the current batch
+  columns: Option<BatchColumns>,
+  position: BatchPosition,
+  /// Index of the next batch
+  next_batch: usize,
+  /// Index of the next row of the current batch
+  next_row: usize,
+  finished: bool,
+}
+
+impl<I> BatchRecords<I> {
+  pub fn new(batches: I) -> Self {
+    Self {
+      batches,
+      columns: None,
+      position: BatchPosition {
+        batch: 0,
+        row: None,
+      },
+      next_batch: 0,
+      next_row: 0,
+      finished: false,
+    }
+  }
+
+  /// Position of the last returned record or error
+  pub fn position(&self) -> BatchPosition {
+    self.position
+  }
+}
+
+impl<I: Iterator<Item = Result<RecordBatch, ArrowError>>> Iterator
+  for BatchRecords<I>
+{
+  type Item = Result<BankRecord, ColumnarError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(columns) = &self.columns
+        && self.next_row < columns.len()
+      {
+        let row = self.next_row;
+        self.next_row += 1;
+        self.position.row = Some(row);
+
+        return Some(columns.record(row));
+      }
+
+      if self.finished {
+        return None;
+      }
+
+      self.columns = None;
+      self.next_row = 0;
+      self.position = BatchPosition {
+        batch: self.next_batch,
+        row: None,
+      };
+      self.next_batch += 1;
+
+      match self.batches.next()? {
+        Ok(batch) => match BatchColumns::new(&batch) {
+          Ok(columns) => self.columns = Some(columns),
+          Err(err) => return Some(Err(err)),
+        },
+        Err(err) => {
+          self.finished = true;
+          return Some(Err(err.into()));
+        }
+      }
+    }
+  }
+}
+
+/// Destination of record batches, e.g. an Arrow IPC or Parquet file writer
+pub trait BatchSink {
+  type Inner;
+
+  fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), ColumnarError>;
+
+  /// Completes the file and returns the underlying writer
+  fn into_inner(self) -> Result<Self::Inner, ColumnarError>;
+}
+
+impl<W: Write> BatchSink for FileWriter<W> {
+  type Inner = W;
+
+  fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), ColumnarError> {
+    Ok(self.write(batch)?)
+  }
+
+  fn into_inner(mut self) -> Result<W, ColumnarError> {
+    self.finish()?;
+
+    Ok(FileWriter::into_inner(self)?)
+  }
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> BatchSink for ArrowWriter<W> {
+  type Inner = W;
+
+  fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), ColumnarError> {
+    Ok(self.write(batch)?)
+  }
+
+  fn into_inner(self) -> Result<W, ColumnarError> {
+    Ok(ArrowWriter::into_inner(self)?)
+  }
+}
+
+/// Writes records in batches of `RECORD_BATCH_SIZE` records, `finish` has to
+/// be called to write the last batch and complete the file
+pub struct ColumnarWriter<S: BatchSink> {
+  sink: S,
+  builder: RecordBatchBuilder,
+}
+
+impl<W: Write> ColumnarWriter<FileWriter<W>> {
+  /// Arrow IPC file writer
+  pub fn ipc(writer: W) -> Result<Self, ColumnarError> {
+    Ok(Self::new(FileWriter::try_new(writer, &record_schema())?))
+  }
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> ColumnarWriter<ArrowWriter<W>> {
+  /// Parquet file writer, columns are Snappy compressed
+  pub fn parquet(writer: W) -> Result<Self, ColumnarError> {
+    let properties = WriterProperties::builder()
+      .set_compression(parquet::basic::Compression::SNAPPY)
+      .build();
+
+    Ok(Self::new(ArrowWriter::try_new(
+      writer,
+      record_schema(),
+      Some(properties),
+    )?))
+  }
+}
+
+impl<S: BatchSink> ColumnarWriter<S> {
+  pub fn new(sink: S) -> Self {
+    Self {
+      sink,
+      builder: RecordBatchBuilder::new(),
+    }
+  }
+
+  pub fn write(&mut self, record: &BankRecord) -> Result<(), ColumnarError> {
+    self.builder.push(record)?;
+
+    if self.builder.len() >= RECORD_BATCH_SIZE {
+      self.sink.write_batch(&self.builder.finish()?)?;
+    }
+
+    Ok(())
+  }
+
+  pub fn finish(mut self) -> Result<S::Inner, ColumnarError> {
+    if !self.builder.is_empty() {
+      self.sink.write_batch(&self.builder.finish()?)?;
+    }
+
+    self.sink.into_inner()
+  }
+}
+
+/// Reads records of the Arrow IPC file batch by batch
+pub fn ipc_records<R: Read + Seek>(
+  reader: R,
+) -> Result<BatchRecords<FileReader<R>>, ColumnarError> {
+  Ok(BatchRecords::new(FileReader::try_new(reader, None)?))
+}
+
+/// Reads all records of the Arrow IPC file
+pub fn read_ipc(
+  reader: impl Read + Seek,
+) -> Result<Vec<BankRecord>, ColumnarError> {
+  ipc_records(reader)?.collect()
+}
+
+/// Reads records of the Parquet file batch by batch, e.g. of `File` or
+/// `Bytes`
+#[cfg(feature = "parquet")]
+pub fn parquet_records(
+  reader: impl ChunkReader + 'static,
+) -> Result<BatchRecords<ParquetRecordBatchReader>, ColumnarError> {
+  Ok(BatchRecords::new(
+    ParquetRecordBatchReaderBuilder::try_new(reader)?.build()?,
+  ))
+}
+
+/// Reads all records of the Parquet file, e.g. `File` or `Bytes`
+#[cfg(feature = "parquet")]
+pub fn read_parquet(
+  reader: impl ChunkReader + 'static,
+) -> Result<Vec<BankRecord>, ColumnarError> {
+  parquet_records(reader)?.collect()
+}
+
+#[cfg(test)]
+mod columnar_test {
+  use crate::columnar::{
+    ARROW_IPC_HEADER, BatchPosition, BatchRecords, ColumnarWriter,
+    RECORD_BATCH_SIZE, RecordBatchBuilder, batch_records, read_ipc,
+  };
+  use crate::errors::ColumnarError;
+  use crate::record::{BankRecord, Status, TxType};
+  use arrow::array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+  use arrow::datatypes::DataType;
+  use std::io::Cursor;
+  use std::sync::Arc;
+
+  fn record(tx_id: u64) -> BankRecord {
+    BankRecord {
+      tx_id,
+      tx_type: [TxType::Deposit, TxType::Transfer, TxType::Withdrawal]
+        [tx_id as usize % 3]
+        .clone(),
+      from_user_id: tx_id % 7,
+      to_user_id: u64::MAX - tx_id,
+      amount: 100 * tx_id,
+      timestamp: 1633036860000 + tx_id,
+      status: [Status::Success, Status::Failure, Status::Pending]
+        [tx_id as usize % 3]
+        .clone(),
+      description: format!("Record number {tx_id}"),
+    }
+  }
+
+  #[test]
+  fn test_record_batch() -> Result<(), ColumnarError> {
+    let records: Vec<BankRecord> = (0..5).map(record).collect();
+    let mut builder = RecordBatchBuilder::new();
+    for record in &records {
+      builder.push(record)?;
+    }
+
+    let batch = builder.finish()?;
+
+    assert!(builder.is_empty());
+    assert_eq!(batch.num_rows(), 5);
+    assert!(matches!(
+      batch.column_by_name("status").unwrap().data_type(),
+      DataType::Dictionary(..)
+    ));
+    assert_eq!(batch_records(&batch)?, records);
+
+    // Plain text columns of other tools are read as well
+    let columns: Vec<(&str, ArrayRef)> = vec![
+      ("tx_id", Arc::new(UInt64Array::from(vec![1]))),
+      ("tx_type", Arc::new(StringArray::from(vec!["REFUND"]))),
+    ];
+    let batch = RecordBatch::try_from_iter(columns)?;
+    assert!(matches!(
+      batch_records(&batch),
+      Err(ColumnarError::MissingColumn("from_user_id"))
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_batch_records_positions() -> Result<(), ColumnarError> {
+    let mut builder = RecordBatchBuilder::new();
+    builder.push(&record(1))?;
+    let valid = builder.finish()?;
+
+    let text = |value: &str| -> ArrayRef {
+      Arc::new(StringArray::from(vec![value, value]))
+    };
+    let number = || -> ArrayRef { Arc::new(UInt64Array::from(vec![7, 8])) };
+    let columns: Vec<(&str, ArrayRef)> = vec![
+      ("tx_id", number()),
+      (
+        "tx_type",
+        Arc::new(StringArray::from(vec!["REFUND", "DEPOSIT"])),
+      ),
+      ("from_user_id", number()),
+      ("to_user_id", number()),
+      ("amount", number()),
+      ("timestamp", number()),
+      ("status", text("SUCCESS")),
+      ("description", text("")),
+    ];
+    let invalid_row = RecordBatch::try_from_iter(columns)?;
+    let columns: Vec<(&str, ArrayRef)> = vec![("tx_id", number())];
+    let missing_column = RecordBatch::try_from_iter(columns)?;
+
+    let mut records = BatchRecords::new(
+      vec![Ok(invalid_row), Ok(missing_column), Ok(valid)].into_iter(),
+    );
+    let mut positions = vec![];
+    while let Some(record) = records.next() {
+      positions.push((records.position(), record.map(|record| record.tx_id)));
+    }
+
+    let position =
+      |batch: usize, row: Option<usize>| BatchPosition { batch, row };
+    assert!(matches!(
+      positions[..],
+      [
+        (p0, Err(ColumnarError::InvalidValue {
+          column: "tx_type",
+          ..
+        })),
+        (p1, Ok(8)),
+        (p2, Err(ColumnarError::MissingColumn("tx_type"))),
+        (p3, Ok(1)),
+      ] if p0 == position(0, Some(0))
+        && p1 == position(0, Some(1))
+        && p2 == position(1, None)
+        && p3 == position(2, Some(0))
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_ipc_round_trip() -> Result<(), ColumnarError> {
+    let records: Vec<BankRecord> =
+      (0..RECORD_BATCH_SIZE as u64 + 10).map(record).collect();
+    let mut writer = ColumnarWriter::ipc(vec![])?;
+    for record in &records {
+      writer.write(record)?;
+    }
+
+    let data = writer.finish()?;
+
+    assert!(data.starts_with(ARROW_IPC_HEADER));
+    assert_eq!(read_ipc(Cursor::new(data))?, records);
+
+    Ok(())
+  }
+
+  #[cfg(feature = "parquet")]
+  #[test]
+  fn test_parquet_round_trip() -> Result<(), ColumnarError> {
+    use crate::columnar::{PARQUET_HEADER, read_parquet};
+    use bytes::Bytes;
+
+    let records: Vec<BankRecord> = (0..100).map(record).collect();
+    let mut writer = ColumnarWriter::parquet(vec![])?;
+    for record in &records {
+      writer.write(record)?;
+    }
+
+    let data = writer.finish()?;
+
+    assert!(data.starts_with(PARQUET_HEADER));
+    assert_eq!(read_parquet(Bytes::from(data))?, records);
+
+    Ok(())
+  }
+}
@@ -0,0 +1,225 @@
+//! Transparent gzip and zstd compression of report sources.
+
+use flate2::Compression as GzipLevel;
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use std::ffi::OsStr;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+const GZIP_MAGIC: &[u8; 2] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8; 4] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+  #[default]
+  None,
+  Gzip,
+  Zstd,
+}
+
+impl Compression {
+  /// Detects compression by the last extension of the path, e.g. `.csv.gz`
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+    match path.as_ref().extension().and_then(OsStr::to_str) {
+      Some("gz") => Self::Gzip,
+      Some("zst") => Self::Zstd,
+      _ => Self::None,
+    }
+  }
+
+  /// Detects compression by magic bytes of the data beginning
+  pub fn sniff(data: &[u8]) -> Self {
+    if data.starts_with(GZIP_MAGIC) {
+      Self::Gzip
+    } else if data.starts_with(ZSTD_MAGIC) {
+      Self::Zstd
+    } else {
+      Self::None
+    }
+  }
+
+  pub fn extension(&self) -> Option<&'static str> {
+    match self {
+      Self::None => None,
+      Self::Gzip => Some("gz"),
+      Self::Zstd => Some("zst"),
+    }
+  }
+}
+
+/// Extension of the report data itself, compression extension is skipped,
+/// e.g. `csv` for `records.csv.gz`
+pub fn data_extension(path: &Path) -> Option<&str> {
+  let path = match Compression::from_path(path) {
+    Compression::None => path,
+    _ => Path::new(path.file_stem()?),
+  };
+
+  path.extension().and_then(OsStr::to_str)
+}
+
+/// Reader which decompresses the source if it starts with gzip or zstd magic
+/// bytes, plain sources are read as is
+pub enum DecompressedReader<R: BufRead> {
+  Plain(R),
+  Gzip(BufReader<MultiGzDecoder<R>>),
+  Zstd(BufReader<zstd::Decoder<'static, R>>),
+}
+
+impl<R: BufRead> DecompressedReader<R> {
+  pub fn new(mut reader: R) -> io::Result<Self> {
+    // Magic bytes are peeked, so nothing is consumed from the source
+    let compression = Compression::sniff(reader.fill_buf()?);
+
+    Ok(match compression {
+      Compression::None => Self::Plain(reader),
+      Compression::Gzip => {
+        Self::Gzip(BufReader::new(MultiGzDecoder::new(reader)))
+      }
+      Compression::Zstd => {
+        Self::Zstd(BufReader::new(zstd::Decoder::with_buffer(reader)?))
+      }
+    })
+  }
+
+  pub fn compression(&self) -> Compression {
+    match self {
+      Self::Plain(_) => Compression::None,
+      Self::Gzip(_) => Compression::Gzip,
+      Self::Zstd(_) => Compression::Zstd,
+    }
+  }
+}
+
+impl<R: BufRead> Read for DecompressedReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    match self {
+      Self::Plain(reader) => reader.read(buf),
+      Self::Gzip(reader) => reader.read(buf),
+      Self::Zstd(reader) => reader.read(buf),
+    }
+  }
+}
+
+impl<R: BufRead> BufRead for DecompressedReader<R> {
+  fn fill_buf(&mut self) -> io::Result<&[u8]> {
+    match self {
+      Self::Plain(reader) => reader.fill_buf(),
+      Self::Gzip(reader) => reader.fill_buf(),
+      Self::Zstd(reader) => reader.fill_buf(),
+    }
+  }
+
+  fn consume(&mut self, amount: usize) {
+    match self {
+      Self::Plain(reader) => reader.consume(amount),
+      Self::Gzip(reader) => reader.consume(amount),
+      Self::Zstd(reader) => reader.consume(amount),
+    }
+  }
+}
+
+/// Writer which compresses written data, [CompressedWriter::finish] has to
+/// be called to write the compressed stream trailer
+pub enum CompressedWriter<W: Write> {
+  Plain(W),
+  Gzip(GzEncoder<W>),
+  Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> CompressedWriter<W> {
+  pub fn new(writer: W, compression: Compression) -> io::Result<Self> {
+    Ok(match compression {
+      Compression::None => Self::Plain(writer),
+      Compression::Gzip => {
+        Self::Gzip(GzEncoder::new(writer, GzipLevel::default()))
+      }
+      Compression::Zstd => Self::Zstd(zstd::Encoder::new(writer, 0)?),
+    })
+  }
+
+  /// Finishes the compressed stream and returns the underlying writer
+  pub fn finish(self) -> io::Result<W> {
+    match self {
+      Self::Plain(writer) => Ok(writer),
+      Self::Gzip(encoder) => encoder.finish(),
+      Self::Zstd(encoder) => encoder.finish(),
+    }
+  }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    match self {
+      Self::Plain(writer) => writer.write(buf),
+      Self::Gzip(encoder) => encoder.write(buf),
+      Self::Zstd(encoder) => encoder.write(buf),
+    }
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    match self {
+      Self::Plain(writer) => writer.flush(),
+      Self::Gzip(encoder) => encoder.flush(),
+      Self::Zstd(encoder) => encoder.flush(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod compression_test {
+  use crate::compression::{
+    CompressedWriter, Compression, DecompressedReader, data_extension,
+  };
+  use crate::parsers::CsvRecord;
+  use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+  use std::io::{Cursor, Write};
+  use std::path::Path;
+
+  fn record() -> BankRecord {
+    BankRecord {
+      tx_id: 1,
+      tx_type: TxType::Deposit,
+      from_user_id: 0,
+      to_user_id: 2,
+      amount: 100,
+      timestamp: 1633036860000,
+      status: Status::Success,
+      description: String::from("Record number 1"),
+    }
+  }
+
+  fn round_trip(compression: Compression) {
+    let mut writer = CompressedWriter::new(vec![], compression).unwrap();
+    CsvRecord(record()).write_to(&mut writer).unwrap();
+    writer.flush().unwrap();
+    let data = writer.finish().unwrap();
+
+    assert_eq!(Compression::sniff(&data), compression);
+
+    let mut reader = DecompressedReader::new(Cursor::new(data)).unwrap();
+
+    assert_eq!(reader.compression(), compression);
+    assert_eq!(CsvRecord::from_read(&mut reader).unwrap(), record());
+  }
+
+  #[test]
+  fn test_round_trip() {
+    round_trip(Compression::None);
+    round_trip(Compression::Gzip);
+    round_trip(Compression::Zstd);
+  }
+
+  #[test]
+  fn test_path_extensions() {
+    let path = Path::new("./records.csv.gz");
+
+    assert_eq!(Compression::from_path(path), Compression::Gzip);
+    assert_eq!(data_extension(path), Some("csv"));
+    assert_eq!(data_extension(Path::new("records.bin.zst")), Some("bin"));
+    assert_eq!(data_extension(Path::new("records.txt")), Some("txt"));
+    assert_eq!(data_extension(Path::new("records.gz")), None);
+  }
+}
@@ -0,0 +1,250 @@
+//! Calendar dates of record timestamps. Timestamps are milliseconds since
+//! the Unix epoch, dates are in UTC.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+const MILLIS_PER_MINUTE: u64 = 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+  pub year: u32,
+  /// Month of the year, from 1 to 12
+  pub month: u32,
+  /// Day of the month, from 1
+  pub day: u32,
+}
+
+impl Date {
+  pub fn from_timestamp(timestamp: u64) -> Self {
+    Self::from_days(timestamp / MILLIS_PER_DAY)
+  }
+
+  /// Converts days since the epoch to a date of the proleptic Gregorian
+  /// calendar, see http://howardhinnant.github.io/date_algorithms.html
+  fn from_days(days: u64) -> Self {
+    // Shift epoch to 0000-03-01, so leap day is the last day of the year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+      - day_of_era / 146_096)
+      / 365;
+    let day_of_year =
+      day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+      shifted_month + 3
+    } else {
+      shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    Self {
+      year: year as u32,
+      month: month as u32,
+      day: day as u32,
+    }
+  }
+
+  /// Days since the epoch, `None` for dates before it or invalid dates
+  pub fn days(&self) -> Option<u64> {
+    if !(1..=12).contains(&self.month)
+      || self.day == 0
+      || self.day > days_in_month(self.year, self.month)
+    {
+      return None;
+    }
+
+    // Inverse of `from_days`, years start on March 1st
+    let year = u64::from(self.year) - u64::from(self.month <= 2);
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let shifted_month = u64::from((self.month + 9) % 12);
+    let day_of_year = (153 * shifted_month + 2) / 5 + u64::from(self.day) - 1;
+    let day_of_era =
+      year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+  }
+
+  /// Timestamp of the day start
+  pub fn timestamp(&self) -> Option<u64> {
+    self.days().map(|days| days * MILLIS_PER_DAY)
+  }
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+  match month {
+    2 if year.is_multiple_of(4)
+      && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+    {
+      29
+    }
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+fn parse_number<T: FromStr>(digits: &str) -> Option<T> {
+  match digits.bytes().all(|byte| byte.is_ascii_digit()) {
+    true => digits.parse().ok(),
+    false => None,
+  }
+}
+
+/// Parses ISO 8601 date, e.g. `2021-10-01`
+impl FromStr for Date {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut parts = s.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+      (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+      return Err(());
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+      return Err(());
+    }
+
+    let date = Self {
+      year: parse_number(year).ok_or(())?,
+      month: parse_number(month).ok_or(())?,
+      day: parse_number(day).ok_or(())?,
+    };
+
+    date.days().map(|_| date).ok_or(())
+  }
+}
+
+/// ISO 8601 date and time in UTC with milliseconds, e.g.
+/// `2021-10-01T00:00:00.000Z`
+pub fn format_date_time(timestamp: u64) -> String {
+  let time = timestamp % MILLIS_PER_DAY;
+
+  format!(
+    "{}T{:02}:{:02}:{:02}.{:03}Z",
+    Date::from_timestamp(timestamp),
+    time / (60 * MILLIS_PER_MINUTE),
+    time / MILLIS_PER_MINUTE % 60,
+    time / 1000 % 60,
+    time % 1000
+  )
+}
+
+/// Parses ISO 8601 date and time, e.g. `2021-10-01T02:00:00.5+02:00`. Time
+/// without an offset is in UTC, fractions finer than milliseconds are
+/// truncated.
+pub fn parse_date_time(value: &str) -> Option<u64> {
+  let (date, time) = value.split_once('T')?;
+  let date = Date::from_str(date).ok()?;
+
+  let (time, offset) = match time.find(['Z', '+', '-']) {
+    Some(index) => time.split_at(index),
+    None => (time, ""),
+  };
+  let offset: i64 = match offset.as_bytes() {
+    [] | [b'Z'] => 0,
+    [sign @ (b'+' | b'-'), ..] => {
+      let (hours, minutes) = offset[1..].split_once(':')?;
+      let hours: i64 = parse_number(hours)?;
+      let minutes: i64 = parse_number(minutes)?;
+      if hours > 23 || minutes > 59 {
+        return None;
+      }
+      let minutes = hours * 60 + minutes;
+      if *sign == b'+' { minutes } else { -minutes }
+    }
+    _ => return None,
+  };
+
+  let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+  let mut parts = time.split(':');
+  let (Some(hours), Some(minutes), seconds, None) =
+    (parts.next(), parts.next(), parts.next(), parts.next())
+  else {
+    return None;
+  };
+  let hours: u64 = parse_number(hours)?;
+  let minutes: u64 = parse_number(minutes)?;
+  let seconds: u64 = seconds.map_or(Some(0), parse_number)?;
+  if hours > 23 || minutes > 59 || seconds > 59 {
+    return None;
+  }
+  let millis: u64 = match fraction.len() {
+    0 => 0,
+    len => {
+      parse_number::<u64>(fraction.get(..len.min(3))?)?
+        * 10u64.pow(3 - len.min(3) as u32)
+    }
+  };
+
+  let local = date.timestamp()?
+    + (hours * 60 + minutes) * MILLIS_PER_MINUTE
+    + seconds * 1000
+    + millis;
+
+  local.checked_add_signed(-offset * MILLIS_PER_MINUTE as i64)
+}
+
+/// ISO 8601 date, e.g. `2021-10-01`
+impl Display for Date {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+  }
+}
+
+#[cfg(test)]
+mod date_test {
+  use crate::date::{Date, format_date_time, parse_date_time};
+  use std::str::FromStr;
+
+  #[test]
+  fn test_from_timestamp() {
+    let date = |timestamp: u64| Date::from_timestamp(timestamp).to_string();
+
+    assert_eq!(date(0), "1970-01-01");
+    assert_eq!(date(1633036860000), "2021-09-30");
+    assert_eq!(date(1633046400000), "2021-10-01");
+    assert_eq!(date(951782400000), "2000-02-29");
+    assert_eq!(date(951868800000), "2000-03-01");
+    assert_eq!(date(4107542399999), "2100-02-28");
+  }
+
+  #[test]
+  fn test_date_time() {
+    for timestamp in [0, 951782400000, 1633036860123, 4107542399999] {
+      let date = Date::from_timestamp(timestamp);
+      assert_eq!(Date::from_str(&date.to_string()), Ok(date));
+      assert_eq!(
+        parse_date_time(&format_date_time(timestamp)),
+        Some(timestamp)
+      );
+    }
+
+    assert_eq!(format_date_time(1633036860123), "2021-09-30T21:21:00.123Z");
+    assert_eq!(
+      parse_date_time("2021-10-01T00:21:00.1234+03:00"),
+      Some(1633036860123)
+    );
+    assert_eq!(parse_date_time("2021-09-30T21:21"), Some(1633036860000));
+    assert_eq!(parse_date_time("1969-12-31T23:59:59Z"), None);
+    assert_eq!(Date::from_str("2021-02-29"), Err(()));
+    assert_eq!(parse_date_time("2021-09-30T24:00:00Z"), None);
+    assert_eq!(
+      parse_date_time("2021-10-01T02:00:00-23:59"),
+      Some(1633139940000)
+    );
+    assert_eq!(parse_date_time("2021-10-01T02:00:00+24:00"), None);
+    assert_eq!(parse_date_time("2021-10-01T02:00:00+02:60"), None);
+    assert_eq!(
+      parse_date_time("2021-10-01T02:00:00+999999999999999999:00"),
+      None
+    );
+    assert_eq!(parse_date_time("2021-10-01T02:00:00.00\u{e9}"), None);
+  }
+}
@@ -0,0 +1,162 @@
+//! Removal of duplicate records, e.g. transactions posted twice.
+//!
+//! Only 64-bit content hashes of seen records are kept, so memory use stays
+//! small for large reports.
+
+use crate::record::BankRecord;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupeKey {
+  /// Records with the same transaction id are duplicates, the first one is
+  /// kept
+  TxId,
+  /// Only records with the same contents are duplicates
+  Full,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DedupeReport {
+  /// Number of removed duplicate records
+  pub removed: usize,
+  /// Transaction ids shared by records with different contents
+  pub conflicts: BTreeSet<u64>,
+}
+
+#[derive(Debug)]
+pub struct Deduplicator {
+  key: DedupeKey,
+  /// Content hashes of kept records by transaction id
+  seen: HashMap<u64, Vec<u64>>,
+  report: DedupeReport,
+}
+
+impl Deduplicator {
+  pub fn new(key: DedupeKey) -> Self {
+    Self {
+      key,
+      seen: HashMap::new(),
+      report: DedupeReport::default(),
+    }
+  }
+
+  /// Registers the record, returns `false` if it is a duplicate of an
+  /// already seen one and has to be dropped
+  pub fn insert(&mut self, record: &BankRecord) -> bool {
+    let hash = content_hash(record);
+    let hashes = self.seen.entry(record.tx_id).or_default();
+
+    if !hashes.is_empty() && !hashes.contains(&hash) {
+      self.report.conflicts.insert(record.tx_id);
+    }
+
+    let duplicate = match self.key {
+      DedupeKey::TxId => !hashes.is_empty(),
+      DedupeKey::Full => hashes.contains(&hash),
+    };
+
+    if duplicate {
+      self.report.removed += 1;
+    } else {
+      hashes.push(hash);
+    }
+
+    !duplicate
+  }
+
+  pub fn report(&self) -> &DedupeReport {
+    &self.report
+  }
+
+  pub fn into_report(self) -> DedupeReport {
+    self.report
+  }
+}
+
+impl Display for DedupeReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Removed duplicate records: {}", self.removed)?;
+
+    if !self.conflicts.is_empty() {
+      let tx_ids: Vec<String> =
+        self.conflicts.iter().map(u64::to_string).collect();
+      write!(
+        f,
+        "\nDuplicates with conflicting contents, tx ids: {}",
+        tx_ids.join(", ")
+      )?;
+    }
+
+    Ok(())
+  }
+}
+
+fn content_hash(record: &BankRecord) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  record.hash(&mut hasher);
+  hasher.finish()
+}
+
+#[cfg(test)]
+mod dedupe_test {
+  use crate::dedupe::{DedupeKey, Deduplicator};
+  use crate::record::{BankRecord, Status, TxType};
+
+  fn records() -> Vec<BankRecord> {
+    let record = |tx_id: u64, amount: u64| BankRecord {
+      tx_id,
+      tx_type: TxType::Transfer,
+      from_user_id: 1,
+      to_user_id: 2,
+      amount,
+      timestamp: 1633036860000,
+      status: Status::Success,
+      description: String::from("Record"),
+    };
+
+    vec![
+      record(1, 100),
+      record(2, 200),
+      record(1, 100),
+      record(2, 300),
+      record(3, 400),
+    ]
+  }
+
+  fn dedupe(key: DedupeKey) -> (Vec<u64>, Deduplicator) {
+    let mut deduplicator = Deduplicator::new(key);
+    let kept = records()
+      .into_iter()
+      .filter(|record| deduplicator.insert(record))
+      .map(|record| record.amount)
+      .collect();
+
+    (kept, deduplicator)
+  }
+
+  #[test]
+  fn test_dedupe_by_tx_id() {
+    let (kept, deduplicator) = dedupe(DedupeKey::TxId);
+
+    assert_eq!(kept, vec![100, 200, 400]);
+    assert_eq!(deduplicator.report().removed, 2);
+    assert_eq!(
+      deduplicator.into_report().to_string(),
+      "Removed duplicate records: 2\nDuplicates with conflicting contents, tx ids: 2"
+    );
+  }
+
+  #[test]
+  fn test_dedupe_full() {
+    let (kept, deduplicator) = dedupe(DedupeKey::Full);
+
+    assert_eq!(kept, vec![100, 200, 300, 400]);
+    assert_eq!(deduplicator.report().removed, 1);
+    assert_eq!(
+      deduplicator.report().conflicts.iter().collect::<Vec<_>>(),
+      vec![&2]
+    );
+  }
+}
@@ -1,3 +1,4 @@
+use crate::parsers::BIN_RECORD_MAX_SIZE;
 use std::fmt::{Debug, Display, Formatter};
 use std::io;
 use std::num::ParseIntError;
@@ -14,7 +15,27 @@ pub enum ParsingError {
     source: io::Error,
     description: String,
   },
+  Checksum {
+    expected: u32,
+    actual: u32,
+  },
+  RecordLayout {
+    record_size: u32,
+    description_len: u32,
+  },
+  /// Declared record size exceeds `BIN_RECORD_MAX_SIZE`
+  RecordTooLarge {
+    record_size: u32,
+  },
+  /// Stream ended after `read` bytes of a record
+  TruncatedRecord {
+    read: usize,
+  },
+  /// Record which can't be deserialized by a serde data format
+  Deserialize(Box<dyn std::error::Error + Send + Sync>),
   Custom(String),
+  #[cfg(feature = "camt")]
+  Xml(quick_xml::Error),
 }
 
 #[derive(Debug)]
@@ -22,6 +43,69 @@ pub enum SerializeError {
   IO(io::Error),
 }
 
+#[cfg(feature = "filter")]
+#[derive(Debug)]
+pub enum FilterError {
+  UnexpectedToken { position: usize, token: String },
+  UnexpectedEnd,
+  UnknownField(String),
+  InvalidValue { field: String, value: String },
+  UnsupportedOperator { field: String, operator: String },
+  Regex(regex::Error),
+}
+
+#[cfg(feature = "query")]
+#[derive(Debug)]
+pub enum QueryError {
+  Syntax(String),
+  Filter(FilterError),
+  UnknownColumn(String),
+  /// Column of an aggregate query is neither aggregated nor grouped by
+  NotGrouped(String),
+  NotNumeric {
+    function: String,
+    field: String,
+  },
+}
+
+#[cfg(feature = "arrow")]
+#[derive(Debug)]
+pub enum ColumnarError {
+  Arrow(arrow::error::ArrowError),
+  #[cfg(feature = "parquet")]
+  Parquet(parquet::errors::ParquetError),
+  MissingColumn(&'static str),
+  /// Null, out of range or unknown value
+  InvalidValue {
+    column: &'static str,
+    value: String,
+  },
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+pub enum SqliteError {
+  Sqlite(rusqlite::Error),
+  /// Transaction type or status which can't be parsed
+  InvalidValue {
+    column: &'static str,
+    value: String,
+  },
+  /// Record value which does not fit into a signed 64-bit integer
+  OutOfRange {
+    column: &'static str,
+    value: u64,
+    tx_id: u64,
+  },
+}
+
+#[cfg(feature = "transform")]
+#[derive(Debug)]
+pub enum TransformError {
+  InvalidFactor(String),
+  Regex(regex::Error),
+}
+
 #[derive(Debug)]
 pub enum TxTypeError {
   InvalidSting(String),
@@ -50,7 +134,37 @@ impl Display for ParsingError {
       } => {
         write!(f, "{:?}\n {:?}", description, source)
       }
+      Self::Checksum { expected, actual } => {
+        write!(
+          f,
+          "Record checksum mismatch, expected: {:#010x}, actual: {:#010x}",
+          expected, actual
+        )
+      }
+      Self::RecordLayout {
+        record_size,
+        description_len,
+      } => {
+        write!(
+          f,
+          "Record size {} does not match fields layout, description length: {}",
+          record_size, description_len
+        )
+      }
+      Self::RecordTooLarge { record_size } => {
+        write!(
+          f,
+          "Record size {} exceeds the limit of {} bytes",
+          record_size, BIN_RECORD_MAX_SIZE
+        )
+      }
+      Self::TruncatedRecord { read } => {
+        write!(f, "Stream ended after {} bytes of a record", read)
+      }
+      Self::Deserialize(err) => write!(f, "{err}"),
       Self::Custom(str) => write!(f, "{:?}", str),
+      #[cfg(feature = "camt")]
+      Self::Xml(err) => write!(f, "{err}"),
     }
   }
 }
@@ -67,7 +181,20 @@ impl std::error::Error for ParsingError {
         source,
         description: _,
       } => Some(source),
+      Self::Checksum {
+        expected: _,
+        actual: _,
+      } => None,
+      Self::RecordLayout {
+        record_size: _,
+        description_len: _,
+      } => None,
+      Self::RecordTooLarge { record_size: _ } => None,
+      Self::TruncatedRecord { read: _ } => None,
+      Self::Deserialize(err) => Some(err.as_ref()),
       Self::Custom(_str) => None,
+      #[cfg(feature = "camt")]
+      Self::Xml(err) => Some(err),
     }
   }
 }
@@ -102,6 +229,13 @@ impl From<FromUtf8Error> for ParsingError {
   }
 }
 
+#[cfg(feature = "camt")]
+impl From<quick_xml::Error> for ParsingError {
+  fn from(err: quick_xml::Error) -> Self {
+    Self::Xml(err)
+  }
+}
+
 impl Display for TxTypeError {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
@@ -140,17 +274,205 @@ impl From<io::Error> for SerializeError {
   }
 }
 
+#[cfg(feature = "filter")]
+impl Display for FilterError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::UnexpectedToken { position, token } => {
+        write!(f, "Unexpected token {token:?} at position {position}")
+      }
+      Self::UnexpectedEnd => write!(f, "Unexpected end of filter expression"),
+      Self::UnknownField(field) => write!(f, "Unknown record field: {field}"),
+      Self::InvalidValue { field, value } => {
+        write!(f, "Invalid value {value:?} for field {field}")
+      }
+      Self::UnsupportedOperator { field, operator } => {
+        write!(f, "Operator {operator} is not supported for field {field}")
+      }
+      Self::Regex(err) => write!(f, "{:?}", err),
+    }
+  }
+}
+
+#[cfg(feature = "filter")]
+impl std::error::Error for FilterError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Regex(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(feature = "filter")]
+impl From<regex::Error> for FilterError {
+  fn from(err: regex::Error) -> Self {
+    Self::Regex(err)
+  }
+}
+
+#[cfg(feature = "query")]
+impl Display for QueryError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Syntax(message) => write!(f, "Invalid query: {message}"),
+      Self::Filter(err) => write!(f, "Invalid WHERE clause: {err}"),
+      Self::UnknownColumn(column) => {
+        write!(f, "Unknown column {column:?} in ORDER BY clause")
+      }
+      Self::NotGrouped(column) => {
+        write!(
+          f,
+          "Column {column} has to be aggregated or listed in GROUP BY clause"
+        )
+      }
+      Self::NotNumeric { function, field } => {
+        write!(f, "{function} is not supported for field {field}")
+      }
+    }
+  }
+}
+
+#[cfg(feature = "query")]
+impl std::error::Error for QueryError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Filter(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(feature = "query")]
+impl From<FilterError> for QueryError {
+  fn from(err: FilterError) -> Self {
+    Self::Filter(err)
+  }
+}
+
+#[cfg(feature = "arrow")]
+impl Display for ColumnarError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Arrow(err) => write!(f, "{err}"),
+      #[cfg(feature = "parquet")]
+      Self::Parquet(err) => write!(f, "{err}"),
+      Self::MissingColumn(column) => write!(f, "Missing {column} column"),
+      Self::InvalidValue { column, value } => {
+        write!(f, "Invalid {column} value: {value:?}")
+      }
+    }
+  }
+}
+
+#[cfg(feature = "arrow")]
+impl std::error::Error for ColumnarError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Arrow(err) => Some(err),
+      #[cfg(feature = "parquet")]
+      Self::Parquet(err) => Some(err),
+      Self::MissingColumn(_) | Self::InvalidValue { .. } => None,
+    }
+  }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow::error::ArrowError> for ColumnarError {
+  fn from(err: arrow::error::ArrowError) -> Self {
+    Self::Arrow(err)
+  }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for ColumnarError {
+  fn from(err: parquet::errors::ParquetError) -> Self {
+    Self::Parquet(err)
+  }
+}
+
+#[cfg(feature = "sqlite")]
+impl Display for SqliteError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Sqlite(err) => write!(f, "{err}"),
+      Self::InvalidValue { column, value } => {
+        write!(f, "Invalid {column} value: {value:?}")
+      }
+      Self::OutOfRange {
+        column,
+        value,
+        tx_id,
+      } => write!(
+        f,
+        "{column} value {value} of transaction {tx_id} does not fit into a \
+         signed 64-bit integer"
+      ),
+    }
+  }
+}
+
+#[cfg(feature = "sqlite")]
+impl std::error::Error for SqliteError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Sqlite(err) => Some(err),
+      Self::InvalidValue { .. } => None,
+      Self::OutOfRange { .. } => None,
+    }
+  }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for SqliteError {
+  fn from(err: rusqlite::Error) -> Self {
+    Self::Sqlite(err)
+  }
+}
+
+#[cfg(feature = "transform")]
+impl Display for TransformError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::InvalidFactor(factor) => {
+        write!(
+          f,
+          "Invalid scale factor, positive decimal expected: {factor}"
+        )
+      }
+      Self::Regex(err) => write!(f, "{:?}", err),
+    }
+  }
+}
+
+#[cfg(feature = "transform")]
+impl std::error::Error for TransformError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::InvalidFactor(_) => None,
+      Self::Regex(err) => Some(err),
+    }
+  }
+}
+
+#[cfg(feature = "transform")]
+impl From<regex::Error> for TransformError {
+  fn from(err: regex::Error) -> Self {
+    Self::Regex(err)
+  }
+}
+
 impl Display for StatusTypeError {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
       Self::InvalidSting(s) => {
-        write!(f, "Invalid string transaction type: {:?}", s)
+        write!(f, "Invalid string status: {:?}", s)
       }
       Self::InvalidNumber(n) => {
-        write!(f, "Invalid number transaction type: {:?}", n)
+        write!(f, "Invalid number status: {:?}", n)
       }
       Self::NotFound => {
-        write!(f, "Type option does not exist")
+        write!(f, "Status option does not exist")
       }
     }
   }
@@ -0,0 +1,595 @@
+//! Record filters and a small expression language for them, e.g.
+//! `status == FAILURE && amount > 1000`.
+//!
+//! Expressions compare record fields with values using `==`, `!=`, `>`,
+//! `>=`, `<`, `<=`, descriptions can be checked with `contains` and
+//! `matches` (regular expression). Comparisons are combined with `&&`, `||`,
+//! `!` or their `AND`, `OR`, `NOT` keyword forms and grouped with
+//! parentheses. Field names are case-insensitive and match report column
+//! names, text values may be quoted with `"` or `'`.
+
+use crate::constants::record_field;
+use crate::errors::FilterError;
+use crate::record::{BankRecord, Status, TxType};
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+  TxId,
+  TxType,
+  FromUserId,
+  ToUserId,
+  Amount,
+  Timestamp,
+  Status,
+  Description,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+  Number(u64),
+  TxType(TxType),
+  Status(Status),
+  Text(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Filter {
+  /// Matches if all filters match, empty list matches every record
+  All(Vec<Filter>),
+  /// Matches if any of filters matches
+  Any(Vec<Filter>),
+  Not(Box<Filter>),
+  Compare {
+    field: Field,
+    op: CmpOp,
+    value: Value,
+  },
+  DescriptionContains(String),
+  DescriptionMatches(Regex),
+}
+
+impl Field {
+  pub const ALL: [Field; 8] = [
+    Field::TxId,
+    Field::TxType,
+    Field::FromUserId,
+    Field::ToUserId,
+    Field::Amount,
+    Field::Timestamp,
+    Field::Status,
+    Field::Description,
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Field::TxId => record_field::TX_ID,
+      Field::TxType => record_field::TX_TYPE,
+      Field::FromUserId => record_field::FROM_USER_ID,
+      Field::ToUserId => record_field::TO_USER_ID,
+      Field::Amount => record_field::AMOUNT,
+      Field::Timestamp => record_field::TIMESTAMP,
+      Field::Status => record_field::STATUS,
+      Field::Description => record_field::DESCRIPTION,
+    }
+  }
+
+  /// Value of a numeric field, `None` for other fields
+  pub fn number(&self, record: &BankRecord) -> Option<u64> {
+    match self {
+      Field::TxId => Some(record.tx_id),
+      Field::FromUserId => Some(record.from_user_id),
+      Field::ToUserId => Some(record.to_user_id),
+      Field::Amount => Some(record.amount),
+      Field::Timestamp => Some(record.timestamp),
+      Field::TxType | Field::Status | Field::Description => None,
+    }
+  }
+}
+
+impl FromStr for Field {
+  type Err = FilterError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Field::ALL
+      .into_iter()
+      .find(|field| field.name().eq_ignore_ascii_case(s))
+      .ok_or_else(|| FilterError::UnknownField(s.to_string()))
+  }
+}
+
+impl Display for Field {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl CmpOp {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CmpOp::Eq => "==",
+      CmpOp::Ne => "!=",
+      CmpOp::Lt => "<",
+      CmpOp::Le => "<=",
+      CmpOp::Gt => ">",
+      CmpOp::Ge => ">=",
+    }
+  }
+
+  pub fn apply<T: PartialOrd + ?Sized>(&self, left: &T, right: &T) -> bool {
+    match self {
+      CmpOp::Eq => left == right,
+      CmpOp::Ne => left != right,
+      CmpOp::Lt => left < right,
+      CmpOp::Le => left <= right,
+      CmpOp::Gt => left > right,
+      CmpOp::Ge => left >= right,
+    }
+  }
+
+  fn from_token(token: &str) -> Option<Self> {
+    match token {
+      "==" | "=" => Some(CmpOp::Eq),
+      "!=" | "<>" => Some(CmpOp::Ne),
+      "<" => Some(CmpOp::Lt),
+      "<=" => Some(CmpOp::Le),
+      ">" => Some(CmpOp::Gt),
+      ">=" => Some(CmpOp::Ge),
+      _ => None,
+    }
+  }
+}
+
+impl Value {
+  /// Parses value in the type of the field
+  pub fn parse(field: Field, value: &str) -> Result<Self, FilterError> {
+    let invalid = || FilterError::InvalidValue {
+      field: field.to_string(),
+      value: value.to_string(),
+    };
+
+    match field {
+      Field::TxType => TxType::from_str(&value.to_ascii_uppercase())
+        .map(Value::TxType)
+        .map_err(|_| invalid()),
+      Field::Status => Status::from_str(&value.to_ascii_uppercase())
+        .map(Value::Status)
+        .map_err(|_| invalid()),
+      Field::Description => Ok(Value::Text(value.to_string())),
+      _ => value
+        .parse::<u64>()
+        .map(Value::Number)
+        .map_err(|_| invalid()),
+    }
+  }
+}
+
+impl Default for Filter {
+  fn default() -> Self {
+    Filter::All(vec![])
+  }
+}
+
+impl Filter {
+  /// Comparison of the field with a value, only `==` and `!=` are
+  /// supported for transaction types and statuses
+  pub fn compare(
+    field: Field,
+    op: CmpOp,
+    value: &str,
+  ) -> Result<Self, FilterError> {
+    if matches!(field, Field::TxType | Field::Status)
+      && !matches!(op, CmpOp::Eq | CmpOp::Ne)
+    {
+      return Err(FilterError::UnsupportedOperator {
+        field: field.to_string(),
+        operator: op.as_str().to_string(),
+      });
+    }
+
+    Ok(Filter::Compare {
+      field,
+      op,
+      value: Value::parse(field, value)?,
+    })
+  }
+
+  pub fn description_contains(text: &str) -> Self {
+    Filter::DescriptionContains(text.to_string())
+  }
+
+  pub fn description_matches(pattern: &str) -> Result<Self, FilterError> {
+    Ok(Filter::DescriptionMatches(Regex::new(pattern)?))
+  }
+
+  /// Combines filters, so both have to match
+  pub fn and(self, other: Filter) -> Self {
+    match (self, other) {
+      (Filter::All(mut filters), Filter::All(other)) => {
+        filters.extend(other);
+        Filter::All(filters)
+      }
+      (Filter::All(mut filters), other) => {
+        filters.push(other);
+        Filter::All(filters)
+      }
+      (filter, other) => Filter::All(vec![filter, other]),
+    }
+  }
+
+  pub fn matches(&self, record: &BankRecord) -> bool {
+    match self {
+      Filter::All(filters) => filters.iter().all(|f| f.matches(record)),
+      Filter::Any(filters) => filters.iter().any(|f| f.matches(record)),
+      Filter::Not(filter) => !filter.matches(record),
+      Filter::Compare { field, op, value } => match (field, value) {
+        (Field::TxType, Value::TxType(tx_type)) => {
+          op.apply(&(record.tx_type == *tx_type), &true)
+        }
+        (Field::Status, Value::Status(status)) => {
+          op.apply(&(record.status == *status), &true)
+        }
+        (Field::Description, Value::Text(text)) => {
+          op.apply(record.description.as_str(), text.as_str())
+        }
+        (field, Value::Number(number)) => field
+          .number(record)
+          .is_some_and(|field_value| op.apply(&field_value, number)),
+        _ => false,
+      },
+      Filter::DescriptionContains(text) => record.description.contains(text),
+      Filter::DescriptionMatches(regex) => regex.is_match(&record.description),
+    }
+  }
+}
+
+impl FromStr for Filter {
+  type Err = FilterError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut parser = ExpressionParser {
+      tokens: tokenize(s)?,
+      position: 0,
+    };
+
+    let filter = parser.parse_or()?;
+
+    match parser.tokens.get(parser.position) {
+      Some((position, token)) => Err(FilterError::UnexpectedToken {
+        position: *position,
+        token: token.to_string(),
+      }),
+      None => Ok(filter),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Ident(String),
+  Number(String),
+  Text(String),
+  Op(&'static str),
+}
+
+impl Display for Token {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Token::Ident(value) | Token::Number(value) => write!(f, "{value}"),
+      Token::Text(value) => write!(f, "{value:?}"),
+      Token::Op(op) => write!(f, "{op}"),
+    }
+  }
+}
+
+/// Longer operators go first, so they are not split
+const OPERATORS: &[&str] = &[
+  "==", "!=", "<>", ">=", "<=", "&&", "||", "=", ">", "<", "!", "(", ")",
+];
+
+/// Splits expression into tokens with their positions
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+  let mut tokens = vec![];
+  let mut chars = input.char_indices().peekable();
+
+  while let Some(&(position, char)) = chars.peek() {
+    if char.is_whitespace() {
+      chars.next();
+    } else if char == '"' || char == '\'' {
+      chars.next();
+      let mut text = String::new();
+
+      loop {
+        match chars.next() {
+          Some((_, '\\')) => {
+            text.extend(chars.next().map(|(_, escaped)| escaped));
+          }
+          Some((_, next)) if next == char => break,
+          Some((_, next)) => text.push(next),
+          None => return Err(FilterError::UnexpectedEnd),
+        }
+      }
+
+      tokens.push((position, Token::Text(text)));
+    } else if char.is_ascii_alphanumeric() || char == '_' {
+      let mut word = String::new();
+
+      while let Some(&(_, next)) = chars.peek()
+        && (next.is_ascii_alphanumeric() || next == '_')
+      {
+        word.push(next);
+        chars.next();
+      }
+
+      if word.bytes().all(|byte| byte.is_ascii_digit()) {
+        tokens.push((position, Token::Number(word)));
+      } else {
+        tokens.push((position, Token::Ident(word)));
+      }
+    } else {
+      let op = OPERATORS
+        .iter()
+        .find(|op| input[position..].starts_with(**op))
+        .ok_or_else(|| FilterError::UnexpectedToken {
+          position,
+          token: char.to_string(),
+        })?;
+
+      for _ in 0..op.len() {
+        chars.next();
+      }
+
+      tokens.push((position, Token::Op(op)));
+    }
+  }
+
+  Ok(tokens)
+}
+
+/// Recursive descent parser, `||` has lower precedence than `&&`
+struct ExpressionParser {
+  tokens: Vec<(usize, Token)>,
+  position: usize,
+}
+
+impl ExpressionParser {
+  fn next(&mut self) -> Result<(usize, Token), FilterError> {
+    let token = self
+      .tokens
+      .get(self.position)
+      .cloned()
+      .ok_or(FilterError::UnexpectedEnd)?;
+    self.position += 1;
+
+    Ok(token)
+  }
+
+  /// Consumes the next token if it is the operator or the keyword
+  fn eat(&mut self, op: &str, keyword: &str) -> bool {
+    let matched = match self.tokens.get(self.position) {
+      Some((_, Token::Op(token))) => *token == op,
+      Some((_, Token::Ident(word))) => word.eq_ignore_ascii_case(keyword),
+      _ => false,
+    };
+
+    if matched {
+      self.position += 1;
+    }
+
+    matched
+  }
+
+  fn parse_or(&mut self) -> Result<Filter, FilterError> {
+    let mut filters = vec![self.parse_and()?];
+
+    while self.eat("||", "or") {
+      filters.push(self.parse_and()?);
+    }
+
+    Ok(match filters.len() {
+      1 => filters.remove(0),
+      _ => Filter::Any(filters),
+    })
+  }
+
+  fn parse_and(&mut self) -> Result<Filter, FilterError> {
+    let mut filters = vec![self.parse_unary()?];
+
+    while self.eat("&&", "and") {
+      filters.push(self.parse_unary()?);
+    }
+
+    Ok(match filters.len() {
+      1 => filters.remove(0),
+      _ => Filter::All(filters),
+    })
+  }
+
+  fn parse_unary(&mut self) -> Result<Filter, FilterError> {
+    if self.eat("!", "not") {
+      return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+    }
+
+    if self.eat("(", "") {
+      let filter = self.parse_or()?;
+
+      return match self.next()? {
+        (_, Token::Op(")")) => Ok(filter),
+        (position, token) => Err(FilterError::UnexpectedToken {
+          position,
+          token: token.to_string(),
+        }),
+      };
+    }
+
+    self.parse_comparison()
+  }
+
+  fn parse_comparison(&mut self) -> Result<Filter, FilterError> {
+    let field = match self.next()? {
+      (_, Token::Ident(name)) => Field::from_str(&name)?,
+      (position, token) => {
+        return Err(FilterError::UnexpectedToken {
+          position,
+          token: token.to_string(),
+        });
+      }
+    };
+    let (op_position, op_token) = self.next()?;
+    let (value_position, value_token) = self.next()?;
+
+    let value = match value_token {
+      Token::Ident(value) | Token::Number(value) | Token::Text(value) => value,
+      token => {
+        return Err(FilterError::UnexpectedToken {
+          position: value_position,
+          token: token.to_string(),
+        });
+      }
+    };
+
+    match op_token {
+      Token::Op(op) if CmpOp::from_token(op).is_some() => {
+        Filter::compare(field, CmpOp::from_token(op).unwrap(), &value)
+      }
+      Token::Ident(keyword) if field == Field::Description => {
+        match keyword.to_ascii_lowercase().as_str() {
+          "contains" => Ok(Filter::description_contains(&value)),
+          "matches" => Filter::description_matches(&value),
+          _ => Err(FilterError::UnexpectedToken {
+            position: op_position,
+            token: keyword,
+          }),
+        }
+      }
+      Token::Ident(keyword)
+        if ["contains", "matches"]
+          .iter()
+          .any(|op| keyword.eq_ignore_ascii_case(op)) =>
+      {
+        Err(FilterError::UnsupportedOperator {
+          field: field.to_string(),
+          operator: keyword,
+        })
+      }
+      token => Err(FilterError::UnexpectedToken {
+        position: op_position,
+        token: token.to_string(),
+      }),
+    }
+  }
+}
+
+#[cfg(test)]
+mod filter_test {
+  use crate::errors::FilterError;
+  use crate::filter::{CmpOp, Field, Filter};
+  use crate::record::{BankRecord, Status, TxType};
+  use std::str::FromStr;
+
+  fn record() -> BankRecord {
+    BankRecord {
+      tx_id: 1000000000000000,
+      tx_type: TxType::Transfer,
+      from_user_id: 10,
+      to_user_id: 20,
+      amount: 1500,
+      timestamp: 1633036860000,
+      status: Status::Failure,
+      description: String::from("Record number 1"),
+    }
+  }
+
+  fn matches(expression: &str) -> bool {
+    Filter::from_str(expression).unwrap().matches(&record())
+  }
+
+  #[test]
+  fn test_compare_fields() {
+    assert!(matches("status == FAILURE && amount > 1000"));
+    assert!(matches("tx_type != deposit"));
+    assert!(matches("TIMESTAMP >= 1633036860000"));
+    assert!(matches("from_user_id = 10 AND to_user_id <= 20"));
+    assert!(!matches("amount < 1500"));
+    assert!(!matches("status == SUCCESS"));
+    assert!(matches("description == \"Record number 1\""));
+  }
+
+  #[test]
+  fn test_logical_operators() {
+    assert!(matches("status == SUCCESS || amount > 1000"));
+    assert!(matches("!(status == SUCCESS)"));
+    assert!(matches("NOT tx_id == 1"));
+    // `&&` has higher precedence than `||`
+    assert!(matches("amount > 1000 || status == SUCCESS && amount < 10"));
+    assert!(!matches(
+      "(amount > 1000 || status == SUCCESS) && amount < 10"
+    ));
+  }
+
+  #[test]
+  fn test_description_operators() {
+    assert!(matches("description contains 'number'"));
+    assert!(matches("description MATCHES \"^Record number \\\\d+$\""));
+    assert!(!matches("description contains \"Deposit\""));
+  }
+
+  #[test]
+  fn test_invalid_expressions() {
+    assert!(matches!(
+      Filter::from_str("state == FAILURE"),
+      Err(FilterError::UnknownField(_))
+    ));
+    assert!(matches!(
+      Filter::from_str("status > FAILURE"),
+      Err(FilterError::UnsupportedOperator { .. })
+    ));
+    assert!(matches!(
+      Filter::from_str("amount > many"),
+      Err(FilterError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+      Filter::from_str("amount > 1 status == FAILURE"),
+      Err(FilterError::UnexpectedToken { position: 11, .. })
+    ));
+    assert!(matches!(
+      Filter::from_str("(amount > 1"),
+      Err(FilterError::UnexpectedEnd)
+    ));
+    assert!(matches!(
+      Filter::from_str("amount contains 1"),
+      Err(FilterError::UnsupportedOperator { .. })
+    ));
+    assert!(matches!(
+      Filter::from_str("description matches '('"),
+      Err(FilterError::Regex(_))
+    ));
+  }
+
+  #[test]
+  fn test_combine_filters() {
+    let filter = Filter::default()
+      .and(Filter::compare(Field::Amount, CmpOp::Ge, "1000").unwrap())
+      .and(Filter::description_contains("number"));
+
+    assert!(Filter::default().matches(&record()));
+    assert!(filter.matches(&record()));
+    assert!(
+      !filter
+        .and(Filter::from_str("tx_id < 5").unwrap())
+        .matches(&record())
+    );
+  }
+}
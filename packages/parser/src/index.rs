@@ -0,0 +1,454 @@
+use crate::errors::{ParsingError, SerializeError};
+use crate::parsers::{BinRecord, BinRecoveryReader};
+use crate::record::{BankRecord, BankRecordParser};
+use std::io;
+use std::io::{BufRead, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::ops::{Bound, RangeBounds};
+
+pub static BIN_INDEX_HEADER: &[u8; 4] = b"YPBI";
+pub const BIN_INDEX_VERSION: u8 = 1;
+
+const INDEX_ENTRY_SIZE: usize = 24;
+
+/// Position of a single binary record in a report file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IndexEntry {
+  pub tx_id: u64,
+  pub timestamp: u64,
+  /// Offset of the record header from the start of the report file
+  pub offset: u64,
+}
+
+/// Sidecar index of a binary report, maps `tx_id` and `timestamp` of every
+/// intact record to its byte offset
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BinIndex {
+  // Sorted by tx_id
+  entries: Vec<IndexEntry>,
+  // Positions of entries sorted by timestamp
+  by_timestamp: Vec<usize>,
+}
+
+/// Difference between an index and the report it was built for
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct IndexVerification {
+  /// Records of the report which are not indexed
+  pub missing: Vec<IndexEntry>,
+  /// Index entries which do not point to a matching record
+  pub stale: Vec<IndexEntry>,
+}
+
+impl IndexVerification {
+  pub fn is_valid(&self) -> bool {
+    self.missing.is_empty() && self.stale.is_empty()
+  }
+}
+
+impl BinIndex {
+  pub fn from_entries(mut entries: Vec<IndexEntry>) -> Self {
+    entries.sort();
+
+    let mut by_timestamp: Vec<usize> = (0..entries.len()).collect();
+    by_timestamp
+      .sort_by_key(|&pos| (entries[pos].timestamp, entries[pos].offset));
+
+    Self {
+      entries,
+      by_timestamp,
+    }
+  }
+
+  /// Scans the binary report and indexes every intact record, damaged spans
+  /// of the report are skipped
+  pub fn build<R: BufRead>(reader: R) -> Result<Self, ParsingError> {
+    let mut recovery_reader = BinRecoveryReader::new(reader);
+    let mut entries = vec![];
+
+    while let Some(record) = recovery_reader.read_record()? {
+      entries.push(IndexEntry {
+        tx_id: record.tx_id,
+        timestamp: record.timestamp,
+        offset: recovery_reader.record_offset(),
+      });
+    }
+
+    Ok(Self::from_entries(entries))
+  }
+
+  /// Compares the index with a fresh scan of the binary report
+  pub fn verify<R: BufRead>(
+    &self,
+    reader: R,
+  ) -> Result<IndexVerification, ParsingError> {
+    let actual = Self::build(reader)?;
+
+    Ok(IndexVerification {
+      missing: difference(&actual.entries, &self.entries),
+      stale: difference(&self.entries, &actual.entries),
+    })
+  }
+
+  /// Entries sorted by `tx_id`
+  pub fn entries(&self) -> &[IndexEntry] {
+    &self.entries
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// First indexed record with the given `tx_id`
+  pub fn find(&self, tx_id: u64) -> Option<&IndexEntry> {
+    self.range_by_tx_id(tx_id..=tx_id).next()
+  }
+
+  /// Entries with `tx_id` in range, ordered by `tx_id`
+  pub fn range_by_tx_id(
+    &self,
+    range: impl RangeBounds<u64>,
+  ) -> impl Iterator<Item = &IndexEntry> {
+    let (start, end) =
+      bounds_to_positions(&self.entries, range, |entry| entry.tx_id);
+
+    self.entries[start..end].iter()
+  }
+
+  /// Entries with `timestamp` in range, ordered by `timestamp`
+  pub fn range_by_timestamp(
+    &self,
+    range: impl RangeBounds<u64>,
+  ) -> impl Iterator<Item = &IndexEntry> {
+    let (start, end) = bounds_to_positions(&self.by_timestamp, range, |&pos| {
+      self.entries[pos].timestamp
+    });
+
+    self.by_timestamp[start..end]
+      .iter()
+      .map(|&pos| &self.entries[pos])
+  }
+
+  pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, ParsingError> {
+    let mut header = [0u8; 4];
+    reader.read_exact(&mut header)?;
+
+    if header != *BIN_INDEX_HEADER {
+      return Err(ParsingError::Custom(
+        "Invalid index file header".to_string(),
+      ));
+    }
+
+    let mut version = [0u8; 1];
+    reader.read_exact(&mut version)?;
+
+    if version[0] != BIN_INDEX_VERSION {
+      return Err(ParsingError::Custom(format!(
+        "Unsupported index version: {}",
+        version[0]
+      )));
+    }
+
+    let mut entries_count = [0u8; 8];
+    reader.read_exact(&mut entries_count)?;
+
+    let entries_count = u64::from_be_bytes(entries_count);
+    let mut entries = vec![];
+    let mut entry_buf = [0u8; INDEX_ENTRY_SIZE];
+
+    for _ in 0..entries_count {
+      reader.read_exact(&mut entry_buf)?;
+
+      entries.push(IndexEntry {
+        tx_id: u64::from_be_bytes(entry_buf[0..8].try_into().unwrap()),
+        timestamp: u64::from_be_bytes(entry_buf[8..16].try_into().unwrap()),
+        offset: u64::from_be_bytes(entry_buf[16..24].try_into().unwrap()),
+      });
+    }
+
+    Ok(Self::from_entries(entries))
+  }
+
+  pub fn write_to<W: Write>(
+    &self,
+    writer: &mut W,
+  ) -> Result<(), SerializeError> {
+    writer.write_all(BIN_INDEX_HEADER)?;
+    writer.write_all(&[BIN_INDEX_VERSION])?;
+    writer.write_all(&(self.entries.len() as u64).to_be_bytes())?;
+
+    for entry in &self.entries {
+      writer.write_all(&entry.tx_id.to_be_bytes())?;
+      writer.write_all(&entry.timestamp.to_be_bytes())?;
+      writer.write_all(&entry.offset.to_be_bytes())?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Binary report reader which seeks directly to indexed records
+pub struct IndexedBinReader<R> {
+  reader: R,
+  index: BinIndex,
+}
+
+/// Records read in order of the index entries they were looked up by
+pub struct IndexedRecords<'a, R> {
+  reader: &'a mut R,
+  entries: std::vec::IntoIter<IndexEntry>,
+}
+
+impl<R: BufRead + Seek> IndexedBinReader<R> {
+  pub fn new(reader: R, index: BinIndex) -> Self {
+    Self { reader, index }
+  }
+
+  pub fn index(&self) -> &BinIndex {
+    &self.index
+  }
+
+  /// Reads the record with the given `tx_id`, `None` if it is not indexed
+  pub fn get(
+    &mut self,
+    tx_id: u64,
+  ) -> Result<Option<BankRecord>, ParsingError> {
+    match self.index.find(tx_id).copied() {
+      Some(entry) => read_entry(&mut self.reader, &entry).map(Some),
+      None => Ok(None),
+    }
+  }
+
+  pub fn range_by_tx_id(
+    &mut self,
+    range: impl RangeBounds<u64>,
+  ) -> IndexedRecords<'_, R> {
+    let entries: Vec<IndexEntry> =
+      self.index.range_by_tx_id(range).copied().collect();
+
+    IndexedRecords {
+      reader: &mut self.reader,
+      entries: entries.into_iter(),
+    }
+  }
+
+  pub fn range_by_timestamp(
+    &mut self,
+    range: impl RangeBounds<u64>,
+  ) -> IndexedRecords<'_, R> {
+    let entries: Vec<IndexEntry> =
+      self.index.range_by_timestamp(range).copied().collect();
+
+    IndexedRecords {
+      reader: &mut self.reader,
+      entries: entries.into_iter(),
+    }
+  }
+
+  pub fn into_inner(self) -> R {
+    self.reader
+  }
+}
+
+impl<R: BufRead + Seek> Iterator for IndexedRecords<'_, R> {
+  type Item = Result<BankRecord, ParsingError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let entry = self.entries.next()?;
+
+    Some(read_entry(self.reader, &entry))
+  }
+}
+
+fn read_entry<R: BufRead + Seek>(
+  reader: &mut R,
+  entry: &IndexEntry,
+) -> Result<BankRecord, ParsingError> {
+  reader.seek(SeekFrom::Start(entry.offset))?;
+
+  let record = BinRecord::from_read(reader)?;
+
+  if record.tx_id != entry.tx_id {
+    return Err(ParsingError::IO(io::Error::new(
+      ErrorKind::InvalidData,
+      format!(
+        "Stale index entry, record at offset {} has tx_id {} instead of {}",
+        entry.offset, record.tx_id, entry.tx_id
+      ),
+    )));
+  }
+
+  Ok(record)
+}
+
+/// Positions of the slice, sorted by `key`, which keys fit into range
+fn bounds_to_positions<T>(
+  items: &[T],
+  range: impl RangeBounds<u64>,
+  key: impl Fn(&T) -> u64,
+) -> (usize, usize) {
+  let start = match range.start_bound() {
+    Bound::Included(&value) => items.partition_point(|item| key(item) < value),
+    Bound::Excluded(&value) => items.partition_point(|item| key(item) <= value),
+    Bound::Unbounded => 0,
+  };
+  let end = match range.end_bound() {
+    Bound::Included(&value) => items.partition_point(|item| key(item) <= value),
+    Bound::Excluded(&value) => items.partition_point(|item| key(item) < value),
+    Bound::Unbounded => items.len(),
+  };
+
+  (start, end.max(start))
+}
+
+/// Entries of the sorted `left` slice which are absent in sorted `right` one
+fn difference(left: &[IndexEntry], right: &[IndexEntry]) -> Vec<IndexEntry> {
+  left
+    .iter()
+    .filter(|entry| right.binary_search(entry).is_err())
+    .copied()
+    .collect()
+}
+
+#[cfg(test)]
+mod index_test {
+  use crate::index::{BinIndex, IndexEntry, IndexedBinReader};
+  use crate::parsers::BinRecord;
+  use crate::record::{BankRecord, BankRecordParser};
+  use std::io::Cursor;
+
+  fn records() -> Vec<BankRecord> {
+    [(3, 300), (1, 200), (2, 100), (5, 400)]
+      .into_iter()
+      .map(|(tx_id, timestamp)| BankRecord {
+        tx_id,
+        timestamp,
+        description: format!("Record number {tx_id}"),
+        ..BankRecord::new()
+      })
+      .collect()
+  }
+
+  fn serialize(records: Vec<BankRecord>) -> Vec<u8> {
+    let mut buff: Vec<u8> = vec![];
+
+    for record in records {
+      BinRecord(record).write_to(&mut buff).unwrap();
+    }
+
+    buff
+  }
+
+  #[test]
+  fn test_build_index() {
+    let data = serialize(records());
+    let record_len = data.len() as u64 / 4;
+    let index = BinIndex::build(Cursor::new(data)).unwrap();
+
+    let tx_ids: Vec<u64> = index.entries().iter().map(|e| e.tx_id).collect();
+
+    assert_eq!(tx_ids, vec![1, 2, 3, 5]);
+    assert_eq!(
+      index.find(2),
+      Some(&IndexEntry {
+        tx_id: 2,
+        timestamp: 100,
+        offset: record_len * 2
+      })
+    );
+    assert_eq!(index.find(4), None);
+  }
+
+  #[test]
+  fn test_index_ranges() {
+    let index = BinIndex::build(Cursor::new(serialize(records()))).unwrap();
+
+    let tx_ids: Vec<u64> =
+      index.range_by_tx_id(2..5).map(|e| e.tx_id).collect();
+    assert_eq!(tx_ids, vec![2, 3]);
+
+    let tx_ids: Vec<u64> =
+      index.range_by_timestamp(150..).map(|e| e.tx_id).collect();
+    assert_eq!(tx_ids, vec![1, 3, 5]);
+
+    let tx_ids: Vec<u64> =
+      index.range_by_timestamp(..=100).map(|e| e.tx_id).collect();
+    assert_eq!(tx_ids, vec![2]);
+
+    assert_eq!(index.range_by_tx_id(6..).count(), 0);
+  }
+
+  #[test]
+  fn test_index_round_trip() {
+    let index = BinIndex::build(Cursor::new(serialize(records()))).unwrap();
+    let mut buff: Vec<u8> = vec![];
+
+    index.write_to(&mut buff).unwrap();
+
+    let read_index = BinIndex::read_from(&mut Cursor::new(buff)).unwrap();
+
+    assert_eq!(read_index, index);
+  }
+
+  #[test]
+  fn test_read_invalid_index() {
+    let result = BinIndex::read_from(&mut Cursor::new(b"YPBN".to_vec()));
+
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_verify_index() {
+    let data = serialize(records());
+    let index = BinIndex::build(Cursor::new(data.clone())).unwrap();
+
+    assert!(index.verify(Cursor::new(data)).unwrap().is_valid());
+
+    let mut changed_records = records();
+    changed_records.remove(0);
+    changed_records.push(BankRecord {
+      tx_id: 7,
+      ..BankRecord::new()
+    });
+
+    let verification = index
+      .verify(Cursor::new(serialize(changed_records)))
+      .unwrap();
+
+    assert!(!verification.is_valid());
+    assert_eq!(verification.missing.len(), 4);
+    assert_eq!(verification.stale.len(), 4);
+  }
+
+  #[test]
+  fn test_indexed_reader() {
+    let data = serialize(records());
+    let index = BinIndex::build(Cursor::new(data.clone())).unwrap();
+    let mut reader = IndexedBinReader::new(Cursor::new(data), index);
+
+    let record = reader.get(5).unwrap().unwrap();
+    assert_eq!(record.description, "Record number 5");
+    assert!(reader.get(4).unwrap().is_none());
+
+    let tx_ids: Vec<u64> = reader
+      .range_by_timestamp(100..=300)
+      .map(|record| record.unwrap().tx_id)
+      .collect();
+    assert_eq!(tx_ids, vec![2, 1, 3]);
+  }
+
+  #[test]
+  fn test_indexed_reader_stale_entry() {
+    let index = BinIndex::from_entries(vec![IndexEntry {
+      tx_id: 2,
+      timestamp: 0,
+      offset: 0,
+    }]);
+    let mut reader =
+      IndexedBinReader::new(Cursor::new(serialize(records())), index);
+
+    assert!(reader.get(2).is_err());
+  }
+}
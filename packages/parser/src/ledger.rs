@@ -0,0 +1,275 @@
+//! Account balances of users, computed by replaying successful transactions.
+//!
+//! Deposits credit the receiver, withdrawals debit the sender and transfers
+//! move the amount between users. User id `0` stands for the bank, its
+//! balance is not tracked. Records have to be applied in timestamp order,
+//! otherwise overdrafts are detected at wrong moments.
+
+use crate::errors::ParsingError;
+use crate::record::{BankRecord, Status, TxType};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::{BufRead, Write};
+
+/// Bank account id, money enters and leaves the ledger through it
+pub const BANK_USER_ID: u64 = 0;
+
+pub const BALANCES_HEADER: &str = "USER_ID,BALANCE";
+
+/// Balances by user id
+pub type Balances = BTreeMap<u64, i128>;
+
+/// Transaction which can't be applied to the ledger or leaves it in a
+/// suspicious state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LedgerIssue {
+  /// Transaction took more money than the user had, it is applied anyway
+  Overdraft {
+    tx_id: u64,
+    user_id: u64,
+    /// Balance after the transaction
+    balance: i128,
+  },
+  /// Transaction accounts don't match its type, it is not applied
+  Impossible { tx_id: u64, reason: &'static str },
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Ledger {
+  balances: Balances,
+  issues: Vec<LedgerIssue>,
+}
+
+impl Display for LedgerIssue {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Overdraft {
+        tx_id,
+        user_id,
+        balance,
+      } => write!(
+        f,
+        "Transaction {tx_id}: overdraft of user {user_id}, balance {balance}"
+      ),
+      Self::Impossible { tx_id, reason } => {
+        write!(f, "Transaction {tx_id} is not applied: {reason}")
+      }
+    }
+  }
+}
+
+impl Ledger {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_opening_balances(balances: Balances) -> Self {
+    Self {
+      balances,
+      issues: vec![],
+    }
+  }
+
+  /// Applies a successful transaction, pending and failed ones don't move
+  /// money and are skipped
+  pub fn apply(&mut self, record: &BankRecord) {
+    if record.status != Status::Success {
+      return;
+    }
+
+    let (from, to) = (record.from_user_id, record.to_user_id);
+    let impossible = match record.tx_type {
+      TxType::Deposit if to == BANK_USER_ID => Some("deposit has no receiver"),
+      TxType::Withdrawal if from == BANK_USER_ID => {
+        Some("withdrawal has no sender")
+      }
+      TxType::Transfer if from == BANK_USER_ID || to == BANK_USER_ID => {
+        Some("transfer has to be made between user accounts")
+      }
+      TxType::Transfer if from == to => Some("transfer to the same user"),
+      _ => None,
+    };
+
+    if let Some(reason) = impossible {
+      self.issues.push(LedgerIssue::Impossible {
+        tx_id: record.tx_id,
+        reason,
+      });
+      return;
+    }
+
+    let amount = record.amount as i128;
+    match record.tx_type {
+      TxType::Deposit => self.credit(to, amount),
+      TxType::Withdrawal => self.debit(record.tx_id, from, amount),
+      TxType::Transfer => {
+        self.debit(record.tx_id, from, amount);
+        self.credit(to, amount);
+      }
+    }
+  }
+
+  pub fn balance(&self, user_id: u64) -> i128 {
+    self.balances.get(&user_id).copied().unwrap_or_default()
+  }
+
+  pub fn balances(&self) -> &Balances {
+    &self.balances
+  }
+
+  pub fn issues(&self) -> &[LedgerIssue] {
+    &self.issues
+  }
+
+  fn credit(&mut self, user_id: u64, amount: i128) {
+    *self.balances.entry(user_id).or_default() += amount;
+  }
+
+  fn debit(&mut self, tx_id: u64, user_id: u64, amount: i128) {
+    let balance = self.balances.entry(user_id).or_default();
+    *balance -= amount;
+
+    if *balance < 0 {
+      self.issues.push(LedgerIssue::Overdraft {
+        tx_id,
+        user_id,
+        balance: *balance,
+      });
+    }
+  }
+}
+
+/// Reads `USER_ID,BALANCE` lines, headers line is optional. Balance sheets
+/// written by `write_balances` can be read back as opening balances.
+pub fn read_balances<R: BufRead>(reader: R) -> Result<Balances, ParsingError> {
+  let mut balances = Balances::new();
+
+  for (index, line) in reader.lines().enumerate() {
+    let line = line?;
+    let line = line.trim();
+
+    if line.is_empty() || (index == 0 && line == BALANCES_HEADER) {
+      continue;
+    }
+
+    let invalid = || {
+      ParsingError::Custom(format!(
+        "Invalid balance line {}: {line}",
+        index + 1
+      ))
+    };
+    let (user_id, balance) = line.split_once(',').ok_or_else(invalid)?;
+    let user_id = user_id.trim().parse::<u64>().map_err(|_| invalid())?;
+    let balance = balance.trim().parse::<i128>().map_err(|_| invalid())?;
+
+    if balances.insert(user_id, balance).is_some() {
+      return Err(ParsingError::Custom(format!(
+        "Duplicate balance of user {user_id}"
+      )));
+    }
+  }
+
+  Ok(balances)
+}
+
+/// Writes `USER_ID,BALANCE` lines ordered by user id
+pub fn write_balances<W: Write>(
+  writer: &mut W,
+  balances: &Balances,
+) -> io::Result<()> {
+  writeln!(writer, "{BALANCES_HEADER}")?;
+  for (user_id, balance) in balances {
+    writeln!(writer, "{user_id},{balance}")?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod ledger_test {
+  use crate::errors::ParsingError;
+  use crate::ledger::{
+    Balances, Ledger, LedgerIssue, read_balances, write_balances,
+  };
+  use crate::record::{BankRecord, Status, TxType};
+  use std::io::Cursor;
+
+  fn record(
+    tx_id: u64,
+    tx_type: TxType,
+    from_user_id: u64,
+    to_user_id: u64,
+    amount: u64,
+    status: Status,
+  ) -> BankRecord {
+    BankRecord {
+      tx_id,
+      tx_type,
+      from_user_id,
+      to_user_id,
+      amount,
+      timestamp: 1633036860000 + tx_id,
+      status,
+      description: String::new(),
+    }
+  }
+
+  #[test]
+  fn test_ledger_replay() {
+    let mut ledger =
+      Ledger::with_opening_balances(Balances::from([(3, 50), (4, 10)]));
+
+    for record in [
+      record(1, TxType::Deposit, 0, 2, 100, Status::Success),
+      record(2, TxType::Transfer, 2, 3, 30, Status::Success),
+      record(3, TxType::Withdrawal, 2, 0, 500, Status::Failure),
+      record(4, TxType::Withdrawal, 3, 0, 100, Status::Success),
+      record(5, TxType::Transfer, 4, 4, 10, Status::Success),
+      record(6, TxType::Deposit, 0, 4, 5, Status::Pending),
+    ] {
+      ledger.apply(&record);
+    }
+
+    assert_eq!(
+      ledger.balances(),
+      &Balances::from([(2, 70), (3, -20), (4, 10)])
+    );
+    assert_eq!(ledger.balance(5), 0);
+    assert_eq!(
+      ledger.issues(),
+      &[
+        LedgerIssue::Overdraft {
+          tx_id: 4,
+          user_id: 3,
+          balance: -20
+        },
+        LedgerIssue::Impossible {
+          tx_id: 5,
+          reason: "transfer to the same user"
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_balances_round_trip() {
+    let balances = Balances::from([(2, 70), (3, -20)]);
+    let mut output_buffer: Vec<u8> = vec![];
+
+    write_balances(&mut output_buffer, &balances).unwrap();
+
+    assert_eq!(
+      String::from_utf8(output_buffer.clone()).unwrap(),
+      "USER_ID,BALANCE\n2,70\n3,-20\n"
+    );
+    assert_eq!(read_balances(Cursor::new(output_buffer)).unwrap(), balances);
+    assert!(read_balances(Cursor::new("2,70\n2,10\n")).is_err());
+    assert!(read_balances(Cursor::new("2;70\n")).is_err());
+    assert!(matches!(
+      read_balances(Cursor::new("2,70\nuser,10\n")),
+      Err(ParsingError::Custom(message))
+        if message == "Invalid balance line 2: user,10"
+    ));
+  }
+}
@@ -1,4 +1,28 @@
+#[cfg(feature = "async")]
+pub mod codec;
+#[cfg(feature = "arrow")]
+pub mod columnar;
+#[cfg(feature = "compression")]
+pub mod compression;
 pub mod constants;
+pub mod date;
+pub mod dedupe;
 pub mod errors;
+#[cfg(feature = "filter")]
+pub mod filter;
+pub mod index;
+pub mod ledger;
+pub mod mmap;
+pub mod parallel;
 pub mod parsers;
+#[cfg(feature = "query")]
+pub mod query;
+pub mod reconcile;
 pub mod record;
+#[cfg(feature = "sort")]
+pub mod sort;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod stats;
+#[cfg(feature = "transform")]
+pub mod transform;
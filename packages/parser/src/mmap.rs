@@ -0,0 +1,82 @@
+use crate::parsers::BinSliceReader;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Binary report reader backed by a memory-mapped file, records are decoded
+/// in place without copying the file into intermediate buffers
+pub struct MmapBinReader {
+  mmap: Mmap,
+}
+
+impl MmapBinReader {
+  pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    let file = File::open(path)?;
+    // SAFETY: the mapping is read only, the report file must not be truncated
+    // or modified by other processes while the reader is alive
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    Ok(Self { mmap })
+  }
+
+  /// Raw content of the mapped file
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.mmap
+  }
+
+  /// Iterator over borrowed records of the mapped file
+  pub fn records(&self) -> BinSliceReader<'_> {
+    BinSliceReader::new(&self.mmap)
+  }
+}
+
+#[cfg(test)]
+mod mmap_test {
+  use crate::mmap::MmapBinReader;
+  use crate::parsers::BinRecord;
+  use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+  use std::io::Write;
+
+  #[test]
+  fn test_read_mapped_records() {
+    let mut buff: Vec<u8> = vec![];
+
+    for tx_id in 0..3 {
+      BinRecord(BankRecord {
+        tx_id,
+        tx_type: TxType::Transfer,
+        from_user_id: 1,
+        to_user_id: 2,
+        amount: 100,
+        timestamp: 1633036860000,
+        status: Status::Success,
+        description: format!("Record number {tx_id}"),
+      })
+      .write_to(&mut buff)
+      .unwrap();
+    }
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&buff).unwrap();
+
+    let reader = MmapBinReader::open(file.path()).unwrap();
+    let records: Vec<BankRecord> = reader
+      .records()
+      .map(|record| record.unwrap().to_owned_record())
+      .collect();
+
+    assert_eq!(reader.as_bytes(), &buff[..]);
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[2].tx_id, 2);
+    assert_eq!(records[2].description, "Record number 2");
+  }
+
+  #[test]
+  fn test_read_empty_file() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let reader = MmapBinReader::open(file.path()).unwrap();
+
+    assert!(reader.records().next().is_none());
+  }
+}
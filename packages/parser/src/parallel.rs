@@ -0,0 +1,272 @@
+use crate::errors::ParsingError;
+use crate::parsers::bin::{is_record_header, record_len};
+use crate::record::{BankRecord, BankRecordParser};
+use std::io::{Cursor, ErrorKind};
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// Boundaries the input can be split at without breaking records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkBoundary {
+  /// Single line records, e.g. CSV rows
+  Line,
+  /// Binary records, chunks end right after a complete record
+  BinRecord,
+}
+
+/// Splits `data` into at most `chunks` parts of a similar size, every part
+/// can be parsed independently of the others
+pub fn split_chunks(
+  data: &[u8],
+  boundary: ChunkBoundary,
+  chunks: NonZeroUsize,
+) -> Vec<&[u8]> {
+  let chunk_size = data.len().div_ceil(chunks.get()).max(1);
+
+  match boundary {
+    ChunkBoundary::Line => split_lines(data, chunk_size),
+    ChunkBoundary::BinRecord => split_bin_records(data, chunk_size),
+  }
+}
+
+/// Parses records of `data` on `jobs` threads, records keep their original
+/// order.
+///
+/// Same as the sequential reading, records are read until the first parsing
+/// error, records of the following chunks are dropped.
+pub fn parse_parallel<P: BankRecordParser>(
+  data: &[u8],
+  boundary: ChunkBoundary,
+  jobs: NonZeroUsize,
+) -> Vec<BankRecord> {
+  let mut records = vec![];
+
+  for (chunk_records, error) in parse_chunks::<P>(data, boundary, jobs) {
+    records.extend(chunk_records);
+
+    if error.is_some() {
+      break;
+    }
+  }
+
+  records
+}
+
+/// Same as `parse_parallel`, but only the end of data between records ends
+/// the records, the first other parsing error is returned
+pub fn try_parse_parallel<P: BankRecordParser>(
+  data: &[u8],
+  boundary: ChunkBoundary,
+  jobs: NonZeroUsize,
+) -> Result<Vec<BankRecord>, ParsingError> {
+  let mut records = vec![];
+
+  for (chunk_records, error) in parse_chunks::<P>(data, boundary, jobs) {
+    records.extend(chunk_records);
+
+    match error {
+      None => {}
+      Some(ParsingError::IO(err)) if err.kind() == ErrorKind::UnexpectedEof => {
+        break;
+      }
+      Some(err) => return Err(err),
+    }
+  }
+
+  Ok(records)
+}
+
+/// Parses chunks of `data` on `jobs` threads, in order
+fn parse_chunks<P: BankRecordParser>(
+  data: &[u8],
+  boundary: ChunkBoundary,
+  jobs: NonZeroUsize,
+) -> Vec<(Vec<BankRecord>, Option<ParsingError>)> {
+  let chunks = split_chunks(data, boundary, jobs);
+
+  thread::scope(|scope| {
+    let handles: Vec<_> = chunks
+      .into_iter()
+      .map(|chunk| scope.spawn(move || parse_chunk::<P>(chunk)))
+      .collect();
+
+    handles
+      .into_iter()
+      .map(|handle| handle.join().expect("Parser thread panicked"))
+      .collect()
+  })
+}
+
+/// Returns parsed records and the error which stopped parsing of the chunk
+fn parse_chunk<P: BankRecordParser>(
+  chunk: &[u8],
+) -> (Vec<BankRecord>, Option<ParsingError>) {
+  let mut cursor = Cursor::new(chunk);
+  let mut records = vec![];
+
+  while (cursor.position() as usize) < chunk.len() {
+    match P::from_read(&mut cursor) {
+      Ok(record) => records.push(record),
+      Err(err) => return (records, Some(err)),
+    }
+  }
+
+  (records, None)
+}
+
+fn split_lines(data: &[u8], chunk_size: usize) -> Vec<&[u8]> {
+  let mut chunks = vec![];
+  let mut start = 0;
+
+  while start < data.len() {
+    let target = (start + chunk_size).min(data.len());
+    let end = data[target..]
+      .iter()
+      .position(|&byte| byte == b'\n')
+      .map_or(data.len(), |position| target + position + 1);
+
+    chunks.push(&data[start..end]);
+    start = end;
+  }
+
+  chunks
+}
+
+/// Walks record boundaries, junk data between records goes to the chunk of
+/// the following record. Damaged record and everything after it is left to
+/// the last chunk, so parsing stops there the same way as sequential one does.
+fn split_bin_records(data: &[u8], chunk_size: usize) -> Vec<&[u8]> {
+  let mut chunks = vec![];
+  let mut start = 0;
+  let mut position = 0;
+
+  while let Some(header_offset) = data[position..]
+    .windows(4)
+    .position(|window| is_record_header(window.try_into().unwrap()))
+  {
+    let Ok(len) = record_len(&data[position + header_offset..]) else {
+      break;
+    };
+
+    position += header_offset + len;
+
+    if position - start >= chunk_size {
+      chunks.push(&data[start..position]);
+      start = position;
+    }
+  }
+
+  if start < data.len() {
+    chunks.push(&data[start..]);
+  }
+
+  chunks
+}
+
+#[cfg(test)]
+mod parallel_test {
+  use crate::errors::ParsingError;
+  use crate::parallel::{
+    ChunkBoundary, parse_parallel, split_chunks, try_parse_parallel,
+  };
+  use crate::parsers::{BinRecord, CsvRecord};
+  use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+  use std::io::ErrorKind;
+  use std::num::NonZeroUsize;
+
+  fn records() -> Vec<BankRecord> {
+    (0..50)
+      .map(|tx_id| BankRecord {
+        tx_id,
+        tx_type: TxType::Withdrawal,
+        from_user_id: tx_id,
+        to_user_id: 0,
+        amount: tx_id * 100,
+        timestamp: 1633036860000 + tx_id,
+        status: Status::Success,
+        description: format!("Record number {tx_id}"),
+      })
+      .collect()
+  }
+
+  fn jobs(jobs: usize) -> NonZeroUsize {
+    NonZeroUsize::new(jobs).unwrap()
+  }
+
+  #[test]
+  fn test_parse_csv_in_order() {
+    let mut buff: Vec<u8> = vec![];
+
+    for record in records() {
+      CsvRecord(record).write_to(&mut buff).unwrap();
+    }
+
+    let parsed =
+      parse_parallel::<CsvRecord>(&buff, ChunkBoundary::Line, jobs(4));
+
+    assert_eq!(parsed, records());
+  }
+
+  #[test]
+  fn test_parse_bin_in_order() {
+    let mut buff: Vec<u8> = b"junk".to_vec();
+
+    for record in records() {
+      BinRecord(record).write_to(&mut buff).unwrap();
+      buff.extend_from_slice(b"junk");
+    }
+
+    let chunks = split_chunks(&buff, ChunkBoundary::BinRecord, jobs(4));
+    let parsed =
+      parse_parallel::<BinRecord>(&buff, ChunkBoundary::BinRecord, jobs(4));
+
+    assert_eq!(chunks.len(), 4);
+    assert_eq!(parsed, records());
+  }
+
+  #[test]
+  fn test_parse_stops_at_first_error() {
+    let mut buff: Vec<u8> = vec![];
+
+    for (index, record) in records().into_iter().enumerate() {
+      if index == 10 {
+        buff.extend_from_slice(b"broken,row\n");
+      }
+      CsvRecord(record).write_to(&mut buff).unwrap();
+    }
+
+    let parsed =
+      parse_parallel::<CsvRecord>(&buff, ChunkBoundary::Line, jobs(8));
+
+    assert_eq!(parsed, records()[..10]);
+  }
+
+  #[test]
+  fn test_try_parse_returns_first_error() {
+    let mut buff: Vec<u8> = vec![];
+
+    for (index, record) in records().into_iter().enumerate() {
+      if index == 10 {
+        buff.extend_from_slice(b"broken,row\n");
+      }
+      CsvRecord(record).write_to(&mut buff).unwrap();
+    }
+
+    assert!(matches!(
+      try_parse_parallel::<CsvRecord>(&buff, ChunkBoundary::Line, jobs(8)),
+      Err(ParsingError::IO(err)) if err.kind() == ErrorKind::InvalidData
+    ));
+
+    // Records of the bin data end with a truncated record
+    let mut buff: Vec<u8> = vec![];
+    for record in records() {
+      BinRecord(record).write_to(&mut buff).unwrap();
+    }
+    buff.truncate(buff.len() - 5);
+
+    assert!(matches!(
+      try_parse_parallel::<BinRecord>(&buff, ChunkBoundary::BinRecord, jobs(4)),
+      Err(ParsingError::TruncatedRecord { .. })
+    ));
+  }
+}
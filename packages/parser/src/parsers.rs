@@ -1,7 +1,31 @@
-mod bin;
+pub(crate) mod bin;
+#[cfg(feature = "camt")]
+mod camt;
 mod csv;
+mod mt940;
+mod ofx;
+mod qif;
+#[cfg(feature = "serde")]
+mod serde_record;
+mod statement;
 mod txt;
 
-pub use bin::{BIN_RECORD_HEADER, BinRecord};
+pub use bin::{
+  BIN_CHECKED_RECORD_HEADER, BIN_EXTENSION_VERSION, BIN_RECORD_FIXED_SIZE,
+  BIN_RECORD_HEADER, BIN_RECORD_MAX_SIZE, BinExtension, BinExtensionField,
+  BinExtensionFields, BinRecord, BinRecoveryReader, BinSliceReader,
+  CheckedBinRecord, ResyncEvent,
+};
+#[cfg(feature = "camt")]
+pub use camt::{CAMT_CURRENCY, CAMT_NAMESPACE, CamtRecord};
 pub use csv::{CVS_RECORD_HEADER, CsvRecord};
+pub use mt940::{
+  MT940_CURRENCY, Mt940Accounts, Mt940Balance, Mt940Reader, Mt940Record,
+};
+pub use ofx::{OFX_BANK_ID, OFX_CURRENCY, OfxSyntax, OfxWriter};
+pub use qif::QifWriter;
+#[cfg(feature = "json")]
+pub use serde_record::JsonLines;
+#[cfg(feature = "serde")]
+pub use serde_record::{SerdeFormat, SerdeRecord};
 pub use txt::TxtRecord;
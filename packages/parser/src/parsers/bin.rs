@@ -1,136 +1,398 @@
-use crate::errors::{ParsingError, SerializeError};
-use crate::record::{BankRecord, BankRecordParser, Status, TxType};
-use std::cmp::min;
+use crate::errors::{
+  ParsingError, SerializeError, StatusTypeError, TxTypeError,
+};
+use crate::record::{
+  BankRecord, BankRecordParser, BankRecordRef, Status, TxType,
+};
 use std::io;
-use std::io::{BufRead, IoSlice, IoSliceMut, Write};
+use std::io::{BufRead, ErrorKind, IoSlice, Read, Write};
+
+mod extension;
+mod recovery;
+mod slice;
+
+pub use extension::{
+  BIN_EXTENSION_VERSION, BinExtension, BinExtensionField, BinExtensionFields,
+};
+pub use recovery::{BinRecoveryReader, ResyncEvent};
+pub use slice::BinSliceReader;
 
 pub struct BinRecord(pub BankRecord);
 
+/// Binary record which is serialized with a CRC32C trailer
+pub struct CheckedBinRecord(pub BankRecord);
+
 pub static BIN_RECORD_HEADER: &[u8; 4] = b"YPBN";
+pub static BIN_CHECKED_RECORD_HEADER: &[u8; 4] = b"YPBC";
+
+/// Size of the record body fields which precede the description
+pub const BIN_RECORD_FIXED_SIZE: u32 = 46;
+
+/// Largest record size accepted by the readers, a larger one comes from a
+/// damaged size field and is not read
+pub const BIN_RECORD_MAX_SIZE: u32 = 1 << 20;
+
+const RECORD_SIZE_LEN: usize = 4;
+const CHECKSUM_LEN: usize = 4;
+
+impl BinRecord {
+  /// Reads the next record together with its extension area
+  pub fn from_read_extended<R: BufRead>(
+    buffer: &mut R,
+  ) -> Result<(BankRecord, BinExtension), ParsingError> {
+    let mut consumed = 0;
+    let magic = scan_header(buffer, &mut consumed)?;
+    let mut raw_record = magic.to_vec();
+
+    read_record_bytes(buffer, &mut raw_record)?;
+
+    decode_record(&raw_record)
+  }
+
+  /// Writes the record followed by the extension area
+  pub fn write_extended_to<W: Write>(
+    &mut self,
+    extension: &BinExtension,
+    buffer: &mut W,
+  ) -> Result<(), SerializeError> {
+    write_record(&self.0, extension, BIN_RECORD_HEADER, buffer)
+  }
+}
 
 impl BankRecordParser for BinRecord {
   fn from_read<R: BufRead>(buffer: &mut R) -> Result<BankRecord, ParsingError> {
-    let mut record_header_buf = [0u8; 4];
+    Self::from_read_extended(buffer).map(|(record, _extension)| record)
+  }
+  fn write_to<W: Write>(
+    &mut self,
+    buffer: &mut W,
+  ) -> Result<(), SerializeError> {
+    self.write_extended_to(&BinExtension::default(), buffer)
+  }
+}
 
-    buffer
-      .read_exact(&mut record_header_buf)
-      .map_err(ParsingError::IO)?;
+impl CheckedBinRecord {
+  /// Writes the record followed by the extension area and checksum trailer
+  pub fn write_extended_to<W: Write>(
+    &mut self,
+    extension: &BinExtension,
+    buffer: &mut W,
+  ) -> Result<(), SerializeError> {
+    let mut record_buf: Vec<u8> = vec![];
 
-    loop {
-      if record_header_buf == *BIN_RECORD_HEADER {
-        break;
-      }
+    write_record(
+      &self.0,
+      extension,
+      BIN_CHECKED_RECORD_HEADER,
+      &mut record_buf,
+    )?;
 
-      // BIN_RECORD_HEADER is lost, scan 4 bytes window with 1 byte step
-      // Move buffer cursor forward by 1 byte
-      let mut step = [0u8; 1];
-      buffer.read_exact(&mut step)?;
+    let checksum = crc32c::crc32c(&record_buf);
 
-      // Shift last 3 bytes to front, fill the last byte from step buffer
-      record_header_buf.copy_within(1.., 0);
-      record_header_buf[3] = step[0];
+    buffer.write_all(&record_buf)?;
+    buffer.write_all(&checksum.to_be_bytes())?;
 
-      // Repeat the loop to check if header is found
-      continue;
-    }
+    Ok(())
+  }
+}
 
-    buffer.read_exact(&mut record_header_buf)?;
-
-    let record_size = u32::from_be_bytes(record_header_buf);
-
-    let mut tx_id = [0u8; 8];
-    let mut tx_type = [0u8; 1];
-    let mut from_user_id = [0u8; 8];
-    let mut to_user_id = [0u8; 8];
-    let mut amount = [0u8; 8];
-    let mut timestamp = [0u8; 8];
-    let mut status = [0u8; 1];
-    let mut description_len = [0u8; 4];
-
-    let mut bufs = [
-      IoSliceMut::new(&mut tx_id),
-      IoSliceMut::new(&mut tx_type),
-      IoSliceMut::new(&mut from_user_id),
-      IoSliceMut::new(&mut to_user_id),
-      IoSliceMut::new(&mut amount),
-      IoSliceMut::new(&mut timestamp),
-      IoSliceMut::new(&mut status),
-      IoSliceMut::new(&mut description_len),
-    ];
-    let read_bytes = buffer.read_vectored(&mut bufs)?;
-
-    let description_len = u32::from_be_bytes(description_len);
-    // Description buffer leftover size needs to be calculated based on number bytes read
-    // It allows to move cursor to the end of record section
-    let buffer_leftover = record_size - read_bytes as u32;
-    let description_buf_size = min(description_len, buffer_leftover);
-    let mut desc_buf = vec![0u8; description_buf_size as usize];
-
-    buffer.read_exact(&mut desc_buf)?;
-
-    let description =
-      String::try_from(desc_buf.clone()).unwrap_or("".to_string());
-    // Escaped quotes are not needed in model
-    let normalized_description = description.replace("\"", "");
-
-    Ok(BankRecord {
-      tx_id: u64::from_be_bytes(tx_id),
-      tx_type: TxType::try_from(u8::from_be_bytes(tx_type))?,
-      from_user_id: u64::from_be_bytes(from_user_id),
-      to_user_id: u64::from_be_bytes(to_user_id),
-      amount: u64::from_be_bytes(amount),
-      timestamp: u64::from_be_bytes(timestamp),
-      status: Status::try_from(u8::from_be_bytes(status))?,
-      description: normalized_description,
-    })
+impl BankRecordParser for CheckedBinRecord {
+  fn from_read<R: BufRead>(buffer: &mut R) -> Result<BankRecord, ParsingError> {
+    BinRecord::from_read(buffer)
   }
   fn write_to<W: Write>(
     &mut self,
     buffer: &mut W,
   ) -> Result<(), SerializeError> {
-    let tx_id_buf = self.0.tx_id.to_be_bytes();
-    let tx_type_buf = (self.0.tx_type.clone() as u8).to_be_bytes();
-    let from_user_id_buf = self.0.from_user_id.to_be_bytes();
-    let to_user_id_buf = self.0.to_user_id.to_be_bytes();
-    let amount_buf = self.0.amount.to_be_bytes();
-    let timestamp_buf = self.0.timestamp.to_be_bytes();
-    let status_buf = (self.0.status.clone() as u8).to_be_bytes();
-    let description_len = self.0.description.len();
-    // Need to add 2 bytes for escaped quotes, to prevent data model layout shift
-    let adjusted_description_len = if description_len == 0 {
-      0
-    } else {
-      description_len + 2
-    };
-    let description_len_buf = (adjusted_description_len as u32).to_be_bytes();
-    let description_buf = self.0.description.as_bytes();
-    let description_buf_len = description_buf.len();
-    let adjusted_description_buf_len = if description_buf_len == 0 {
-      0
+    self.write_extended_to(&BinExtension::default(), buffer)
+  }
+}
+
+pub(crate) fn is_record_header(buf: &[u8; 4]) -> bool {
+  buf == BIN_RECORD_HEADER || buf == BIN_CHECKED_RECORD_HEADER
+}
+
+/// Moves the reader past the next record header and returns it.
+/// Number of bytes read from the buffer, header included, is added to
+/// `consumed` even if the end of the stream is reached.
+fn scan_header<R: BufRead>(
+  buffer: &mut R,
+  consumed: &mut u64,
+) -> io::Result<[u8; 4]> {
+  let mut record_header_buf = [0u8; 4];
+  let mut filled = 0;
+
+  loop {
+    // Move buffer cursor forward by 1 byte
+    let mut step = [0u8; 1];
+    buffer.read_exact(&mut step)?;
+    *consumed += 1;
+
+    if filled < record_header_buf.len() {
+      record_header_buf[filled] = step[0];
+      filled += 1;
     } else {
-      description_buf_len + 2
-    };
+      // BIN_RECORD_HEADER is lost, scan 4 bytes window with 1 byte step
+      // Shift last 3 bytes to front, fill the last byte from step buffer
+      record_header_buf.copy_within(1.., 0);
+      record_header_buf[3] = step[0];
+    }
+
+    if filled == record_header_buf.len() && is_record_header(&record_header_buf)
+    {
+      return Ok(record_header_buf);
+    }
+  }
+}
+
+/// Reads the rest of the record which header is already stored in
+/// `raw_record`. Bytes are appended to `raw_record` as they are read, so the
+/// caller still has them if the record turns out to be broken or truncated.
+fn read_record_bytes<R: Read>(
+  buffer: &mut R,
+  raw_record: &mut Vec<u8>,
+) -> Result<(), ParsingError> {
+  let header_len = raw_record.len();
+
+  read_bytes(buffer, raw_record, RECORD_SIZE_LEN)?;
+
+  let record_size = u32::from_be_bytes(
+    raw_record[header_len..header_len + RECORD_SIZE_LEN]
+      .try_into()
+      .unwrap(),
+  );
+
+  check_layout(record_size, 0)?;
+  read_bytes(buffer, raw_record, BIN_RECORD_FIXED_SIZE as usize)?;
+
+  let description_len =
+    u32::from_be_bytes(raw_record[raw_record.len() - 4..].try_into().unwrap());
+
+  check_layout(record_size, description_len)?;
+  read_bytes(
+    buffer,
+    raw_record,
+    (record_size - BIN_RECORD_FIXED_SIZE) as usize,
+  )?;
+
+  if raw_record[..header_len] == *BIN_CHECKED_RECORD_HEADER {
+    read_bytes(buffer, raw_record, CHECKSUM_LEN)?;
+  }
+
+  Ok(())
+}
+
+/// Length of the record placed at the start of `data`, header and checksum
+/// trailer included. `TruncatedRecord` is returned if `data` ends before the
+/// record does.
+pub(crate) fn record_len(data: &[u8]) -> Result<usize, ParsingError> {
+  let truncated = || ParsingError::TruncatedRecord { read: data.len() };
+  let size_end = BIN_RECORD_HEADER.len() + RECORD_SIZE_LEN;
+  let record_size = u32::from_be_bytes(
+    data
+      .get(BIN_RECORD_HEADER.len()..size_end)
+      .ok_or_else(truncated)?
+      .try_into()
+      .unwrap(),
+  );
+
+  check_layout(record_size, 0)?;
+
+  let fixed_end = size_end + BIN_RECORD_FIXED_SIZE as usize;
+  let description_len = u32::from_be_bytes(
+    data
+      .get(fixed_end - 4..fixed_end)
+      .ok_or_else(truncated)?
+      .try_into()
+      .unwrap(),
+  );
+
+  check_layout(record_size, description_len)?;
+
+  let mut len = size_end + record_size as usize;
+
+  if data[..BIN_RECORD_HEADER.len()] == *BIN_CHECKED_RECORD_HEADER {
+    len += CHECKSUM_LEN;
+  }
+
+  if data.len() < len {
+    return Err(truncated());
+  }
+
+  Ok(len)
+}
+
+/// Record size is limited by `BIN_RECORD_MAX_SIZE`. Description has to fit
+/// into the record body, bytes left after it belong to the extension area.
+fn check_layout(
+  record_size: u32,
+  description_len: u32,
+) -> Result<(), ParsingError> {
+  if record_size > BIN_RECORD_MAX_SIZE {
+    return Err(ParsingError::RecordTooLarge { record_size });
+  }
+
+  if BIN_RECORD_FIXED_SIZE as u64 + description_len as u64 > record_size as u64
+  {
+    return Err(ParsingError::RecordLayout {
+      record_size,
+      description_len,
+    });
+  }
+
+  Ok(())
+}
+
+/// Appends `len` bytes to `raw_record`, the record is truncated if the
+/// stream ends before
+fn read_bytes<R: Read>(
+  buffer: &mut R,
+  raw_record: &mut Vec<u8>,
+  len: usize,
+) -> Result<(), ParsingError> {
+  let read_len = buffer.take(len as u64).read_to_end(raw_record)?;
+
+  if read_len < len {
+    return Err(ParsingError::TruncatedRecord {
+      read: raw_record.len(),
+    });
+  }
+
+  Ok(())
+}
+
+/// Decodes a complete record, header and checksum trailer included
+pub(crate) fn decode_record(
+  raw_record: &[u8],
+) -> Result<(BankRecord, BinExtension), ParsingError> {
+  let (record, extension) = decode_record_ref(raw_record)?;
+
+  Ok((
+    record.to_owned_record(),
+    BinExtension::from_bytes(extension.to_vec()),
+  ))
+}
+
+/// Decodes a complete record without copying, returns the record view and
+/// bytes of its extension area
+fn decode_record_ref(
+  raw_record: &[u8],
+) -> Result<(BankRecordRef<'_>, &[u8]), ParsingError> {
+  let (header, mut body) = raw_record.split_at(4);
+
+  if header == BIN_CHECKED_RECORD_HEADER {
+    let (checked, checksum) = raw_record.split_at(raw_record.len() - 4);
+    let expected = u32::from_be_bytes(checksum.try_into().unwrap());
+    let actual = crc32c::crc32c(checked);
+
+    if expected != actual {
+      return Err(ParsingError::Checksum { expected, actual });
+    }
 
-    let bufs = [
-      IoSlice::new(&tx_id_buf),
-      IoSlice::new(&tx_type_buf),
-      IoSlice::new(&from_user_id_buf),
-      IoSlice::new(&to_user_id_buf),
-      IoSlice::new(&amount_buf),
-      IoSlice::new(&timestamp_buf),
-      IoSlice::new(&status_buf),
-      IoSlice::new(&description_len_buf),
-    ];
+    body = &body[..body.len() - CHECKSUM_LEN];
+  }
+
+  let (_record_size, fields) = body.split_at(RECORD_SIZE_LEN);
+  let (fixed, trailing) = fields.split_at(BIN_RECORD_FIXED_SIZE as usize);
+
+  let tx_id = u64::from_be_bytes(fixed[0..8].try_into().unwrap());
+  let tx_type = fixed[8];
+  let from_user_id = u64::from_be_bytes(fixed[9..17].try_into().unwrap());
+  let to_user_id = u64::from_be_bytes(fixed[17..25].try_into().unwrap());
+  let amount = u64::from_be_bytes(fixed[25..33].try_into().unwrap());
+  let timestamp = u64::from_be_bytes(fixed[33..41].try_into().unwrap());
+  let status = fixed[41];
+  let description_len =
+    u32::from_be_bytes(fixed[42..46].try_into().unwrap()) as usize;
+  let (description, extension) = trailing.split_at(description_len);
+
+  let description = str::from_utf8(description).unwrap_or("");
+  // Escaped quotes surrounding the description are not needed in model
+  let description = description
+    .strip_prefix('"')
+    .and_then(|description| description.strip_suffix('"'))
+    .unwrap_or(description);
+
+  let record = BankRecordRef {
+    tx_id,
+    tx_type: TxType::try_from(tx_type)
+      .map_err(|_| TxTypeError::InvalidNumber(tx_type))?,
+    from_user_id,
+    to_user_id,
+    amount,
+    timestamp,
+    status: Status::try_from(status)
+      .map_err(|_| StatusTypeError::InvalidNumber(status))?,
+    description,
+  };
+
+  Ok((record, extension))
+}
+
+fn write_record<W: Write>(
+  record: &BankRecord,
+  extension: &BinExtension,
+  magic: &[u8; 4],
+  buffer: &mut W,
+) -> Result<(), SerializeError> {
+  let tx_id_buf = record.tx_id.to_be_bytes();
+  let tx_type_buf = (record.tx_type.clone() as u8).to_be_bytes();
+  let from_user_id_buf = record.from_user_id.to_be_bytes();
+  let to_user_id_buf = record.to_user_id.to_be_bytes();
+  let amount_buf = record.amount.to_be_bytes();
+  let timestamp_buf = record.timestamp.to_be_bytes();
+  let status_buf = (record.status.clone() as u8).to_be_bytes();
+  let description_len = record.description.len();
+  // Need to add 2 bytes for escaped quotes, to prevent data model layout shift
+  let adjusted_description_len = if description_len == 0 {
+    0
+  } else {
+    description_len + 2
+  };
+  let description_len_buf = (adjusted_description_len as u32).to_be_bytes();
+  let description_buf = record.description.as_bytes();
+  let description_buf_len = description_buf.len();
+  let adjusted_description_buf_len = if description_buf_len == 0 {
+    0
+  } else {
+    description_buf_len + 2
+  };
+
+  let mut bufs = [
+    IoSlice::new(&tx_id_buf),
+    IoSlice::new(&tx_type_buf),
+    IoSlice::new(&from_user_id_buf),
+    IoSlice::new(&to_user_id_buf),
+    IoSlice::new(&amount_buf),
+    IoSlice::new(&timestamp_buf),
+    IoSlice::new(&status_buf),
+    IoSlice::new(&description_len_buf),
+  ];
+
+  let record_size = bufs.iter().map(|slice| slice.len()).sum::<usize>()
+    + adjusted_description_buf_len
+    + extension.as_bytes().len();
+
+  if record_size > BIN_RECORD_MAX_SIZE as usize {
+    return Err(SerializeError::IO(io::Error::new(
+      ErrorKind::InvalidInput,
+      format!(
+        "Record size {record_size} exceeds the limit of {BIN_RECORD_MAX_SIZE} bytes"
+      ),
+    )));
+  }
 
-    let record_size: u32 = (bufs.iter().map(|slice| slice.len()).sum::<usize>()
-      + adjusted_description_buf_len) as u32;
+  let record_size = record_size as u32;
 
-    // Write record header
-    buffer.write_all(BIN_RECORD_HEADER)?;
-    buffer.write_all(&record_size.to_be_bytes())?;
+  // Write record header
+  buffer.write_all(magic)?;
+  buffer.write_all(&record_size.to_be_bytes())?;
 
-    // Write record body
-    let write_bytes = buffer.write_vectored(&bufs)?;
+  // Write record body, source may accept only a part of the slices at once
+  let mut bufs = &mut bufs[..];
+
+  while !bufs.is_empty() {
+    let write_bytes = buffer.write_vectored(bufs)?;
 
     if write_bytes == 0 {
       return Err(SerializeError::IO(io::Error::other(
@@ -138,22 +400,30 @@ impl BankRecordParser for BinRecord {
       )));
     }
 
-    if description_len > 0 {
-      // Write escaped quotes to record model
-      write!(buffer, "\"")?;
-      buffer.write_all(description_buf)?;
-      write!(buffer, "\"")?;
-    }
+    IoSlice::advance_slices(&mut bufs, write_bytes);
+  }
 
-    Ok(())
+  if description_len > 0 {
+    // Write escaped quotes to record model
+    write!(buffer, "\"")?;
+    buffer.write_all(description_buf)?;
+    write!(buffer, "\"")?;
   }
+
+  buffer.write_all(extension.as_bytes())?;
+
+  Ok(())
 }
 
 #[cfg(test)]
 mod bin_parser_test {
-  use crate::parsers::bin::{BIN_RECORD_HEADER, BinRecord};
+  use crate::errors::ParsingError;
+  use crate::parsers::bin::{
+    BIN_CHECKED_RECORD_HEADER, BIN_RECORD_HEADER, BIN_RECORD_MAX_SIZE,
+    BinExtension, BinRecord, CheckedBinRecord,
+  };
   use crate::record::{BankRecord, BankRecordParser, Status, TxType};
-  use std::io::{Cursor, Write};
+  use std::io::{Cursor, ErrorKind, Write};
 
   #[test]
   fn test_parse_valid_input() {
@@ -212,6 +482,33 @@ mod bin_parser_test {
     let mut buff = Cursor::new(buff);
     let rec_result = BinRecord::from_read(&mut buff);
 
+    // Declared record size is read completely, 17 bytes of it are missing
+    assert!(matches!(
+      rec_result,
+      Err(ParsingError::TruncatedRecord { read: 54 })
+    ));
+  }
+
+  #[test]
+  fn test_parse_empty_description() {
+    let mut buff: Vec<u8> = vec![];
+    let description = String::from("");
+
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    // Content size does not include description field at all
+    buff.extend_from_slice(&46u32.to_be_bytes()[..]);
+    buff.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&0u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&100u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&(description.len() as u32).to_be_bytes()[..]);
+
+    let mut buff = Cursor::new(buff);
+    let rec_result = BinRecord::from_read(&mut buff);
+
     assert!(rec_result.is_ok());
 
     let rec = rec_result.unwrap();
@@ -224,6 +521,7 @@ mod bin_parser_test {
     assert_eq!(rec.timestamp, 1633036860000u64);
     assert_eq!(rec.timestamp, 1633036860000u64);
     assert_eq!(rec.status, Status::Failure);
+    assert_eq!(rec.description, description);
   }
 
   #[test]
@@ -306,9 +604,29 @@ mod bin_parser_test {
     let mut buff = Cursor::new(buff);
     let rec_result = BinRecord::from_read(&mut buff);
 
-    assert!(rec_result.is_ok());
+    // Shifted fields can't fill the declared record size, so no record with
+    // shifted values is returned
+    assert!(matches!(
+      rec_result,
+      Err(ParsingError::TruncatedRecord { read: 46 })
+    ));
 
-    let rec = rec_result.unwrap();
+    // Shifted fields of a complete record are read as other values
+    let mut buff: Vec<u8> = vec![];
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    buff.extend_from_slice(&63u32.to_be_bytes()[..]);
+    buff.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&0u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    // Missing amount field
+    buff.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&0u32.to_be_bytes()[..]);
+    buff.extend_from_slice(&[0u8; 8]);
+    buff.extend_from_slice(&[0u8; 17]);
+
+    let rec = BinRecord::from_read(&mut Cursor::new(buff)).unwrap();
 
     assert_eq!(rec.tx_id, 1000000000000000u64);
     assert_eq!(rec.tx_type, TxType::Deposit);
@@ -316,11 +634,100 @@ mod bin_parser_test {
     assert_eq!(rec.to_user_id, 9223372036854775807u64);
     assert_ne!(rec.amount, 100u64);
     assert_ne!(rec.timestamp, 1633036860000u64);
-    assert_ne!(rec.timestamp, 1633036860000u64);
     assert_ne!(rec.status, Status::Failure);
     assert_ne!(rec.description, description);
   }
 
+  #[test]
+  fn test_parse_record_size_mismatch() {
+    let mut buff: Vec<u8> = vec![];
+
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    // Declared size does not cover the description
+    buff.extend_from_slice(&46u32.to_be_bytes()[..]);
+    buff.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&0u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&100u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&17u32.to_be_bytes()[..]);
+    buff.extend_from_slice("\"Record number 1\"".as_bytes());
+
+    let rec_result = BinRecord::from_read(&mut Cursor::new(buff));
+
+    assert!(matches!(
+      rec_result,
+      Err(ParsingError::RecordLayout {
+        record_size: 46,
+        description_len: 17
+      })
+    ));
+  }
+
+  #[test]
+  fn test_parse_record_too_large() {
+    let mut buff: Vec<u8> = vec![];
+    BinRecord(BankRecord::new()).write_to(&mut buff).unwrap();
+    // Damaged size field of the second record
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    buff.extend_from_slice(&0x7fffffffu32.to_be_bytes()[..]);
+    buff.extend_from_slice(&[0u8; 64]);
+
+    let mut buff = Cursor::new(buff);
+
+    assert!(BinRecord::from_read(&mut buff).is_ok());
+    assert!(matches!(
+      BinRecord::from_read(&mut buff),
+      Err(ParsingError::RecordTooLarge {
+        record_size: 0x7fffffff
+      })
+    ));
+  }
+
+  #[test]
+  fn test_parse_end_of_stream() {
+    let mut record_buff: Vec<u8> = vec![];
+    BinRecord(BankRecord::new())
+      .write_to(&mut record_buff)
+      .unwrap();
+
+    // Junk data after the last record is not a record
+    let mut buff = record_buff.clone();
+    buff.extend_from_slice(b"junk");
+    let mut buff = Cursor::new(buff);
+
+    assert!(BinRecord::from_read(&mut buff).is_ok());
+    assert!(matches!(
+      BinRecord::from_read(&mut buff),
+      Err(ParsingError::IO(err)) if err.kind() == ErrorKind::UnexpectedEof
+    ));
+
+    // Stream ends right after the header of the next record
+    let mut buff = record_buff.clone();
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    let mut buff = Cursor::new(buff);
+
+    assert!(BinRecord::from_read(&mut buff).is_ok());
+    assert!(matches!(
+      BinRecord::from_read(&mut buff),
+      Err(ParsingError::TruncatedRecord { read: 4 })
+    ));
+  }
+
+  #[test]
+  fn test_serialize_record_too_large() {
+    let mut buff: Vec<u8> = vec![];
+    let record = BankRecord {
+      description: "a".repeat(BIN_RECORD_MAX_SIZE as usize),
+      ..BankRecord::new()
+    };
+
+    assert!(BinRecord(record).write_to(&mut buff).is_err());
+    assert!(buff.is_empty());
+  }
+
   #[test]
   fn test_serialize_record() {
     let mut assert_buffer: Vec<u8> = vec![];
@@ -396,4 +803,217 @@ mod bin_parser_test {
 
     assert_eq!(buffer.into_inner(), assert_buffer);
   }
+
+  #[test]
+  fn test_serialize_checked_record() {
+    let mut assert_buffer: Vec<u8> = vec![];
+    let description = String::from("Record number 1");
+
+    assert_buffer.extend_from_slice(BIN_CHECKED_RECORD_HEADER);
+    assert_buffer.extend_from_slice(&63u32.to_be_bytes()[..]);
+    assert_buffer.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    assert_buffer.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    assert_buffer.extend_from_slice(&0u64.to_be_bytes()[..]);
+    assert_buffer.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    assert_buffer.extend_from_slice(&100u64.to_be_bytes()[..]);
+    assert_buffer.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    assert_buffer.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    assert_buffer
+      .extend_from_slice(&((description.len() + 2) as u32).to_be_bytes()[..]);
+    assert_buffer.extend_from_slice("\"".as_bytes());
+    assert_buffer.extend_from_slice(description.as_bytes());
+    assert_buffer.extend_from_slice("\"".as_bytes());
+    // Checksum trailer covers header and body
+    let checksum = crc32c::crc32c(&assert_buffer);
+    assert_buffer.extend_from_slice(&checksum.to_be_bytes()[..]);
+
+    let mut write_buffer = Cursor::new(vec![]);
+
+    let record = BankRecord {
+      tx_id: 1000000000000000,
+      tx_type: TxType::Deposit,
+      from_user_id: 0,
+      to_user_id: 9223372036854775807,
+      amount: 100,
+      timestamp: 1633036860000,
+      status: Status::Failure,
+      description: String::from("Record number 1"),
+    };
+
+    let _ = CheckedBinRecord(record).write_to(&mut write_buffer);
+    write_buffer.flush().unwrap();
+
+    assert_eq!(write_buffer.into_inner(), assert_buffer);
+  }
+
+  #[test]
+  fn test_parse_checked_record() {
+    let record = BankRecord {
+      tx_id: 1000000000000000,
+      tx_type: TxType::Transfer,
+      from_user_id: 1,
+      to_user_id: 2,
+      amount: 100,
+      timestamp: 1633036860000,
+      status: Status::Pending,
+      description: String::from("Record number 1"),
+    };
+    let mut buff: Vec<u8> = vec![];
+
+    CheckedBinRecord(record).write_to(&mut buff).unwrap();
+    // Plain and checked records can be mixed in one stream
+    BinRecord(BankRecord::new()).write_to(&mut buff).unwrap();
+
+    let mut buff = Cursor::new(buff);
+
+    let rec = BinRecord::from_read(&mut buff).unwrap();
+
+    assert_eq!(rec.tx_id, 1000000000000000u64);
+    assert_eq!(rec.tx_type, TxType::Transfer);
+    assert_eq!(rec.amount, 100u64);
+    assert_eq!(rec.status, Status::Pending);
+    assert_eq!(rec.description, String::from("Record number 1"));
+
+    let rec = BinRecord::from_read(&mut buff).unwrap();
+
+    assert_eq!(rec, BankRecord::new());
+  }
+
+  #[test]
+  fn test_parse_checked_record_corrupted_body() {
+    let record = BankRecord {
+      tx_id: 1000000000000000,
+      amount: 100,
+      ..BankRecord::new()
+    };
+    let mut buff: Vec<u8> = vec![];
+
+    CheckedBinRecord(record).write_to(&mut buff).unwrap();
+
+    // Flip a bit in the lowest byte of the amount field
+    let amount_offset = 8 + 8 + 1 + 8 + 8;
+    buff[amount_offset + 7] ^= 0b0000_0100;
+
+    let rec_result = BinRecord::from_read(&mut Cursor::new(buff));
+
+    assert!(matches!(rec_result, Err(ParsingError::Checksum { .. })));
+  }
+
+  #[test]
+  fn test_parse_unknown_trailing_bytes() {
+    let mut buff: Vec<u8> = vec![];
+    let description = String::from("Record number 1");
+    let trailing_bytes = [1u8, 42, 0, 2, 0xCA, 0xFE];
+
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    buff.extend_from_slice(&69u32.to_be_bytes()[..]);
+    buff.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&0u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&100u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    buff.extend_from_slice(&((description.len() + 2) as u32).to_be_bytes()[..]);
+    buff.extend_from_slice("\"".as_bytes());
+    buff.extend_from_slice(description.as_bytes());
+    buff.extend_from_slice("\"".as_bytes());
+    // Fields of a newer format version
+    buff.extend_from_slice(&trailing_bytes);
+
+    BinRecord(BankRecord::new()).write_to(&mut buff).unwrap();
+
+    let mut buff = Cursor::new(buff);
+
+    let (rec, extension) = BinRecord::from_read_extended(&mut buff).unwrap();
+
+    assert_eq!(rec.tx_id, 1000000000000000u64);
+    assert_eq!(rec.description, description);
+    assert_eq!(extension.as_bytes(), trailing_bytes);
+    assert_eq!(extension.version(), Some(1));
+    assert_eq!(extension.field(42), Some(&[0xCA, 0xFE][..]));
+
+    // Stream stays in sync after the record with unknown fields
+    let rec = BinRecord::from_read(&mut buff).unwrap();
+
+    assert_eq!(rec, BankRecord::new());
+  }
+
+  #[test]
+  fn test_serialize_partial_vectored_write() {
+    // Default vectored write accepts only the first non-empty slice
+    struct SliceWriter(Vec<u8>);
+
+    impl Write for SliceWriter {
+      fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+      }
+      fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+      }
+    }
+
+    let record = BankRecord {
+      tx_id: 1,
+      tx_type: TxType::Deposit,
+      from_user_id: 0,
+      to_user_id: 2,
+      amount: 100,
+      timestamp: 1633036860000,
+      status: Status::Success,
+      description: String::from("Record number 1"),
+    };
+    let mut writer = SliceWriter(vec![]);
+
+    BinRecord(record).write_to(&mut writer).unwrap();
+
+    let parsed = BinRecord::from_read(&mut Cursor::new(writer.0)).unwrap();
+
+    assert_eq!(parsed.tx_id, 1);
+    assert_eq!(parsed.description, "Record number 1");
+  }
+
+  #[test]
+  fn test_serialize_extended_record() {
+    let mut extension = BinExtension::new();
+    extension.push_field(42, &[0xCA, 0xFE]).unwrap();
+
+    let record = BankRecord {
+      tx_id: 1000000000000000,
+      amount: 100,
+      description: String::from("Record number 1"),
+      ..BankRecord::new()
+    };
+    let mut buff: Vec<u8> = vec![];
+
+    BinRecord(record)
+      .write_extended_to(&extension, &mut buff)
+      .unwrap();
+
+    // Record size covers the extension area
+    assert_eq!(&buff[4..8], &(63u32 + 6).to_be_bytes());
+
+    let record = BankRecord {
+      tx_id: 1000000000000001,
+      ..BankRecord::new()
+    };
+
+    CheckedBinRecord(record)
+      .write_extended_to(&extension, &mut buff)
+      .unwrap();
+
+    let mut buff = Cursor::new(buff);
+
+    let (rec, rec_extension) =
+      BinRecord::from_read_extended(&mut buff).unwrap();
+
+    assert_eq!(rec.tx_id, 1000000000000000);
+    assert_eq!(rec_extension, extension);
+
+    let (rec, rec_extension) =
+      BinRecord::from_read_extended(&mut buff).unwrap();
+
+    assert_eq!(rec.tx_id, 1000000000000001);
+    assert_eq!(rec_extension, extension);
+  }
 }
@@ -0,0 +1,171 @@
+use crate::errors::{ParsingError, SerializeError};
+use std::io;
+use std::io::ErrorKind;
+
+/// Version of the extension area layout written by this crate
+pub const BIN_EXTENSION_VERSION: u8 = 1;
+
+const FIELD_HEADER_LEN: usize = 3;
+
+/// Bytes of a binary record body which follow the description.
+///
+/// Records written by newer producers may carry optional trailing fields
+/// this reader knows nothing about. These bytes are kept as is, so the record
+/// can be written back without losing them.
+///
+/// Extension area starts with the layout version byte, followed by fields,
+/// each one is encoded as `[TAG: u8][LEN: u16][DATA: LEN bytes]`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BinExtension(Vec<u8>);
+
+/// Single optional trailing field of the extension area
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinExtensionField<'a> {
+  pub tag: u8,
+  pub data: &'a [u8],
+}
+
+impl BinExtension {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn from_bytes(bytes: Vec<u8>) -> Self {
+    Self(bytes)
+  }
+
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.0
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Layout version of the record producer, `None` if the record has no
+  /// extension area
+  pub fn version(&self) -> Option<u8> {
+    self.0.first().copied()
+  }
+
+  /// Iterates over the trailing fields in order they were written
+  pub fn fields(&self) -> BinExtensionFields<'_> {
+    BinExtensionFields {
+      bytes: self.0.get(1..).unwrap_or_default(),
+    }
+  }
+
+  /// Data of the first field with the given tag
+  pub fn field(&self, tag: u8) -> Option<&[u8]> {
+    self
+      .fields()
+      .map_while(Result::ok)
+      .find(|field| field.tag == tag)
+      .map(|field| field.data)
+  }
+
+  /// Appends a trailing field, fields of newer layout versions should be
+  /// pushed after the ones of older versions
+  pub fn push_field(
+    &mut self,
+    tag: u8,
+    data: &[u8],
+  ) -> Result<(), SerializeError> {
+    let data_len = u16::try_from(data.len()).map_err(|_| {
+      SerializeError::IO(io::Error::new(
+        ErrorKind::InvalidInput,
+        format!("Extension field {tag} exceeds {} bytes", u16::MAX),
+      ))
+    })?;
+
+    if self.0.is_empty() {
+      self.0.push(BIN_EXTENSION_VERSION);
+    }
+
+    self.0.push(tag);
+    self.0.extend_from_slice(&data_len.to_be_bytes());
+    self.0.extend_from_slice(data);
+
+    Ok(())
+  }
+}
+
+pub struct BinExtensionFields<'a> {
+  bytes: &'a [u8],
+}
+
+impl<'a> Iterator for BinExtensionFields<'a> {
+  type Item = Result<BinExtensionField<'a>, ParsingError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.bytes.is_empty() {
+      return None;
+    }
+
+    if self.bytes.len() < FIELD_HEADER_LEN {
+      self.bytes = &[];
+
+      return Some(Err(ParsingError::Custom(
+        "Truncated extension field header".to_string(),
+      )));
+    }
+
+    let tag = self.bytes[0];
+    let data_len = u16::from_be_bytes([self.bytes[1], self.bytes[2]]) as usize;
+    let rest = &self.bytes[FIELD_HEADER_LEN..];
+
+    if rest.len() < data_len {
+      self.bytes = &[];
+
+      return Some(Err(ParsingError::Custom(format!(
+        "Truncated extension field {tag}"
+      ))));
+    }
+
+    let (data, rest) = rest.split_at(data_len);
+
+    self.bytes = rest;
+
+    Some(Ok(BinExtensionField { tag, data }))
+  }
+}
+
+#[cfg(test)]
+mod extension_test {
+  use crate::parsers::bin::{
+    BIN_EXTENSION_VERSION, BinExtension, BinExtensionField,
+  };
+
+  #[test]
+  fn test_push_fields() {
+    let mut extension = BinExtension::new();
+
+    assert_eq!(extension.version(), None);
+
+    extension.push_field(1, b"EUR").unwrap();
+    extension.push_field(7, &[]).unwrap();
+
+    assert_eq!(
+      extension.as_bytes(),
+      &[BIN_EXTENSION_VERSION, 1, 0, 3, b'E', b'U', b'R', 7, 0, 0]
+    );
+    assert_eq!(extension.version(), Some(BIN_EXTENSION_VERSION));
+    assert_eq!(extension.field(1), Some(&b"EUR"[..]));
+    assert_eq!(extension.field(7), Some(&[][..]));
+    assert_eq!(extension.field(2), None);
+  }
+
+  #[test]
+  fn test_read_truncated_field() {
+    let extension = BinExtension::from_bytes(vec![2, 1, 0, 1, b'A', 5, 0, 4]);
+    let mut fields = extension.fields();
+
+    assert_eq!(extension.version(), Some(2));
+    assert_eq!(
+      fields.next().unwrap().unwrap(),
+      BinExtensionField { tag: 1, data: b"A" }
+    );
+    assert!(fields.next().unwrap().is_err());
+    assert!(fields.next().is_none());
+  }
+}
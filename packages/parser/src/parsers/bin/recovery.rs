@@ -0,0 +1,353 @@
+use crate::errors::ParsingError;
+use crate::parsers::bin::{
+  BinExtension, decode_record, read_record_bytes, scan_header,
+};
+use crate::record::BankRecord;
+use std::collections::VecDeque;
+use std::io;
+use std::io::{BufRead, ErrorKind, Read};
+
+/// Span of bytes the reader had to skip to find the next intact record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResyncEvent {
+  /// Stream offset of the first skipped byte
+  pub offset: u64,
+  /// Number of skipped bytes
+  pub skipped: u64,
+}
+
+/// Binary records reader which skips damaged records instead of failing and
+/// keeps track of every skipped span of the stream.
+///
+/// Record is considered damaged if its layout does not match `RECORD_SIZE`,
+/// its checksum does not match or its fields hold unknown values. Scanning for
+/// the next header is resumed from the byte following the damaged record
+/// header, so intact records overlapped by a damaged one are not lost. Bytes
+/// read for a damaged record are limited by `BIN_RECORD_MAX_SIZE`.
+pub struct BinRecoveryReader<R> {
+  source: PushbackReader<R>,
+  offset: u64,
+  record_offset: u64,
+  events: Vec<ResyncEvent>,
+}
+
+impl<R: BufRead> BinRecoveryReader<R> {
+  pub fn new(reader: R) -> Self {
+    Self {
+      source: PushbackReader::new(reader),
+      offset: 0,
+      record_offset: 0,
+      events: vec![],
+    }
+  }
+
+  /// Reads next intact record, returns `None` at the end of the stream.
+  /// Only errors of the underlying reader are returned.
+  pub fn read_record(&mut self) -> Result<Option<BankRecord>, ParsingError> {
+    self
+      .read_record_extended()
+      .map(|record| record.map(|(record, _extension)| record))
+  }
+
+  /// Reads next intact record together with its extension area
+  pub fn read_record_extended(
+    &mut self,
+  ) -> Result<Option<(BankRecord, BinExtension)>, ParsingError> {
+    let resync_start = self.offset;
+
+    loop {
+      let mut consumed = 0;
+      let scan_result = scan_header(&mut self.source, &mut consumed);
+
+      self.offset += consumed;
+
+      let magic = match scan_result {
+        Ok(magic) => magic,
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+          self.resync(resync_start, self.offset);
+
+          return Ok(None);
+        }
+        Err(err) => return Err(ParsingError::IO(err)),
+      };
+      let header_offset = self.offset - magic.len() as u64;
+      let mut raw_record = magic.to_vec();
+
+      let record = read_record_bytes(&mut self.source, &mut raw_record)
+        .and_then(|_| decode_record(&raw_record));
+
+      match record {
+        Ok(record) => {
+          self.resync(resync_start, header_offset);
+          self.offset = header_offset + raw_record.len() as u64;
+          self.record_offset = header_offset;
+
+          return Ok(Some(record));
+        }
+        Err(ParsingError::IO(err)) => return Err(ParsingError::IO(err)),
+        Err(_) => {
+          // Damaged record, continue scanning from the byte following its
+          // header start, skipped span keeps growing from `resync_start`
+          self.source.unread(&raw_record[1..]);
+          self.offset = header_offset + 1;
+        }
+      }
+    }
+  }
+
+  /// Skipped spans found so far
+  pub fn resync_events(&self) -> &[ResyncEvent] {
+    &self.events
+  }
+
+  /// Stream offset of the last returned record header
+  pub fn record_offset(&self) -> u64 {
+    self.record_offset
+  }
+
+  /// Stream offset right after the last returned record
+  pub fn offset(&self) -> u64 {
+    self.offset
+  }
+
+  pub fn into_inner(self) -> R {
+    self.source.inner
+  }
+
+  fn resync(&mut self, from: u64, to: u64) {
+    if to > from {
+      self.events.push(ResyncEvent {
+        offset: from,
+        skipped: to - from,
+      });
+    }
+  }
+}
+
+impl<R: BufRead> Iterator for BinRecoveryReader<R> {
+  type Item = Result<BankRecord, ParsingError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.read_record().transpose()
+  }
+}
+
+/// Buffered reader which allows to return already read bytes back
+struct PushbackReader<R> {
+  inner: R,
+  pushback: VecDeque<u8>,
+}
+
+impl<R> PushbackReader<R> {
+  fn new(inner: R) -> Self {
+    Self {
+      inner,
+      pushback: VecDeque::new(),
+    }
+  }
+
+  /// Puts bytes in front of the not yet read data, bytes which are already
+  /// pushed back are not copied again
+  fn unread(&mut self, bytes: &[u8]) {
+    for &byte in bytes.iter().rev() {
+      self.pushback.push_front(byte);
+    }
+  }
+}
+
+impl<R: BufRead> Read for PushbackReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let available = self.fill_buf()?;
+    let len = available.len().min(buf.len());
+
+    buf[..len].copy_from_slice(&available[..len]);
+    self.consume(len);
+
+    Ok(len)
+  }
+}
+
+impl<R: BufRead> BufRead for PushbackReader<R> {
+  fn fill_buf(&mut self) -> io::Result<&[u8]> {
+    if !self.pushback.is_empty() {
+      // Front slice of a non-empty deque is never empty
+      return Ok(self.pushback.as_slices().0);
+    }
+
+    self.inner.fill_buf()
+  }
+
+  fn consume(&mut self, amount: usize) {
+    if self.pushback.is_empty() {
+      self.inner.consume(amount);
+    } else {
+      self.pushback.drain(..amount);
+    }
+  }
+}
+
+#[cfg(test)]
+mod recovery_test {
+  use crate::parsers::bin::{
+    BIN_RECORD_HEADER, BinRecord, BinRecoveryReader, CheckedBinRecord,
+    ResyncEvent,
+  };
+  use crate::record::{BankRecord, BankRecordParser};
+  use std::io::Cursor;
+
+  fn record(tx_id: u64) -> BankRecord {
+    BankRecord {
+      tx_id,
+      amount: 100,
+      description: format!("Record number {tx_id}"),
+      ..BankRecord::new()
+    }
+  }
+
+  fn serialize(tx_id: u64) -> Vec<u8> {
+    let mut buff: Vec<u8> = vec![];
+
+    BinRecord(record(tx_id)).write_to(&mut buff).unwrap();
+
+    buff
+  }
+
+  #[test]
+  fn test_read_intact_stream() {
+    let mut buff = serialize(1);
+    buff.extend(serialize(2));
+
+    let mut reader = BinRecoveryReader::new(Cursor::new(buff));
+
+    assert_eq!(reader.read_record().unwrap(), Some(record(1)));
+    assert_eq!(reader.read_record().unwrap(), Some(record(2)));
+    assert_eq!(reader.read_record().unwrap(), None);
+    assert!(reader.resync_events().is_empty());
+  }
+
+  #[test]
+  fn test_report_junk_between_records() {
+    let first = serialize(1);
+    let mut buff = first.clone();
+    buff.extend_from_slice(b"Hello Kitty");
+    buff.extend(serialize(2));
+    buff.extend_from_slice(b"Bye");
+
+    let mut reader = BinRecoveryReader::new(Cursor::new(buff));
+    let records: Vec<BankRecord> =
+      reader.by_ref().map(Result::unwrap).collect();
+
+    assert_eq!(records, vec![record(1), record(2)]);
+    assert_eq!(
+      reader.resync_events(),
+      &[
+        ResyncEvent {
+          offset: first.len() as u64,
+          skipped: 11
+        },
+        ResyncEvent {
+          offset: (first.len() * 2 + 11) as u64,
+          skipped: 3
+        }
+      ]
+    );
+  }
+
+  #[test]
+  fn test_skip_layout_shift() {
+    let mut buff: Vec<u8> = vec![];
+
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    buff.extend_from_slice(&63u32.to_be_bytes()[..]);
+    buff.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&[0u8]);
+    buff.extend_from_slice(&0u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    // Missing amount field
+    buff.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    buff.extend_from_slice(&[0u8]);
+    buff.extend_from_slice(&0u32.to_be_bytes()[..]);
+
+    let damaged_len = buff.len() as u64;
+    // Intact record is overlapped by the declared size of the damaged one
+    buff.extend(serialize(2));
+
+    let mut reader = BinRecoveryReader::new(Cursor::new(buff));
+
+    assert_eq!(reader.read_record().unwrap(), Some(record(2)));
+    assert_eq!(reader.record_offset(), damaged_len);
+    assert_eq!(reader.read_record().unwrap(), None);
+    assert_eq!(
+      reader.resync_events(),
+      &[ResyncEvent {
+        offset: 0,
+        skipped: damaged_len
+      }]
+    );
+  }
+
+  #[test]
+  fn test_skip_damaged_record_size() {
+    let mut buff: Vec<u8> = vec![];
+
+    buff.extend_from_slice(BIN_RECORD_HEADER);
+    buff.extend_from_slice(&0x7fffffffu32.to_be_bytes()[..]);
+    buff.extend(serialize(1));
+    buff.extend(serialize(2));
+
+    let mut reader = BinRecoveryReader::new(Cursor::new(buff));
+
+    assert_eq!(reader.read_record().unwrap(), Some(record(1)));
+    assert_eq!(reader.read_record().unwrap(), Some(record(2)));
+    assert_eq!(reader.read_record().unwrap(), None);
+    assert_eq!(
+      reader.resync_events(),
+      &[ResyncEvent {
+        offset: 0,
+        skipped: 8
+      }]
+    );
+  }
+
+  #[test]
+  fn test_skip_checksum_mismatch() {
+    let mut buff: Vec<u8> = vec![];
+
+    CheckedBinRecord(record(1)).write_to(&mut buff).unwrap();
+
+    let damaged_len = buff.len() as u64;
+    // Flip a bit in the amount field
+    buff[40] ^= 1;
+    buff.extend(serialize(2));
+
+    let mut reader = BinRecoveryReader::new(Cursor::new(buff));
+
+    assert_eq!(reader.read_record().unwrap(), Some(record(2)));
+    assert_eq!(
+      reader.resync_events(),
+      &[ResyncEvent {
+        offset: 0,
+        skipped: damaged_len
+      }]
+    );
+  }
+
+  #[test]
+  fn test_report_truncated_record() {
+    let first = serialize(1);
+    let mut buff = first.clone();
+    buff.extend_from_slice(&serialize(2)[..20]);
+
+    let mut reader = BinRecoveryReader::new(Cursor::new(buff));
+
+    assert_eq!(reader.read_record().unwrap(), Some(record(1)));
+    assert_eq!(reader.read_record().unwrap(), None);
+    assert_eq!(
+      reader.resync_events(),
+      &[ResyncEvent {
+        offset: first.len() as u64,
+        skipped: 20
+      }]
+    );
+  }
+}
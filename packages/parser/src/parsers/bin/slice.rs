@@ -0,0 +1,132 @@
+use crate::errors::ParsingError;
+use crate::parsers::bin::{decode_record_ref, is_record_header, record_len};
+use crate::record::BankRecordRef;
+
+/// Binary records reader over an in-memory buffer, records are borrowed from
+/// the buffer instead of being copied.
+///
+/// Junk data between records is skipped the same way as
+/// [BinRecord](crate::parsers::BinRecord) does it. Iteration may be continued
+/// after an error, scanning is resumed right after the broken record header.
+pub struct BinSliceReader<'a> {
+  data: &'a [u8],
+  offset: usize,
+}
+
+impl<'a> BinSliceReader<'a> {
+  pub fn new(data: &'a [u8]) -> Self {
+    Self { data, offset: 0 }
+  }
+
+  /// Reads the next record together with bytes of its extension area,
+  /// returns `None` if there are no more record headers in the buffer
+  pub fn read_record_extended(
+    &mut self,
+  ) -> Option<Result<(BankRecordRef<'a>, &'a [u8]), ParsingError>> {
+    let data = self.data;
+    let header_offset = self.offset
+      + data[self.offset..]
+        .windows(4)
+        .position(|window| is_record_header(window.try_into().unwrap()))?;
+
+    let record = record_len(&data[header_offset..]).and_then(|len| {
+      self.offset = header_offset + len;
+
+      decode_record_ref(&data[header_offset..header_offset + len])
+    });
+
+    if let Err(err) = &record {
+      self.offset = match err {
+        ParsingError::TruncatedRecord { .. } => data.len(),
+        _ => header_offset + 1,
+      };
+    }
+
+    Some(record)
+  }
+
+  /// Offset of the first byte which is not read yet
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+}
+
+impl<'a> Iterator for BinSliceReader<'a> {
+  type Item = Result<BankRecordRef<'a>, ParsingError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self
+      .read_record_extended()
+      .map(|record| record.map(|(record, _extension)| record))
+  }
+}
+
+#[cfg(test)]
+mod slice_test {
+  use crate::parsers::bin::{BinExtension, BinRecord, BinSliceReader};
+  use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+
+  fn record(tx_id: u64) -> BankRecord {
+    BankRecord {
+      tx_id,
+      tx_type: TxType::Deposit,
+      from_user_id: 0,
+      to_user_id: 42,
+      amount: 500,
+      timestamp: 1633036860000,
+      status: Status::Pending,
+      description: format!("Record number {tx_id}"),
+    }
+  }
+
+  #[test]
+  fn test_read_borrowed_records() {
+    let mut buff: Vec<u8> = b"junk".to_vec();
+    BinRecord(record(1)).write_to(&mut buff).unwrap();
+    buff.extend_from_slice(b"more junk");
+    BinRecord(record(2)).write_to(&mut buff).unwrap();
+
+    let records: Vec<BankRecord> = BinSliceReader::new(&buff)
+      .map(|record| record.unwrap().to_owned_record())
+      .collect();
+
+    assert_eq!(records, vec![record(1), record(2)]);
+  }
+
+  #[test]
+  fn test_read_extension_area() {
+    let mut extension = BinExtension::new();
+    extension.push_field(1, b"EUR").unwrap();
+
+    let mut buff: Vec<u8> = vec![];
+    BinRecord::write_extended_to(
+      &mut BinRecord(record(1)),
+      &extension,
+      &mut buff,
+    )
+    .unwrap();
+
+    let (record_ref, extension_bytes) = BinSliceReader::new(&buff)
+      .read_record_extended()
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(record_ref.description, "Record number 1");
+    assert_eq!(extension_bytes, extension.as_bytes());
+  }
+
+  #[test]
+  fn test_read_truncated_record() {
+    let mut buff: Vec<u8> = vec![];
+    BinRecord(record(1)).write_to(&mut buff).unwrap();
+    BinRecord(record(2)).write_to(&mut buff).unwrap();
+    buff.truncate(buff.len() - 10);
+
+    let mut reader = BinSliceReader::new(&buff);
+
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+    assert_eq!(reader.offset(), buff.len());
+  }
+}
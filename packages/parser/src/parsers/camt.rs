@@ -0,0 +1,441 @@
+//! ISO 20022 camt.053 bank to customer statements.
+//!
+//! Every statement entry (`Ntry`) is a record, the document envelope is
+//! written by `CamtRecord::write_header` and `CamtRecord::write_footer` and
+//! skipped while reading. Fields are mapped as follows:
+//!
+//! | Record field   | camt.053 entry element                                |
+//! |----------------|-------------------------------------------------------|
+//! | `TX_ID`        | `NtryRef`                                             |
+//! | `TX_TYPE`      | `BkTxCd/Prtry/Cd` if it's a transaction type, else    |
+//! |                | `TRANSFER` if both parties are users, `DEPOSIT` for   |
+//! |                | `CdtDbtInd` `CRDT` and `WITHDRAWAL` for `DBIT`        |
+//! | `FROM_USER_ID` | `NtryDtls/TxDtls/RltdPties/DbtrAcct/Id/Othr/Id`, the  |
+//! |                | bank (`0`) if omitted                                 |
+//! | `TO_USER_ID`   | `NtryDtls/TxDtls/RltdPties/CdtrAcct/Id/Othr/Id`, the  |
+//! |                | bank (`0`) if omitted                                 |
+//! | `AMOUNT`       | `Amt` in minor units, e.g. `10.50` is `1050`          |
+//! | `TIMESTAMP`    | `BookgDt/DtTm` or the start of `BookgDt/Dt` in UTC    |
+//! | `STATUS`       | `Sts/Cd` or `Sts`: `BOOK` is `SUCCESS`, `PDNG` and    |
+//! |                | `FUTR` are `PENDING`, `INFO` is `FAILURE`             |
+//! | `DESCRIPTION`  | `NtryDtls/TxDtls/RmtInf/Ustrd`, joined by spaces      |
+//!
+//! Deposits are written as credits, transfers and withdrawals as debits.
+//! Amounts are written in `CAMT_CURRENCY`, currencies of read amounts are not
+//! checked. Entries with several transaction details are read as a single
+//! record.
+
+use crate::date::{Date, format_date_time, parse_date_time};
+use crate::errors::{ParsingError, SerializeError};
+use crate::parsers::statement::{format_amount, parse_amount};
+use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use std::io;
+use std::io::{BufRead, ErrorKind, Write};
+use std::str::FromStr;
+
+pub struct CamtRecord(pub BankRecord);
+
+/// Namespace of written documents, camt.053 version 8
+pub const CAMT_NAMESPACE: &str =
+  "urn:iso:std:iso:20022:tech:xsd:camt.053.001.08";
+
+/// Currency of written amounts
+pub const CAMT_CURRENCY: &str = "EUR";
+
+/// Texts of the entry elements mapped to record fields
+#[derive(Debug, Default)]
+struct Entry {
+  reference: Option<String>,
+  amount: Option<String>,
+  indicator: Option<String>,
+  status: Option<String>,
+  booking: Option<String>,
+  booking_date: Option<String>,
+  tx_type: Option<String>,
+  debtor: Option<String>,
+  creditor: Option<String>,
+  description: Vec<String>,
+}
+
+fn missing(element: &str) -> ParsingError {
+  ParsingError::Custom(format!("camt.053 entry has no {element} element"))
+}
+
+fn invalid(element: &str, value: &str) -> ParsingError {
+  ParsingError::Custom(format!("Invalid camt.053 {element} value: {value:?}"))
+}
+
+impl Entry {
+  /// Stores the text of the element at the path relative to the entry
+  fn set(&mut self, path: &str, text: String) {
+    let field = match path {
+      "NtryRef" => &mut self.reference,
+      "Amt" => &mut self.amount,
+      "CdtDbtInd" => &mut self.indicator,
+      "Sts" | "Sts/Cd" => &mut self.status,
+      "BookgDt/DtTm" => &mut self.booking,
+      "BookgDt/Dt" => &mut self.booking_date,
+      "BkTxCd/Prtry/Cd" => &mut self.tx_type,
+      "NtryDtls/TxDtls/RltdPties/DbtrAcct/Id/Othr/Id" => &mut self.debtor,
+      "NtryDtls/TxDtls/RltdPties/CdtrAcct/Id/Othr/Id" => &mut self.creditor,
+      "NtryDtls/TxDtls/RmtInf/Ustrd" => {
+        self.description.push(text);
+        return;
+      }
+      _ => return,
+    };
+
+    *field = Some(text);
+  }
+
+  fn into_record(self) -> Result<BankRecord, ParsingError> {
+    let user_id = |account: Option<String>, element: &str| match account {
+      Some(account) => account.parse().map_err(|_| invalid(element, &account)),
+      None => Ok(0),
+    };
+    let from_user_id = user_id(self.debtor, "DbtrAcct")?;
+    let to_user_id = user_id(self.creditor, "CdtrAcct")?;
+
+    let indicator = self.indicator.ok_or_else(|| missing("CdtDbtInd"))?;
+    let tx_type =
+      match self.tx_type.and_then(|code| TxType::from_str(&code).ok()) {
+        Some(tx_type) => tx_type,
+        None if from_user_id != 0 && to_user_id != 0 => TxType::Transfer,
+        None => match indicator.as_str() {
+          "CRDT" => TxType::Deposit,
+          "DBIT" => TxType::Withdrawal,
+          _ => return Err(invalid("CdtDbtInd", &indicator)),
+        },
+      };
+
+    let status = self.status.ok_or_else(|| missing("Sts"))?;
+    let status = match status.as_str() {
+      "BOOK" => Status::Success,
+      "PDNG" | "FUTR" => Status::Pending,
+      "INFO" => Status::Failure,
+      _ => return Err(invalid("Sts", &status)),
+    };
+
+    let timestamp = match (self.booking, self.booking_date) {
+      (Some(booking), _) => {
+        parse_date_time(&booking).ok_or_else(|| invalid("DtTm", &booking))?
+      }
+      (None, Some(date)) => Date::from_str(&date)
+        .ok()
+        .and_then(|date| date.timestamp())
+        .ok_or_else(|| invalid("Dt", &date))?,
+      (None, None) => return Err(missing("BookgDt")),
+    };
+
+    let reference = self.reference.ok_or_else(|| missing("NtryRef"))?;
+    let amount = self.amount.ok_or_else(|| missing("Amt"))?;
+
+    Ok(BankRecord {
+      tx_id: reference
+        .parse()
+        .map_err(|_| invalid("NtryRef", &reference))?,
+      tx_type,
+      from_user_id,
+      to_user_id,
+      amount: parse_amount(&amount, '.')
+        .ok_or_else(|| invalid("Amt", &amount))?,
+      timestamp,
+      status,
+      description: self.description.join(" "),
+    })
+  }
+}
+
+impl CamtRecord {
+  /// Writes the document envelope up to the first entry, the statement of
+  /// the bank account `0` is identified by `statement_id`
+  pub fn write_header<W: Write>(
+    writer: &mut W,
+    statement_id: &str,
+    created: u64,
+  ) -> Result<(), SerializeError> {
+    let statement_id = escape(statement_id);
+    let created = format_date_time(created);
+
+    write!(
+      writer,
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="{CAMT_NAMESPACE}">
+  <BkToCstmrStmt>
+    <GrpHdr>
+      <MsgId>{statement_id}</MsgId>
+      <CreDtTm>{created}</CreDtTm>
+    </GrpHdr>
+    <Stmt>
+      <Id>{statement_id}</Id>
+      <CreDtTm>{created}</CreDtTm>
+      <Acct>
+        <Id>
+          <Othr>
+            <Id>0</Id>
+          </Othr>
+        </Id>
+      </Acct>
+"#
+    )?;
+
+    Ok(())
+  }
+
+  /// Completes the document after the last entry
+  pub fn write_footer<W: Write>(writer: &mut W) -> Result<(), SerializeError> {
+    writeln!(writer, "    </Stmt>\n  </BkToCstmrStmt>\n</Document>")?;
+
+    Ok(())
+  }
+}
+
+impl BankRecordParser for CamtRecord {
+  /// Reads the next entry, elements before it are skipped
+  fn from_read<R: BufRead>(buffer: &mut R) -> Result<BankRecord, ParsingError> {
+    let mut reader = Reader::from_reader(buffer);
+    // Reading starts in the middle of the document, so end tags of the
+    // envelope have no start tags
+    reader.config_mut().trim_text(true);
+    reader.config_mut().allow_unmatched_ends = true;
+
+    let mut buf = vec![];
+    // Elements of the current entry, empty outside of entries
+    let mut path: Vec<String> = vec![];
+    let mut entry = Entry::default();
+
+    loop {
+      match reader.read_event_into(&mut buf)? {
+        Event::Start(element) => {
+          let name =
+            String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+          if !path.is_empty() || name == "Ntry" {
+            path.push(name);
+          }
+        }
+        Event::End(_) if !path.is_empty() => {
+          path.pop();
+          if path.is_empty() {
+            return entry.into_record();
+          }
+        }
+        Event::Text(text) if path.len() > 1 => {
+          entry.set(&path[1..].join("/"), text.unescape()?.into_owned());
+        }
+        Event::Eof if !path.is_empty() => {
+          return Err(ParsingError::Custom(String::from(
+            "camt.053 entry is not complete",
+          )));
+        }
+        Event::Eof => {
+          return Err(ParsingError::IO(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "Document has no more camt.053 entries",
+          )));
+        }
+        _ => {}
+      }
+
+      buf.clear();
+    }
+  }
+
+  fn write_to<W: Write>(
+    &mut self,
+    buffer: &mut W,
+  ) -> Result<(), SerializeError> {
+    let record = &self.0;
+    let indicator = match record.tx_type {
+      TxType::Deposit => "CRDT",
+      TxType::Transfer | TxType::Withdrawal => "DBIT",
+    };
+    let status = match record.status {
+      Status::Success => "BOOK",
+      Status::Pending => "PDNG",
+      Status::Failure => "INFO",
+    };
+    let account = |element: &str, user_id: u64| match user_id {
+      0 => String::new(),
+      user_id => format!(
+        "
+            <{element}>
+              <Id>
+                <Othr>
+                  <Id>{user_id}</Id>
+                </Othr>
+              </Id>
+            </{element}>"
+      ),
+    };
+
+    writeln!(
+      buffer,
+      r#"      <Ntry>
+        <NtryRef>{}</NtryRef>
+        <Amt Ccy="{CAMT_CURRENCY}">{}</Amt>
+        <CdtDbtInd>{indicator}</CdtDbtInd>
+        <Sts>
+          <Cd>{status}</Cd>
+        </Sts>
+        <BookgDt>
+          <DtTm>{}</DtTm>
+        </BookgDt>
+        <BkTxCd>
+          <Prtry>
+            <Cd>{}</Cd>
+          </Prtry>
+        </BkTxCd>
+        <NtryDtls>
+          <TxDtls>
+            <RltdPties>{}{}
+            </RltdPties>
+            <RmtInf>
+              <Ustrd>{}</Ustrd>
+            </RmtInf>
+          </TxDtls>
+        </NtryDtls>
+      </Ntry>"#,
+      record.tx_id,
+      format_amount(record.amount, '.'),
+      format_date_time(record.timestamp),
+      record.tx_type,
+      account("DbtrAcct", record.from_user_id),
+      account("CdtrAcct", record.to_user_id),
+      escape(&record.description),
+    )?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod camt_test {
+  use crate::errors::ParsingError;
+  use crate::parsers::CamtRecord;
+  use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+  use std::io::Cursor;
+
+  const SAMPLE: &str = include_str!("../../../../mocks/camt053_example.xml");
+
+  fn read_all(data: &[u8]) -> Result<Vec<BankRecord>, ParsingError> {
+    let mut reader = Cursor::new(data);
+    let mut records = vec![];
+    loop {
+      match CamtRecord::from_read(&mut reader) {
+        Ok(record) => records.push(record),
+        Err(ParsingError::IO(err))
+          if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+        {
+          return Ok(records);
+        }
+        Err(err) => return Err(err),
+      }
+    }
+  }
+
+  fn sample_records() -> Vec<BankRecord> {
+    vec![
+      BankRecord {
+        tx_id: 1000000000000000,
+        tx_type: TxType::Deposit,
+        from_user_id: 0,
+        to_user_id: 9223372036854775807,
+        amount: 10050,
+        timestamp: 1633036860000,
+        status: Status::Success,
+        description: String::from("Salary & bonus September"),
+      },
+      BankRecord {
+        tx_id: 1000000000000001,
+        tx_type: TxType::Transfer,
+        from_user_id: 9223372036854775807,
+        to_user_id: 42,
+        amount: 2000,
+        timestamp: 1633046400000,
+        status: Status::Failure,
+        description: String::from("Invoice 17 part 1"),
+      },
+      BankRecord {
+        tx_id: 1000000000000002,
+        tx_type: TxType::Withdrawal,
+        from_user_id: 42,
+        to_user_id: 0,
+        amount: 500,
+        timestamp: 1633046400000,
+        status: Status::Pending,
+        description: String::new(),
+      },
+    ]
+  }
+
+  #[test]
+  fn test_read_sample() -> Result<(), ParsingError> {
+    assert_eq!(read_all(SAMPLE.as_bytes())?, sample_records());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_and_read_back() -> Result<(), ParsingError> {
+    // Transaction types differing from the parties are kept as well
+    let refund = || BankRecord {
+      tx_id: 3,
+      tx_type: TxType::Deposit,
+      from_user_id: 7,
+      to_user_id: 8,
+      amount: 1,
+      timestamp: 1633036860123,
+      status: Status::Success,
+      description: String::from("<Refund> \"42\""),
+    };
+    let mut data: Vec<u8> = vec![];
+
+    CamtRecord::write_header(&mut data, "STMT-1", 1633046400000).unwrap();
+    for record in sample_records().into_iter().chain([refund()]) {
+      CamtRecord(record).write_to(&mut data).unwrap();
+    }
+    CamtRecord::write_footer(&mut data).unwrap();
+
+    let document = String::from_utf8(data.clone()).unwrap();
+    assert!(document.contains("<CreDtTm>2021-10-01T00:00:00.000Z</CreDtTm>"));
+    assert!(document.contains("<Ustrd>&lt;Refund&gt; &quot;42&quot;</Ustrd>"));
+    assert!(document.ends_with("</Document>\n"));
+    let mut records = sample_records();
+    records.push(refund());
+    assert_eq!(read_all(&data)?, records);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_invalid_entries() {
+    let entry = |content: &str| {
+      read_all(
+        format!("<Document><Ntry>{content}</Ntry></Document>").as_bytes(),
+      )
+    };
+
+    assert!(matches!(
+      entry("<NtryRef>1</NtryRef><Amt>1.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>"),
+      Err(ParsingError::Custom(_))
+    ));
+    assert!(matches!(
+      entry(
+        "<NtryRef>1</NtryRef><Amt>1.001</Amt><CdtDbtInd>CRDT</CdtDbtInd>\
+         <Sts>BOOK</Sts><BookgDt><Dt>2021-10-01</Dt></BookgDt>"
+      ),
+      Err(ParsingError::Custom(_))
+    ));
+    assert!(matches!(
+      entry("<NtryRef>1</Amt>"),
+      Err(ParsingError::Xml(_))
+    ));
+    assert!(matches!(
+      read_all(b"<Document><Ntry><NtryRef>1</NtryRef>"),
+      Err(ParsingError::Custom(_))
+    ));
+  }
+}
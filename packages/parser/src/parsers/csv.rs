@@ -1,5 +1,5 @@
 use crate::constants::{RECORD_LINES_NUMBER, record_field};
-use crate::errors::{ParsingError, SerializeError};
+use crate::errors::{ParsingError, SerializeError, StatusTypeError};
 use crate::record::{BankRecord, BankRecordParser, Status, TxType};
 use std::io;
 use std::io::{BufRead, ErrorKind, Write};
@@ -16,7 +16,10 @@ impl BankRecordParser for CsvRecord {
     let mut bank_record = BankRecord::new();
 
     let line = lines.next().ok_or_else(|| {
-      ParsingError::Custom("EOF: File has no lines to read".to_string())
+      ParsingError::IO(io::Error::new(
+        ErrorKind::UnexpectedEof,
+        "EOF: File has no lines to read",
+      ))
     })?;
     let line = line?;
 
@@ -55,7 +58,9 @@ impl BankRecordParser for CsvRecord {
           bank_record.timestamp = field_value.parse::<u64>()?;
         }
         record_field::STATUS => {
-          bank_record.status = Status::from_str(field_value)?;
+          bank_record.status = Status::from_str(field_value).map_err(|_| {
+            StatusTypeError::InvalidSting(field_value.to_string())
+          })?;
         }
         record_field::DESCRIPTION => {
           bank_record.description = field_value.replace('"', "");
@@ -0,0 +1,794 @@
+//! SWIFT MT940 customer statements.
+//!
+//! Every statement line (`:61:`) with the following information to the
+//! account owner (`:86:`) is a record. Statements are read by [Mt940Reader],
+//! which keeps the account of the current statement (`:25:`), and records
+//! are written by [Mt940Record] into a statement of the bank account `0`.
+//! Fields are mapped as follows:
+//!
+//! | Record field   | MT940 statement line                                  |
+//! |----------------|-------------------------------------------------------|
+//! | `TX_ID`        | Reference for the account owner, or the reference of  |
+//! |                | the account servicing institution after `//`          |
+//! | `TX_TYPE`      | `TRANSFER` if both parties are users, `DEPOSIT` for   |
+//! |                | credits and `WITHDRAWAL` for debits                   |
+//! | `FROM_USER_ID` | `:86:` `/ORDP/` account, else the `/CNTP/` account of |
+//! |                | credits or the statement account of debits            |
+//! | `TO_USER_ID`   | `:86:` `/BENM/` account, else the statement account   |
+//! |                | of credits or the `/CNTP/` account of debits          |
+//! | `AMOUNT`       | Amount in minor units, e.g. `10,50` is `1050`         |
+//! | `TIMESTAMP`    | Start of the value date in UTC                        |
+//! | `STATUS`       | Supplementary details if they are a status, else      |
+//! |                | `SUCCESS`                                             |
+//! | `DESCRIPTION`  | `:86:` text after `/REMI/`, or the whole text if it   |
+//! |                | doesn't start with a code                             |
+//!
+//! Accounts are mapped to user ids by [Mt940Accounts], a missing counterparty
+//! is the bank (`0`). Reversals of credits are read as debits and reversals
+//! of debits as credits. Lines of `:86:` are joined without separators, as
+//! written descriptions are wrapped at 65 characters.
+//!
+//! Deposits are written as credits, transfers and withdrawals as debits.
+//! Times of day aren't kept and value dates have two-digit years, which are
+//! read as years from 2000 to 2099. Written statements open with a zero
+//! balance, the closing balance is tracked by [Mt940Balance].
+
+use crate::date::Date;
+use crate::errors::{ParsingError, SerializeError};
+use crate::parsers::statement::{
+  format_amount, format_signed_amount, parse_amount,
+};
+use crate::record::{BankRecord, Status, TxType};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+pub struct Mt940Record(pub BankRecord);
+
+/// Currency of written amounts and balances
+pub const MT940_CURRENCY: &str = "EUR";
+
+/// Maximum length of `:86:` lines
+const INFORMATION_LINE_LENGTH: usize = 65;
+
+/// Pseudo tag of the statement end (`-`)
+const END_TAG: &str = "-";
+
+/// Balance of a written statement in minor units, dated by the latest value
+/// date of its statement lines
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Mt940Balance {
+  pub amount: i128,
+  pub date: u64,
+}
+
+/// Mapping of statement accounts to user ids, numeric accounts which aren't
+/// mapped are user ids themselves
+#[derive(Debug, Default, Clone)]
+pub struct Mt940Accounts(HashMap<String, u64>);
+
+impl Mt940Accounts {
+  pub fn insert(&mut self, account: impl Into<String>, user_id: u64) {
+    self.0.insert(account.into(), user_id);
+  }
+
+  pub fn user_id(&self, account: &str) -> Option<u64> {
+    self
+      .0
+      .get(account)
+      .copied()
+      .or_else(|| account.parse().ok())
+  }
+}
+
+impl FromIterator<(String, u64)> for Mt940Accounts {
+  fn from_iter<T: IntoIterator<Item = (String, u64)>>(iter: T) -> Self {
+    Self(iter.into_iter().collect())
+  }
+}
+
+/// Reads records of all statements of MT940 files, lines outside of
+/// statements, e.g. SWIFT message headers, are skipped. Statement lines
+/// which can't be converted are returned as errors and reading continues,
+/// read errors end the records.
+pub struct Mt940Reader<R> {
+  reader: R,
+  accounts: Mt940Accounts,
+  /// Account of the current statement
+  account: Option<String>,
+  /// Statement line waiting for its information
+  entry: Option<Entry>,
+  /// Line read ahead while looking for the field end, with its number
+  line: Option<(usize, String)>,
+  /// Number of lines read
+  lines: usize,
+  /// Line number of the last returned record
+  record_line: usize,
+  failed: bool,
+}
+
+/// Field with its tag and value lines
+struct Field {
+  tag: String,
+  lines: Vec<String>,
+  /// Number of the tag line
+  line_number: usize,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+  /// First line of the `:61:` field
+  line: String,
+  /// Number of the first line
+  line_number: usize,
+  /// Supplementary details, the second line of the `:61:` field
+  details: Option<String>,
+  /// Joined lines of the `:86:` field
+  information: String,
+}
+
+/// Parties and description of the `:86:` field
+#[derive(Debug, Default)]
+struct Information<'a> {
+  ordering: Option<&'a str>,
+  beneficiary: Option<&'a str>,
+  counterparty: Option<&'a str>,
+  description: &'a str,
+}
+
+fn missing(field: &str) -> ParsingError {
+  ParsingError::Custom(format!("MT940 statement has no {field} field"))
+}
+
+fn invalid(field: &str, value: &str) -> ParsingError {
+  ParsingError::Custom(format!("Invalid MT940 {field} value: {value:?}"))
+}
+
+/// Splits `:TAG:value` lines into the tag and the value, tags are two digits
+/// with an optional letter
+fn split_tag(line: &str) -> Option<(&str, &str)> {
+  let (tag, value) = line.strip_prefix(':')?.split_once(':')?;
+  let bytes = tag.as_bytes();
+  let valid = matches!(bytes.len(), 2 | 3)
+    && bytes[..2].iter().all(u8::is_ascii_digit)
+    && bytes.get(2).is_none_or(u8::is_ascii_uppercase);
+
+  valid.then_some((tag, value))
+}
+
+/// Statements end with `-`, which is followed by `}` in SWIFT messages
+fn is_end(line: &str) -> bool {
+  line == END_TAG || line.starts_with("-}")
+}
+
+/// Line starting with `chars` would be read as a field or the statement end
+fn starts_field(chars: &[char]) -> bool {
+  let start: String = chars.iter().take(6).collect();
+
+  is_end(&start) || split_tag(&start).is_some()
+}
+
+/// Length of the next `:86:` line. Lines are broken before a character
+/// other than `:` and `-` if there is one, else before a line which is not
+/// read as a field. Lines are never a single `-`.
+fn information_line_length(chars: &[char]) -> usize {
+  if chars.len() <= INFORMATION_LINE_LENGTH {
+    return chars.len();
+  }
+
+  let lengths = || {
+    (1..=INFORMATION_LINE_LENGTH)
+      .rev()
+      .filter(|&length| length > 1 || chars[0] != '-')
+  };
+
+  lengths()
+    .find(|&length| !matches!(chars[length], ':' | '-'))
+    .or_else(|| lengths().find(|&length| !starts_field(&chars[length..])))
+    .unwrap_or(INFORMATION_LINE_LENGTH)
+}
+
+/// Value dates are `YYMMDD`
+fn format_value_date(timestamp: u64) -> String {
+  let date = Date::from_timestamp(timestamp);
+
+  format!("{:02}{:02}{:02}", date.year % 100, date.month, date.day)
+}
+
+fn parse_value_date(value: &str) -> Option<u64> {
+  if value.len() != 6 || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+    return None;
+  }
+
+  Date {
+    year: 2000 + value[..2].parse::<u32>().ok()?,
+    month: value[2..4].parse().ok()?,
+    day: value[4..].parse().ok()?,
+  }
+  .timestamp()
+}
+
+impl<'a> Information<'a> {
+  fn parse(text: &'a str) -> Self {
+    if !text.starts_with('/') {
+      return Self {
+        description: text,
+        ..Self::default()
+      };
+    }
+
+    let (codes, description) = text.split_once("/REMI/").unwrap_or((text, ""));
+    let mut information = Self {
+      description,
+      ..Self::default()
+    };
+    let mut tokens = codes.split('/');
+    while let Some(token) = tokens.next() {
+      let party = match token {
+        "ORDP" => &mut information.ordering,
+        "BENM" => &mut information.beneficiary,
+        "CNTP" => &mut information.counterparty,
+        _ => continue,
+      };
+      *party = tokens.next().filter(|account| !account.is_empty());
+    }
+
+    information
+  }
+}
+
+impl Entry {
+  fn new(field: Field) -> Self {
+    let Field {
+      mut lines,
+      line_number,
+      ..
+    } = field;
+    let details = (lines.len() > 1).then(|| lines.remove(1));
+
+    Self {
+      line: lines.swap_remove(0),
+      line_number,
+      details,
+      information: String::new(),
+    }
+  }
+
+  /// Converts the entry of the statement of `account`
+  fn into_record(
+    self,
+    account: Option<&str>,
+    accounts: &Mt940Accounts,
+  ) -> Result<BankRecord, ParsingError> {
+    let line = self.line.as_str();
+    let invalid_line = || invalid(":61:", line);
+
+    let timestamp = line
+      .get(..6)
+      .and_then(parse_value_date)
+      .ok_or_else(invalid_line)?;
+    let mut rest = &line[6..];
+    // Optional entry date, `MMDD`
+    if rest
+      .get(..4)
+      .is_some_and(|date| date.bytes().all(|byte| byte.is_ascii_digit()))
+    {
+      rest = &rest[4..];
+    }
+
+    let (credit, rest) =
+      [("RC", false), ("RD", true), ("C", true), ("D", false)]
+        .into_iter()
+        .find_map(|(mark, credit)| Some((credit, rest.strip_prefix(mark)?)))
+        .ok_or_else(invalid_line)?;
+    // Optional funds code, the third character of the currency code
+    let rest = rest
+      .strip_prefix(|char: char| char.is_ascii_alphabetic())
+      .unwrap_or(rest);
+
+    let amount_end = rest
+      .find(|char: char| !char.is_ascii_digit() && char != ',')
+      .ok_or_else(invalid_line)?;
+    let amount =
+      parse_amount(&rest[..amount_end], ',').ok_or_else(invalid_line)?;
+    // Transaction type identification code, e.g. `NTRF`
+    let rest = rest.get(amount_end + 4..).ok_or_else(invalid_line)?;
+
+    let (reference, bank_reference) =
+      rest.split_once("//").unwrap_or((rest, ""));
+    let tx_id = reference
+      .parse()
+      .or_else(|_| bank_reference.parse())
+      .map_err(|_| invalid(":61: reference", rest))?;
+
+    let information = Information::parse(&self.information);
+    let user_id = |account: &str| {
+      accounts.user_id(account).ok_or_else(|| {
+        ParsingError::Custom(format!(
+          "MT940 account {account:?} is not mapped to a user id"
+        ))
+      })
+    };
+    let statement_user_id = || user_id(account.ok_or_else(|| missing(":25:"))?);
+    let counterparty_user_id =
+      || information.counterparty.map_or(Ok(0), user_id);
+
+    let from_user_id = match information.ordering {
+      Some(account) => user_id(account)?,
+      None if credit => counterparty_user_id()?,
+      None => statement_user_id()?,
+    };
+    let to_user_id = match information.beneficiary {
+      Some(account) => user_id(account)?,
+      None if credit => statement_user_id()?,
+      None => counterparty_user_id()?,
+    };
+
+    let tx_type = if from_user_id != 0 && to_user_id != 0 {
+      TxType::Transfer
+    } else if credit {
+      TxType::Deposit
+    } else {
+      TxType::Withdrawal
+    };
+
+    Ok(BankRecord {
+      tx_id,
+      tx_type,
+      from_user_id,
+      to_user_id,
+      amount,
+      timestamp,
+      status: self
+        .details
+        .and_then(|details| Status::from_str(details.trim()).ok())
+        .unwrap_or(Status::Success),
+      description: information.description.to_string(),
+    })
+  }
+}
+
+impl<R: BufRead> Mt940Reader<R> {
+  pub fn new(reader: R, accounts: Mt940Accounts) -> Self {
+    Self {
+      reader,
+      accounts,
+      account: None,
+      entry: None,
+      line: None,
+      lines: 0,
+      record_line: 0,
+      failed: false,
+    }
+  }
+
+  /// Line number of the `:61:` field of the last returned record, or of the
+  /// line which couldn't be read. Lines are counted from 1.
+  pub fn line(&self) -> usize {
+    self.record_line
+  }
+
+  fn read_line(&mut self) -> io::Result<Option<(usize, String)>> {
+    if let Some(line) = self.line.take() {
+      return Ok(Some(line));
+    }
+
+    let mut line = vec![];
+    if self.reader.read_until(b'\n', &mut line)? == 0 {
+      return Ok(None);
+    }
+    self.lines += 1;
+    let line = line.strip_suffix(b"\n").unwrap_or(&line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+    Ok(Some((
+      self.lines,
+      String::from_utf8_lossy(line).into_owned(),
+    )))
+  }
+
+  /// Reads the next field with its continuation lines, lines before it are
+  /// skipped
+  fn read_field(&mut self) -> io::Result<Option<Field>> {
+    let mut field = loop {
+      let Some((line_number, line)) = self.read_line()? else {
+        return Ok(None);
+      };
+      if is_end(&line) {
+        return Ok(Some(Field {
+          tag: String::from(END_TAG),
+          lines: vec![],
+          line_number,
+        }));
+      }
+      if let Some((tag, value)) = split_tag(&line) {
+        break Field {
+          tag: tag.to_string(),
+          lines: vec![value.to_string()],
+          line_number,
+        };
+      }
+    };
+
+    while let Some((line_number, line)) = self.read_line()? {
+      if is_end(&line) || split_tag(&line).is_some() {
+        self.line = Some((line_number, line));
+        break;
+      }
+      if !line.is_empty() {
+        field.lines.push(line);
+      }
+    }
+
+    Ok(Some(field))
+  }
+
+  fn finish_entry(&mut self) -> Option<Result<BankRecord, ParsingError>> {
+    let entry = self.entry.take()?;
+    self.record_line = entry.line_number;
+
+    Some(entry.into_record(self.account.as_deref(), &self.accounts))
+  }
+}
+
+impl<R: BufRead> Iterator for Mt940Reader<R> {
+  type Item = Result<BankRecord, ParsingError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.failed {
+      return None;
+    }
+
+    loop {
+      let field = match self.read_field() {
+        Ok(Some(field)) => field,
+        Ok(None) => return self.finish_entry(),
+        Err(err) => {
+          self.failed = true;
+          self.record_line = self.lines + 1;
+          return Some(Err(err.into()));
+        }
+      };
+
+      if field.tag == "86" {
+        if let Some(entry) = &mut self.entry {
+          entry.information = field.lines.concat();
+        }
+        continue;
+      }
+
+      // Any other field completes the statement line
+      let record = self.finish_entry();
+      match field.tag.as_str() {
+        "25" => self.account = Some(field.lines.concat().trim().to_string()),
+        "61" => self.entry = Some(Entry::new(field)),
+        END_TAG => self.account = None,
+        _ => {}
+      }
+
+      if record.is_some() {
+        return record;
+      }
+    }
+  }
+}
+
+impl Mt940Balance {
+  /// Zero balance of the date
+  pub fn new(date: u64) -> Self {
+    Self { amount: 0, date }
+  }
+
+  /// Balance of a statement with the only statement line of the record
+  pub fn of(record: &BankRecord) -> Self {
+    let amount = i128::from(record.amount);
+
+    Self {
+      amount: match record.tx_type {
+        TxType::Deposit => amount,
+        TxType::Transfer | TxType::Withdrawal => -amount,
+      },
+      date: record.timestamp,
+    }
+  }
+
+  /// Moves the balance by the statement lines of `other`
+  pub fn add(&mut self, other: Self) {
+    self.amount += other.amount;
+    self.date = self.date.max(other.date);
+  }
+
+  /// Balance field value, e.g. `D211001EUR10,50`
+  fn format(&self) -> String {
+    let mark = if self.amount < 0 { 'D' } else { 'C' };
+
+    format!(
+      "{mark}{}{MT940_CURRENCY}{}",
+      format_value_date(self.date),
+      format_signed_amount(self.amount.abs(), ',')
+    )
+  }
+}
+
+impl Mt940Record {
+  /// Writes the statement fields up to the first statement line, the
+  /// statement of the bank account `0` is identified by `statement_id`.
+  /// Opening balance is zero, it's dated by the value date of the first
+  /// statement line.
+  pub fn write_header<W: Write>(
+    writer: &mut W,
+    statement_id: &str,
+    date: u64,
+  ) -> Result<(), SerializeError> {
+    writeln!(
+      writer,
+      ":20:{statement_id}\n:25:0\n:28C:1\n:60F:{}",
+      Mt940Balance::new(date).format()
+    )?;
+
+    Ok(())
+  }
+
+  /// Completes the statement after the last statement line, the closing
+  /// balance is the opening one moved by all statement lines
+  pub fn write_footer<W: Write>(
+    writer: &mut W,
+    balance: &Mt940Balance,
+  ) -> Result<(), SerializeError> {
+    writeln!(writer, ":62F:{}\n{END_TAG}", balance.format())?;
+
+    Ok(())
+  }
+
+  pub fn write_to<W: Write>(
+    &mut self,
+    buffer: &mut W,
+  ) -> Result<(), SerializeError> {
+    let record = &self.0;
+    let mark = match record.tx_type {
+      TxType::Deposit => 'C',
+      TxType::Transfer | TxType::Withdrawal => 'D',
+    };
+    let code = match record.tx_type {
+      TxType::Transfer => "TRF",
+      TxType::Deposit | TxType::Withdrawal => "MSC",
+    };
+
+    writeln!(
+      buffer,
+      ":61:{}{mark}{}N{code}{}",
+      format_value_date(record.timestamp),
+      format_amount(record.amount, ','),
+      record.tx_id,
+    )?;
+    if record.status != Status::Success {
+      writeln!(buffer, "{}", record.status)?;
+    }
+
+    let mut information = String::new();
+    if record.from_user_id != 0 {
+      let _ = write!(information, "/ORDP/{}", record.from_user_id);
+    }
+    if record.to_user_id != 0 {
+      let _ = write!(information, "/BENM/{}", record.to_user_id);
+    }
+    information.push_str("/REMI/");
+    information.extend(record.description.chars().map(|char| match char {
+      '\r' | '\n' => ' ',
+      char => char,
+    }));
+
+    let chars: Vec<char> = information.chars().collect();
+    let mut tag = ":86:";
+    let mut start = 0;
+    while start < chars.len() {
+      let end = start + information_line_length(&chars[start..]);
+      let line: String = chars[start..end].iter().collect();
+      writeln!(buffer, "{tag}{line}")?;
+      tag = "";
+      start = end;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod mt940_test {
+  use crate::errors::ParsingError;
+  use crate::parsers::{Mt940Accounts, Mt940Balance, Mt940Reader, Mt940Record};
+  use crate::record::{BankRecord, Status, TxType};
+  use std::io::Cursor;
+
+  const SAMPLE: &str = include_str!("../../../../mocks/mt940_example.sta");
+
+  fn read_all(
+    data: &[u8],
+    accounts: Mt940Accounts,
+  ) -> Result<Vec<BankRecord>, ParsingError> {
+    Mt940Reader::new(Cursor::new(data), accounts).collect()
+  }
+
+  fn sample_records() -> Vec<BankRecord> {
+    vec![
+      BankRecord {
+        tx_id: 1000000000000000,
+        tx_type: TxType::Deposit,
+        from_user_id: 0,
+        to_user_id: 42,
+        amount: 10050,
+        timestamp: 1633046400000,
+        status: Status::Success,
+        description: String::from("Salary and bonus September"),
+      },
+      BankRecord {
+        tx_id: 1000000000000001,
+        tx_type: TxType::Transfer,
+        from_user_id: 42,
+        to_user_id: 9223372036854775807,
+        amount: 2000,
+        timestamp: 1633046400000,
+        status: Status::Failure,
+        description: String::from("Invoice 17 part 1"),
+      },
+      BankRecord {
+        tx_id: 1000000000000002,
+        tx_type: TxType::Withdrawal,
+        from_user_id: 42,
+        to_user_id: 0,
+        amount: 500,
+        timestamp: 1633046400000,
+        status: Status::Success,
+        description: String::from("ATM withdrawal"),
+      },
+    ]
+  }
+
+  #[test]
+  fn test_read_sample() -> Result<(), ParsingError> {
+    let accounts = [
+      (String::from("NL91ABNA0417164300"), 42),
+      (String::from("DE89370400440532013000"), 0),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(read_all(SAMPLE.as_bytes(), accounts)?, sample_records());
+
+    // Counterparty account isn't mapped
+    let mut accounts = Mt940Accounts::default();
+    accounts.insert("NL91ABNA0417164300", 42);
+    assert!(matches!(
+      read_all(SAMPLE.as_bytes(), accounts),
+      Err(ParsingError::Custom(_))
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_record_lines() -> Result<(), ParsingError> {
+    let accounts = [
+      (String::from("NL91ABNA0417164300"), 42),
+      (String::from("DE89370400440532013000"), 0),
+    ]
+    .into_iter()
+    .collect();
+    let mut reader = Mt940Reader::new(Cursor::new(SAMPLE), accounts);
+
+    let mut lines = vec![];
+    while let Some(record) = reader.next() {
+      record?;
+      lines.push(reader.line());
+    }
+    assert_eq!(lines, vec![6, 9, 12]);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_and_read_back() -> Result<(), Box<dyn std::error::Error>> {
+    let records = || {
+      let mut records = sample_records();
+      records.push(BankRecord {
+        tx_id: 1000000000000003,
+        status: Status::Pending,
+        description: "Long description ".repeat(10),
+        ..sample_records().remove(2)
+      });
+      records
+    };
+
+    let mut data = vec![];
+    let mut balance = Mt940Balance::new(1633046400000);
+    Mt940Record::write_header(&mut data, "YP1633046400000", balance.date)?;
+    for record in records() {
+      balance.add(Mt940Balance::of(&record));
+      Mt940Record(record).write_to(&mut data)?;
+    }
+    Mt940Record::write_footer(&mut data, &balance)?;
+
+    let written = String::from_utf8(data.clone())?;
+    assert!(written.starts_with(
+      ":20:YP1633046400000\n:25:0\n:28C:1\n:60F:C211001EUR0,00\n"
+    ));
+    assert!(written.contains(
+      ":61:211001C100,50NMSC1000000000000000\n\
+       :86:/BENM/42/REMI/Salary and bonus September\n"
+    ));
+    assert!(written.contains(":61:211001D5,00NMSC1000000000000003\nPENDING\n"));
+    // Opening balance moved by 100,50 of credits and 30,00 of debits
+    assert!(written.ends_with(":62F:C211001EUR70,50\n-\n"));
+
+    assert_eq!(read_all(&data, Mt940Accounts::default())?, records());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_information_tags() -> Result<(), Box<dyn std::error::Error>> {
+    // Information of the sample withdrawal starts with `/ORDP/42/REMI/`, so
+    // its first line ends after 51 characters of the description
+    let descriptions = [
+      format!("{}:61:211001C1,00NMSC9", "a".repeat(51)),
+      format!("{}-", "a".repeat(51)),
+      format!("{}-}} and - more", "a".repeat(51)),
+      format!("{}::: -- :62F:", "a".repeat(100)),
+      format!("a{}-}}", ":-".repeat(50)),
+      format!("{}-", "-:".repeat(40)),
+    ];
+    let records = || {
+      descriptions
+        .iter()
+        .enumerate()
+        .map(|(index, description)| BankRecord {
+          tx_id: index as u64 + 1,
+          description: description.clone(),
+          ..sample_records().remove(2)
+        })
+    };
+
+    let mut data = vec![];
+    let mut balance = Mt940Balance::new(1633046400000);
+    Mt940Record::write_header(&mut data, "YP1633046400000", balance.date)?;
+    for record in records() {
+      balance.add(Mt940Balance::of(&record));
+      Mt940Record(record).write_to(&mut data)?;
+    }
+    Mt940Record::write_footer(&mut data, &balance)?;
+
+    let written = String::from_utf8(data.clone())?;
+    assert_eq!(written.matches("\n:61:").count(), descriptions.len());
+    assert!(written.ends_with(":62F:D211001EUR30,00\n-\n"));
+    assert!(written.lines().all(|line| line.len() <= 69));
+    assert_eq!(
+      read_all(&data, Mt940Accounts::default())?,
+      records().collect::<Vec<_>>()
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_invalid_lines() {
+    let statement = |line: &str| {
+      read_all(
+        format!(":25:1\n:61:{line}\n:86:Test\n-").as_bytes(),
+        Mt940Accounts::default(),
+      )
+    };
+
+    assert!(statement("211001C1,00NMSC7").is_ok());
+    assert!(statement("211301C1,00NMSC7").is_err());
+    assert!(statement("211001X1,00NMSC7").is_err());
+    assert!(statement("211001C1,001NMSC7").is_err());
+    assert!(statement("211001C1,00NMSCNONREF").is_err());
+    assert!(statement("211001C1,00").is_err());
+    assert!(matches!(
+      read_all(b":61:211001D1,00NMSC7\n", Mt940Accounts::default()),
+      Err(ParsingError::Custom(_))
+    ));
+  }
+}
@@ -0,0 +1,370 @@
+//! OFX bank statements of a single user for personal finance tools.
+//!
+//! Records are written as statement transactions (`STMTTRN`) of the user's
+//! account, amounts are negative if the user sends them and zero if the user
+//! sends them to themselves. Failed records and records of other users are
+//! skipped, and the ledger balance is the sum of the written successful
+//! records. Fields are mapped as follows:
+//!
+//! | Record field   | OFX transaction element                               |
+//! |----------------|-------------------------------------------------------|
+//! | `TX_ID`        | `FITID`                                               |
+//! | `TX_TYPE`      | `TRNTYPE`: `DEP`, `XFER` or `CASH`                    |
+//! | `FROM_USER_ID` | `NAME` of the counterparty if the user receives it    |
+//! | `TO_USER_ID`   | `NAME` of the counterparty if the user sends it       |
+//! | `AMOUNT`       | `TRNAMT` in major units                               |
+//! | `TIMESTAMP`    | `DTPOSTED` in GMT                                     |
+//! | `DESCRIPTION`  | `MEMO`                                                |
+//!
+//! OFX 1.0.2 SGML and OFX 2.2 XML documents differ only in their headers
+//! and encodings, all elements are closed in both. SGML documents are
+//! US-ASCII, other characters are written as character references, e.g.
+//! `&#233;`, XML documents are UTF-8.
+
+use crate::date::Date;
+use crate::errors::SerializeError;
+use crate::parsers::statement::{
+  counterparty_name, format_signed_amount, user_amount,
+};
+use crate::record::{BankRecord, Status, TxType};
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Currency of written amounts
+pub const OFX_CURRENCY: &str = "EUR";
+
+/// Bank id of written accounts
+pub const OFX_BANK_ID: &str = "YPBANK";
+
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OfxSyntax {
+  /// OFX 1.0.2
+  Sgml,
+  /// OFX 2.2
+  Xml,
+}
+
+/// Writes records of the user into a statement of their account, the
+/// statement is complete once the writer is finished
+pub struct OfxWriter<W: Write> {
+  writer: W,
+  syntax: OfxSyntax,
+  user_id: u64,
+  created: u64,
+  /// Sum of written successful amounts in minor units
+  balance: i128,
+}
+
+/// Date and time, e.g. `20211001120000.000[0:GMT]`
+fn format_ofx_date(timestamp: u64) -> String {
+  let date = Date::from_timestamp(timestamp);
+  let millis = timestamp % MILLIS_PER_DAY;
+
+  format!(
+    "{:04}{:02}{:02}{:02}{:02}{:02}.{:03}[0:GMT]",
+    date.year,
+    date.month,
+    date.day,
+    millis / 3_600_000,
+    millis / 60_000 % 60,
+    millis / 1000 % 60,
+    millis % 1000
+  )
+}
+
+/// Escapes markup characters of element values, characters of SGML
+/// documents which aren't ASCII are escaped too
+fn escape(value: &str, syntax: OfxSyntax) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  for char in value.chars() {
+    match char {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      char if !char.is_ascii() && syntax == OfxSyntax::Sgml => {
+        let _ = write!(escaped, "&#{};", u32::from(char));
+      }
+      char => escaped.push(char),
+    }
+  }
+
+  escaped
+}
+
+impl<W: Write> OfxWriter<W> {
+  /// Writes the document up to the first transaction, the statement covers
+  /// all transactions up to `created`
+  pub fn new(
+    mut writer: W,
+    syntax: OfxSyntax,
+    user_id: u64,
+    created: u64,
+  ) -> Result<Self, SerializeError> {
+    match syntax {
+      OfxSyntax::Sgml => writeln!(
+        writer,
+        "OFXHEADER:100
+DATA:OFXSGML
+VERSION:102
+SECURITY:NONE
+ENCODING:USASCII
+CHARSET:1252
+COMPRESSION:NONE
+OLDFILEUID:NONE
+NEWFILEUID:NONE
+"
+      )?,
+      OfxSyntax::Xml => writeln!(
+        writer,
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<?OFX OFXHEADER="200" VERSION="220" SECURITY="NONE" OLDFILEUID="NONE" NEWFILEUID="NONE"?>"#
+      )?,
+    }
+
+    let created_date = format_ofx_date(created);
+    write!(
+      writer,
+      "<OFX>
+  <SIGNONMSGSRSV1>
+    <SONRS>
+      <STATUS>
+        <CODE>0</CODE>
+        <SEVERITY>INFO</SEVERITY>
+      </STATUS>
+      <DTSERVER>{created_date}</DTSERVER>
+      <LANGUAGE>ENG</LANGUAGE>
+    </SONRS>
+  </SIGNONMSGSRSV1>
+  <BANKMSGSRSV1>
+    <STMTTRNRS>
+      <TRNUID>0</TRNUID>
+      <STATUS>
+        <CODE>0</CODE>
+        <SEVERITY>INFO</SEVERITY>
+      </STATUS>
+      <STMTRS>
+        <CURDEF>{OFX_CURRENCY}</CURDEF>
+        <BANKACCTFROM>
+          <BANKID>{OFX_BANK_ID}</BANKID>
+          <ACCTID>{user_id}</ACCTID>
+          <ACCTTYPE>CHECKING</ACCTTYPE>
+        </BANKACCTFROM>
+        <BANKTRANLIST>
+          <DTSTART>{}</DTSTART>
+          <DTEND>{created_date}</DTEND>
+",
+      format_ofx_date(0),
+    )?;
+
+    Ok(Self {
+      writer,
+      syntax,
+      user_id,
+      created,
+      balance: 0,
+    })
+  }
+
+  /// Writes the record from the user's perspective, failed records and
+  /// records which don't involve the user are skipped
+  pub fn write(&mut self, record: &BankRecord) -> Result<(), SerializeError> {
+    let Some(amount) = user_amount(record, self.user_id) else {
+      return Ok(());
+    };
+    if record.status == Status::Failure {
+      return Ok(());
+    }
+
+    if record.status == Status::Success {
+      self.balance += amount;
+    }
+    let tx_type = match record.tx_type {
+      TxType::Deposit => "DEP",
+      TxType::Transfer => "XFER",
+      TxType::Withdrawal => "CASH",
+    };
+
+    write!(
+      self.writer,
+      "          <STMTTRN>
+            <TRNTYPE>{tx_type}</TRNTYPE>
+            <DTPOSTED>{}</DTPOSTED>
+            <TRNAMT>{}</TRNAMT>
+            <FITID>{}</FITID>
+            <NAME>{}</NAME>
+            <MEMO>{}</MEMO>
+          </STMTTRN>
+",
+      format_ofx_date(record.timestamp),
+      format_signed_amount(amount, '.'),
+      record.tx_id,
+      counterparty_name(record, self.user_id),
+      escape(&record.description, self.syntax),
+    )?;
+
+    Ok(())
+  }
+
+  /// Completes the document with the ledger balance and returns the
+  /// underlying writer
+  pub fn finish(mut self) -> Result<W, SerializeError> {
+    write!(
+      self.writer,
+      "        </BANKTRANLIST>
+        <LEDGERBAL>
+          <BALAMT>{}</BALAMT>
+          <DTASOF>{}</DTASOF>
+        </LEDGERBAL>
+      </STMTRS>
+    </STMTTRNRS>
+  </BANKMSGSRSV1>
+</OFX>
+",
+      format_signed_amount(self.balance, '.'),
+      format_ofx_date(self.created),
+    )?;
+
+    Ok(self.writer)
+  }
+}
+
+#[cfg(test)]
+mod ofx_test {
+  use crate::errors::SerializeError;
+  use crate::parsers::{OfxSyntax, OfxWriter};
+  use crate::record::{BankRecord, Status, TxType};
+
+  fn records() -> Vec<BankRecord> {
+    vec![
+      BankRecord {
+        tx_id: 1,
+        tx_type: TxType::Deposit,
+        from_user_id: 0,
+        to_user_id: 42,
+        amount: 10050,
+        timestamp: 1633036860000,
+        status: Status::Success,
+        description: String::from("Salary & bonus"),
+      },
+      BankRecord {
+        tx_id: 2,
+        tx_type: TxType::Transfer,
+        from_user_id: 42,
+        to_user_id: 7,
+        amount: 2000,
+        timestamp: 1633046400123,
+        status: Status::Pending,
+        description: String::from("Invoice 17"),
+      },
+      BankRecord {
+        tx_id: 3,
+        tx_type: TxType::Withdrawal,
+        from_user_id: 42,
+        to_user_id: 0,
+        amount: 500,
+        timestamp: 1633046500000,
+        status: Status::Failure,
+        description: String::from("ATM"),
+      },
+    ]
+  }
+
+  fn write(syntax: OfxSyntax) -> Result<String, SerializeError> {
+    let mut writer = OfxWriter::new(vec![], syntax, 42, 1633046400000)?;
+    for record in records() {
+      writer.write(&record)?;
+    }
+
+    Ok(String::from_utf8(writer.finish()?).unwrap())
+  }
+
+  #[test]
+  fn test_write_statement() -> Result<(), SerializeError> {
+    let sgml = write(OfxSyntax::Sgml)?;
+    let xml = write(OfxSyntax::Xml)?;
+
+    assert!(sgml.starts_with("OFXHEADER:100\nDATA:OFXSGML\n"));
+    assert!(xml.starts_with("<?xml version=\"1.0\""));
+    assert_eq!(
+      sgml.split_once("<OFX>").map(|(_, body)| body),
+      xml.split_once("<OFX>").map(|(_, body)| body)
+    );
+
+    assert!(sgml.contains("<ACCTID>42</ACCTID>"));
+    assert!(sgml.contains(
+      "<TRNTYPE>DEP</TRNTYPE>
+            <DTPOSTED>20210930212100.000[0:GMT]</DTPOSTED>
+            <TRNAMT>100.50</TRNAMT>
+            <FITID>1</FITID>
+            <NAME>YPBank</NAME>
+            <MEMO>Salary &amp; bonus</MEMO>"
+    ));
+    assert!(sgml.contains(
+      "<TRNTYPE>XFER</TRNTYPE>
+            <DTPOSTED>20211001000000.123[0:GMT]</DTPOSTED>
+            <TRNAMT>-20.00</TRNAMT>
+            <FITID>2</FITID>
+            <NAME>User 7</NAME>"
+    ));
+    assert!(!sgml.contains("<FITID>3</FITID>"));
+    assert!(sgml.contains("<BALAMT>100.50</BALAMT>"));
+    assert!(sgml.ends_with("</OFX>\n"));
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_user_records() -> Result<(), SerializeError> {
+    let mut writer =
+      OfxWriter::new(vec![], OfxSyntax::Sgml, 42, 1633046400000)?;
+    writer.write(&BankRecord {
+      tx_id: 4,
+      from_user_id: 7,
+      to_user_id: 8,
+      ..records().remove(1)
+    })?;
+    writer.write(&BankRecord {
+      tx_id: 5,
+      to_user_id: 42,
+      status: Status::Success,
+      ..records().remove(1)
+    })?;
+    writer.write(&records().remove(0))?;
+    let sgml = String::from_utf8(writer.finish()?).unwrap();
+
+    assert!(!sgml.contains("<FITID>4</FITID>"));
+    assert!(sgml.contains(
+      "<TRNAMT>0.00</TRNAMT>
+            <FITID>5</FITID>
+            <NAME>User 42</NAME>"
+    ));
+    assert!(sgml.contains("<BALAMT>100.50</BALAMT>"));
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_encoding() -> Result<(), SerializeError> {
+    let write = |syntax| -> Result<String, SerializeError> {
+      let mut writer = OfxWriter::new(vec![], syntax, 42, 1633046400000)?;
+      writer.write(&BankRecord {
+        description: String::from("Café <Zoë> 5€"),
+        ..records().remove(0)
+      })?;
+
+      Ok(String::from_utf8(writer.finish()?).unwrap())
+    };
+    let sgml = write(OfxSyntax::Sgml)?;
+    let xml = write(OfxSyntax::Xml)?;
+
+    assert!(sgml.contains("\nENCODING:USASCII\nCHARSET:1252\n"));
+    assert!(sgml.is_ascii());
+    assert!(sgml.contains("<MEMO>Caf&#233; &lt;Zo&#235;&gt; 5&#8364;</MEMO>"));
+    assert!(xml.contains("encoding=\"UTF-8\""));
+    assert!(xml.contains("<MEMO>Café &lt;Zoë&gt; 5€</MEMO>"));
+
+    Ok(())
+  }
+}
@@ -0,0 +1,171 @@
+//! QIF bank account transactions of a single user for personal finance
+//! tools.
+//!
+//! Every record is a transaction of the `Bank` account type, amounts are
+//! negative if the user sends them and zero if the user sends them to
+//! themselves. Failed records, as no money was moved, and records of other
+//! users are skipped. Fields are mapped as follows:
+//!
+//! | Record field   | QIF transaction line                                  |
+//! |----------------|-------------------------------------------------------|
+//! | `TX_ID`        | `N`, the reference number                             |
+//! | `FROM_USER_ID` | `P`, the counterparty if the user receives it         |
+//! | `TO_USER_ID`   | `P`, the counterparty if the user sends it            |
+//! | `AMOUNT`       | `T` in major units                                    |
+//! | `TIMESTAMP`    | `D` as `MM/DD/YYYY` in UTC                            |
+//! | `STATUS`       | `C*`, cleared, for successful records                 |
+//! | `DESCRIPTION`  | `M`, line breaks are replaced with spaces             |
+
+use crate::date::Date;
+use crate::errors::SerializeError;
+use crate::parsers::statement::{
+  counterparty_name, format_signed_amount, user_amount,
+};
+use crate::record::{BankRecord, Status};
+use std::io::Write;
+
+/// Writes records of the user as transactions of their bank account
+pub struct QifWriter<W: Write> {
+  writer: W,
+  user_id: u64,
+}
+
+impl<W: Write> QifWriter<W> {
+  /// Writes the account type header
+  pub fn new(mut writer: W, user_id: u64) -> Result<Self, SerializeError> {
+    writeln!(writer, "!Type:Bank")?;
+
+    Ok(Self { writer, user_id })
+  }
+
+  /// Writes the record from the user's perspective, failed records and
+  /// records which don't involve the user are skipped
+  pub fn write(&mut self, record: &BankRecord) -> Result<(), SerializeError> {
+    let Some(amount) = user_amount(record, self.user_id) else {
+      return Ok(());
+    };
+    if record.status == Status::Failure {
+      return Ok(());
+    }
+
+    let date = Date::from_timestamp(record.timestamp);
+    let description: String = record
+      .description
+      .chars()
+      .map(|char| match char {
+        '\r' | '\n' => ' ',
+        char => char,
+      })
+      .collect();
+
+    writeln!(
+      self.writer,
+      "D{:02}/{:02}/{:04}\nT{}\nN{}\nP{}\nM{description}",
+      date.month,
+      date.day,
+      date.year,
+      format_signed_amount(amount, '.'),
+      record.tx_id,
+      counterparty_name(record, self.user_id),
+    )?;
+    if record.status == Status::Success {
+      writeln!(self.writer, "C*")?;
+    }
+    writeln!(self.writer, "^")?;
+
+    Ok(())
+  }
+
+  /// Returns the underlying writer
+  pub fn finish(self) -> Result<W, SerializeError> {
+    Ok(self.writer)
+  }
+}
+
+#[cfg(test)]
+mod qif_test {
+  use crate::errors::SerializeError;
+  use crate::parsers::QifWriter;
+  use crate::record::{BankRecord, Status, TxType};
+
+  #[test]
+  fn test_write_transactions() -> Result<(), SerializeError> {
+    let mut writer = QifWriter::new(vec![], 42)?;
+    writer.write(&BankRecord {
+      tx_id: 1,
+      tx_type: TxType::Deposit,
+      from_user_id: 0,
+      to_user_id: 42,
+      amount: 10050,
+      timestamp: 1633036860000,
+      status: Status::Success,
+      description: String::from("Salary\nSeptember"),
+    })?;
+    writer.write(&BankRecord {
+      tx_id: 2,
+      tx_type: TxType::Transfer,
+      from_user_id: 42,
+      to_user_id: 7,
+      amount: 2000,
+      timestamp: 1633046400000,
+      status: Status::Pending,
+      description: String::from("Invoice 17"),
+    })?;
+    writer.write(&BankRecord {
+      tx_id: 3,
+      status: Status::Failure,
+      ..BankRecord::default()
+    })?;
+    // Records of other users
+    writer.write(&BankRecord {
+      tx_id: 4,
+      from_user_id: 7,
+      to_user_id: 8,
+      ..BankRecord::default()
+    })?;
+    writer.write(&BankRecord {
+      tx_id: 5,
+      tx_type: TxType::Deposit,
+      ..BankRecord::default()
+    })?;
+    // Transfer to the user themselves
+    writer.write(&BankRecord {
+      tx_id: 6,
+      tx_type: TxType::Transfer,
+      from_user_id: 42,
+      to_user_id: 42,
+      amount: 700,
+      timestamp: 1633046400000,
+      status: Status::Success,
+      description: String::from("Savings"),
+    })?;
+
+    assert_eq!(
+      String::from_utf8(writer.finish()?).unwrap(),
+      "!Type:Bank
+D09/30/2021
+T100.50
+N1
+PYPBank
+MSalary September
+C*
+^
+D10/01/2021
+T-20.00
+N2
+PUser 7
+MInvoice 17
+^
+D10/01/2021
+T0.00
+N6
+PUser 42
+MSavings
+C*
+^
+"
+    );
+
+    Ok(())
+  }
+}
@@ -0,0 +1,161 @@
+use crate::errors::{ParsingError, SerializeError};
+use crate::record::{BankRecord, BankRecordParser};
+#[cfg(feature = "json")]
+use std::io;
+#[cfg(feature = "json")]
+use std::io::ErrorKind;
+use std::io::{BufRead, Write};
+use std::marker::PhantomData;
+
+/// Serde data format which reads records from a stream and writes them to it
+pub trait SerdeFormat {
+  /// Reads the next record, the end of data is reported as
+  /// `ParsingError::IO` of `UnexpectedEof` kind
+  fn read<R: BufRead>(reader: &mut R) -> Result<BankRecord, ParsingError>;
+  fn write<W: Write>(
+    writer: &mut W,
+    record: &BankRecord,
+  ) -> Result<(), SerializeError>;
+}
+
+/// Record adapter which plugs any [SerdeFormat] into [BankRecordParser]
+pub struct SerdeRecord<F>(pub BankRecord, PhantomData<F>);
+
+impl<F: SerdeFormat> SerdeRecord<F> {
+  pub fn new(record: BankRecord) -> Self {
+    Self(record, PhantomData)
+  }
+}
+
+impl<F: SerdeFormat> BankRecordParser for SerdeRecord<F> {
+  fn from_read<R: BufRead>(buffer: &mut R) -> Result<BankRecord, ParsingError> {
+    F::read(buffer)
+  }
+  fn write_to<W: Write>(
+    &mut self,
+    buffer: &mut W,
+  ) -> Result<(), SerializeError> {
+    F::write(buffer, &self.0)
+  }
+}
+
+/// [JSON Lines](https://jsonlines.org) format, one JSON object per line,
+/// empty lines between records are skipped
+#[cfg(feature = "json")]
+pub struct JsonLines;
+
+#[cfg(feature = "json")]
+impl SerdeFormat for JsonLines {
+  fn read<R: BufRead>(reader: &mut R) -> Result<BankRecord, ParsingError> {
+    let mut line = String::new();
+
+    while line.trim().is_empty() {
+      line.clear();
+
+      if reader.read_line(&mut line)? == 0 {
+        return Err(ParsingError::IO(io::Error::new(
+          ErrorKind::UnexpectedEof,
+          "EOF: File has no lines to read",
+        )));
+      }
+    }
+
+    serde_json::from_str(line.trim_end())
+      .map_err(|err| ParsingError::Deserialize(Box::new(err)))
+  }
+  fn write<W: Write>(
+    writer: &mut W,
+    record: &BankRecord,
+  ) -> Result<(), SerializeError> {
+    serde_json::to_writer(&mut *writer, record).map_err(io::Error::from)?;
+    writeln!(writer)?;
+
+    Ok(())
+  }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod serde_record_test {
+  use crate::constants::record_field;
+  use crate::errors::ParsingError;
+  use crate::parsers::serde_record::{JsonLines, SerdeRecord};
+  use crate::record::{BankRecord, BankRecordParser, Status, TxType};
+  use std::io::{Cursor, ErrorKind};
+
+  fn record() -> BankRecord {
+    BankRecord {
+      tx_id: 1000000000000000,
+      tx_type: TxType::Withdrawal,
+      from_user_id: 9223372036854775807,
+      to_user_id: 0,
+      amount: 100,
+      timestamp: 1633036860000,
+      status: Status::Failure,
+      description: String::from("Record number 1"),
+    }
+  }
+
+  #[test]
+  fn test_serialize_canonical_names() {
+    let mut buff: Vec<u8> = vec![];
+    SerdeRecord::<JsonLines>::new(record())
+      .write_to(&mut buff)
+      .unwrap();
+
+    let value: serde_json::Value = serde_json::from_slice(&buff).unwrap();
+    let fields = [
+      record_field::TX_ID,
+      record_field::TX_TYPE,
+      record_field::FROM_USER_ID,
+      record_field::TO_USER_ID,
+      record_field::AMOUNT,
+      record_field::TIMESTAMP,
+      record_field::STATUS,
+      record_field::DESCRIPTION,
+    ];
+
+    assert_eq!(value.as_object().unwrap().len(), fields.len());
+    assert!(fields.iter().all(|field| value.get(field).is_some()));
+    assert_eq!(value[record_field::TX_TYPE], "WITHDRAWAL");
+    assert_eq!(value[record_field::STATUS], "FAILURE");
+  }
+
+  #[test]
+  fn test_parse_records() {
+    let mut buff: Vec<u8> = vec![];
+    SerdeRecord::<JsonLines>::new(record())
+      .write_to(&mut buff)
+      .unwrap();
+    buff.extend_from_slice(b"\n");
+    SerdeRecord::<JsonLines>::new(record())
+      .write_to(&mut buff)
+      .unwrap();
+
+    let mut buff = Cursor::new(buff);
+
+    assert_eq!(
+      SerdeRecord::<JsonLines>::from_read(&mut buff).unwrap(),
+      record()
+    );
+    assert_eq!(
+      SerdeRecord::<JsonLines>::from_read(&mut buff).unwrap(),
+      record()
+    );
+    assert!(matches!(
+      SerdeRecord::<JsonLines>::from_read(&mut buff),
+      Err(ParsingError::IO(err)) if err.kind() == ErrorKind::UnexpectedEof
+    ));
+  }
+
+  #[test]
+  fn test_parse_invalid_status() {
+    let mut buff = Cursor::new(
+      r#"{"TX_ID":1,"TX_TYPE":"DEPOSIT","FROM_USER_ID":0,"TO_USER_ID":1,"AMOUNT":1,"TIMESTAMP":1,"STATUS":"DONE","DESCRIPTION":""}"#,
+    );
+
+    assert!(matches!(
+      SerdeRecord::<JsonLines>::from_read(&mut buff),
+      Err(ParsingError::Deserialize(_))
+    ));
+  }
+}
@@ -0,0 +1,142 @@
+//! Helpers shared by bank statement formats. Statement amounts are decimals
+//! with two fraction digits, record amounts are integer minor units, e.g.
+//! cents.
+
+use crate::record::BankRecord;
+
+/// Minor units of one major currency unit
+pub(crate) const MINOR_UNITS: u64 = 100;
+
+/// Formats minor units as a decimal, e.g. `1050` as `10.50`
+pub(crate) fn format_amount(amount: u64, separator: char) -> String {
+  format!(
+    "{}{separator}{:02}",
+    amount / MINOR_UNITS,
+    amount % MINOR_UNITS
+  )
+}
+
+/// Formats signed minor units as a decimal, e.g. `-1050` as `-10.50`
+pub(crate) fn format_signed_amount(amount: i128, separator: char) -> String {
+  let sign = if amount < 0 { "-" } else { "" };
+  let amount = amount.unsigned_abs();
+
+  format!(
+    "{sign}{}{separator}{:02}",
+    amount / u128::from(MINOR_UNITS),
+    amount % u128::from(MINOR_UNITS)
+  )
+}
+
+/// Amount of the record from the user's perspective, negative if the user
+/// sends it and zero if the user sends it to themselves. Records which don't
+/// involve the user have no amount.
+pub(crate) fn user_amount(record: &BankRecord, user_id: u64) -> Option<i128> {
+  let amount = i128::from(record.amount);
+
+  match (record.from_user_id == user_id, record.to_user_id == user_id) {
+    (true, true) => Some(0),
+    (true, false) => Some(-amount),
+    (false, true) => Some(amount),
+    (false, false) => None,
+  }
+}
+
+/// Name of the other party of the record from the user's perspective
+pub(crate) fn counterparty_name(record: &BankRecord, user_id: u64) -> String {
+  let counterparty = if record.from_user_id == user_id {
+    record.to_user_id
+  } else {
+    record.from_user_id
+  };
+
+  match counterparty {
+    0 => String::from("YPBank"),
+    user_id => format!("User {user_id}"),
+  }
+}
+
+/// Parses a decimal with up to two fraction digits into minor units, e.g.
+/// `10.5` into `1050`
+pub(crate) fn parse_amount(value: &str, separator: char) -> Option<u64> {
+  let (units, fraction) = value.split_once(separator).unwrap_or((value, ""));
+  let digits = |digits: &str| digits.bytes().all(|byte| byte.is_ascii_digit());
+  if units.is_empty() || !digits(units) || !digits(fraction) {
+    return None;
+  }
+
+  let fraction = match fraction.len() {
+    0 => 0,
+    1 => fraction.parse::<u64>().ok()? * 10,
+    2 => fraction.parse::<u64>().ok()?,
+    _ => return None,
+  };
+
+  units
+    .parse::<u64>()
+    .ok()?
+    .checked_mul(MINOR_UNITS)?
+    .checked_add(fraction)
+}
+
+#[cfg(test)]
+mod statement_test {
+  use crate::parsers::statement::{
+    counterparty_name, format_amount, format_signed_amount, parse_amount,
+    user_amount,
+  };
+  use crate::record::{BankRecord, TxType};
+
+  #[test]
+  fn test_amounts() {
+    assert_eq!(format_amount(1050, '.'), "10.50");
+    assert_eq!(format_amount(7, ','), "0,07");
+    assert_eq!(parse_amount("10.5", '.'), Some(1050));
+    assert_eq!(parse_amount("10,", ','), Some(1000));
+    assert_eq!(parse_amount("0.07", '.'), Some(7));
+    assert_eq!(parse_amount("1.005", '.'), None);
+    assert_eq!(parse_amount("-1.00", '.'), None);
+    assert_eq!(parse_amount(".50", '.'), None);
+    assert_eq!(format_signed_amount(-1050, '.'), "-10.50");
+    assert_eq!(format_signed_amount(7, ','), "0,07");
+  }
+
+  #[test]
+  fn test_user_perspective() {
+    let record = BankRecord {
+      tx_type: TxType::Transfer,
+      from_user_id: 1,
+      to_user_id: 2,
+      amount: 1050,
+      ..BankRecord::default()
+    };
+
+    assert_eq!(user_amount(&record, 1), Some(-1050));
+    assert_eq!(user_amount(&record, 2), Some(1050));
+    assert_eq!(user_amount(&record, 3), None);
+    assert_eq!(
+      user_amount(
+        &BankRecord {
+          from_user_id: 1,
+          to_user_id: 1,
+          amount: 1050,
+          ..BankRecord::default()
+        },
+        1
+      ),
+      Some(0)
+    );
+    assert_eq!(counterparty_name(&record, 1), "User 2");
+    assert_eq!(counterparty_name(&record, 2), "User 1");
+    assert_eq!(
+      counterparty_name(
+        &BankRecord {
+          to_user_id: 2,
+          ..BankRecord::default()
+        },
+        2
+      ),
+      "YPBank"
+    );
+  }
+}
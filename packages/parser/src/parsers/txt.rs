@@ -1,5 +1,5 @@
 use crate::constants::{RECORD_LINES_NUMBER, record_field};
-use crate::errors::{ParsingError, SerializeError};
+use crate::errors::{ParsingError, SerializeError, StatusTypeError};
 use crate::record::{BankRecord, BankRecordParser, Status, TxType};
 use std::io;
 use std::io::{BufRead, ErrorKind, Write};
@@ -79,7 +79,9 @@ impl BankRecordParser for TxtRecord {
           bank_record.timestamp = field_value.parse::<u64>()?;
         }
         record_field::STATUS => {
-          bank_record.status = Status::from_str(field_value)?;
+          bank_record.status = Status::from_str(field_value).map_err(|_| {
+            StatusTypeError::InvalidSting(field_value.to_string())
+          })?;
         }
         record_field::DESCRIPTION => {
           bank_record.description = field_value.replace('"', "");
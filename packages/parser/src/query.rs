@@ -0,0 +1,796 @@
+//! SQL-like queries over records, e.g.
+//! `SELECT tx_type, SUM(amount) FROM 'records.bin' WHERE status = 'FAILURE'
+//! GROUP BY tx_type ORDER BY 2 DESC LIMIT 10`.
+//!
+//! Clauses follow the SQL order, only `SELECT` is required:
+//! - `SELECT` lists `*`, record fields and `COUNT`, `SUM`, `MIN`, `MAX`,
+//!   `AVG` aggregates, each with an optional `AS` alias
+//! - `FROM` names the report, records are provided by the caller
+//! - `WHERE` is a [Filter] expression
+//! - `GROUP BY` lists record fields, non-aggregated columns have to be
+//!   listed there
+//! - `ORDER BY` lists column names, aliases or 1-based column numbers with
+//!   optional `ASC` or `DESC`
+//! - `LIMIT` is the maximum number of rows
+//!
+//! Aggregate queries keep only per group accumulators, other queries keep
+//! selected rows if they are ordered.
+
+use crate::errors::QueryError;
+use crate::filter::{Field, Filter};
+use crate::record::BankRecord;
+use crate::stats::{GroupKey, Summary};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+  Count,
+  Sum,
+  Min,
+  Max,
+  Avg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expr {
+  Field(Field),
+  /// Aggregate of the field, `None` stands for `COUNT(*)`
+  Aggregate(Aggregate, Option<Field>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+  /// Alias or the normalized expression, e.g. `SUM(AMOUNT)`
+  pub name: String,
+  pub expr: Expr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderBy {
+  /// Index of the selected column
+  pub column: usize,
+  pub descending: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+  pub columns: Vec<Column>,
+  pub from: Option<String>,
+  pub filter: Filter,
+  pub group_by: Vec<Field>,
+  pub order_by: Vec<OrderBy>,
+  pub limit: Option<usize>,
+}
+
+/// Value of the result, aggregates of empty groups are `Null`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+  Null,
+  Number(u128),
+  Float(f64),
+  Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResult {
+  pub columns: Vec<String>,
+  pub rows: Vec<Vec<Cell>>,
+}
+
+/// Query applied to records one by one
+#[derive(Debug)]
+pub struct Execution<'a> {
+  query: &'a Query,
+  rows: Vec<Vec<Cell>>,
+  groups: BTreeMap<Vec<GroupKey>, Group>,
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+  count: u64,
+  /// Summaries of aggregated fields in the order of columns
+  summaries: Vec<Summary>,
+}
+
+const CLAUSES: [&str; 6] =
+  ["SELECT", "FROM", "WHERE", "GROUP BY", "ORDER BY", "LIMIT"];
+
+impl Aggregate {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::Count => "COUNT",
+      Self::Sum => "SUM",
+      Self::Min => "MIN",
+      Self::Max => "MAX",
+      Self::Avg => "AVG",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<Self> {
+    [Self::Count, Self::Sum, Self::Min, Self::Max, Self::Avg]
+      .into_iter()
+      .find(|aggregate| aggregate.name().eq_ignore_ascii_case(name))
+  }
+}
+
+impl Display for Cell {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Null => Ok(()),
+      Self::Number(number) => write!(f, "{number}"),
+      Self::Float(number) => write!(f, "{number:.2}"),
+      Self::Text(text) => write!(f, "{text}"),
+    }
+  }
+}
+
+impl Cell {
+  fn field(field: Field, record: &BankRecord) -> Self {
+    match field {
+      Field::TxType => Self::Text(record.tx_type.to_string()),
+      Field::Status => Self::Text(record.status.to_string()),
+      Field::Description => Self::Text(record.description.clone()),
+      field => Self::Number(field.number(record).unwrap_or_default() as u128),
+    }
+  }
+
+  fn key(field: Field, record: &BankRecord) -> GroupKey {
+    match Self::field(field, record) {
+      Self::Number(number) => GroupKey::Number(number as u64),
+      cell => GroupKey::Text(cell.to_string()),
+    }
+  }
+
+  /// Nulls go first, numbers before texts
+  fn compare(&self, other: &Self) -> Ordering {
+    let number = |cell: &Self| match cell {
+      Self::Number(number) => *number as f64,
+      Self::Float(number) => *number,
+      _ => 0.0,
+    };
+
+    match (self, other) {
+      (Self::Null, Self::Null) => Ordering::Equal,
+      (Self::Null, _) => Ordering::Less,
+      (_, Self::Null) => Ordering::Greater,
+      (Self::Text(left), Self::Text(right)) => left.cmp(right),
+      (Self::Text(_), _) => Ordering::Greater,
+      (_, Self::Text(_)) => Ordering::Less,
+      (Self::Number(left), Self::Number(right)) => left.cmp(right),
+      (left, right) => number(left).total_cmp(&number(right)),
+    }
+  }
+}
+
+impl From<GroupKey> for Cell {
+  fn from(key: GroupKey) -> Self {
+    match key {
+      GroupKey::Number(number) => Self::Number(number as u128),
+      GroupKey::Text(text) => Self::Text(text),
+    }
+  }
+}
+
+impl Query {
+  /// Aggregate queries return a row for every group, or a single row if
+  /// there is no `GROUP BY` clause
+  pub fn is_aggregate(&self) -> bool {
+    !self.group_by.is_empty()
+      || self
+        .columns
+        .iter()
+        .any(|column| matches!(column.expr, Expr::Aggregate(..)))
+  }
+
+  pub fn execution(&self) -> Execution<'_> {
+    Execution {
+      query: self,
+      rows: vec![],
+      groups: BTreeMap::new(),
+    }
+  }
+
+  pub fn execute<I: IntoIterator<Item = BankRecord>>(
+    &self,
+    records: I,
+  ) -> QueryResult {
+    let mut execution = self.execution();
+
+    for record in records {
+      if !execution.push(&record) {
+        break;
+      }
+    }
+
+    execution.finish()
+  }
+
+  fn validate(&self) -> Result<(), QueryError> {
+    let aggregate = self.is_aggregate();
+
+    for column in &self.columns {
+      match column.expr {
+        Expr::Field(field) if aggregate && !self.group_by.contains(&field) => {
+          return Err(QueryError::NotGrouped(column.name.clone()));
+        }
+        Expr::Aggregate(function, Some(field))
+          if function != Aggregate::Count
+            && matches!(
+              field,
+              Field::TxType | Field::Status | Field::Description
+            ) =>
+        {
+          return Err(QueryError::NotNumeric {
+            function: function.name().to_string(),
+            field: field.to_string(),
+          });
+        }
+        _ => {}
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl Execution<'_> {
+  /// Applies the record, returns `false` if the result is complete and
+  /// following records are not needed
+  pub fn push(&mut self, record: &BankRecord) -> bool {
+    let query = self.query;
+
+    if !query.filter.matches(record) {
+      return true;
+    }
+
+    if !query.is_aggregate() {
+      let row = query
+        .columns
+        .iter()
+        .map(|column| match column.expr {
+          Expr::Field(field) => Cell::field(field, record),
+          Expr::Aggregate(..) => Cell::Null,
+        })
+        .collect();
+      self.rows.push(row);
+
+      return !query.order_by.is_empty()
+        || query.limit.is_none_or(|limit| self.rows.len() < limit);
+    }
+
+    let key = query
+      .group_by
+      .iter()
+      .map(|field| Cell::key(*field, record))
+      .collect();
+    let group = self.groups.entry(key).or_insert_with(|| Group {
+      count: 0,
+      summaries: vec![Summary::default(); query.columns.len()],
+    });
+
+    group.count += 1;
+    for (column, summary) in query.columns.iter().zip(&mut group.summaries) {
+      if let Expr::Aggregate(_, Some(field)) = column.expr
+        && let Some(number) = field.number(record)
+      {
+        summary.add(number);
+      }
+    }
+
+    true
+  }
+
+  pub fn finish(self) -> QueryResult {
+    let query = self.query;
+    let mut rows = self.rows;
+
+    if query.is_aggregate() {
+      let mut groups = self.groups;
+      if groups.is_empty() && query.group_by.is_empty() {
+        groups.insert(
+          vec![],
+          Group {
+            count: 0,
+            summaries: vec![Summary::default(); query.columns.len()],
+          },
+        );
+      }
+
+      rows = groups
+        .into_iter()
+        .map(|(key, group)| group_row(query, key, group))
+        .collect();
+    }
+
+    if !query.order_by.is_empty() {
+      rows.sort_by(|left, right| {
+        query
+          .order_by
+          .iter()
+          .map(|order| {
+            let ordering = left[order.column].compare(&right[order.column]);
+            match order.descending {
+              true => ordering.reverse(),
+              false => ordering,
+            }
+          })
+          .find(|ordering| ordering.is_ne())
+          .unwrap_or(Ordering::Equal)
+      });
+    }
+
+    if let Some(limit) = query.limit {
+      rows.truncate(limit);
+    }
+
+    QueryResult {
+      columns: query
+        .columns
+        .iter()
+        .map(|column| column.name.clone())
+        .collect(),
+      rows,
+    }
+  }
+}
+
+fn group_row(query: &Query, key: Vec<GroupKey>, group: Group) -> Vec<Cell> {
+  query
+    .columns
+    .iter()
+    .zip(group.summaries)
+    .map(|(column, summary)| match column.expr {
+      Expr::Field(field) => query
+        .group_by
+        .iter()
+        .position(|group_field| *group_field == field)
+        .map(|index| Cell::from(key[index].clone()))
+        .unwrap_or(Cell::Null),
+      Expr::Aggregate(Aggregate::Count, _) => Cell::Number(group.count as u128),
+      Expr::Aggregate(_, _) if summary.count == 0 => Cell::Null,
+      Expr::Aggregate(Aggregate::Sum, _) => Cell::Number(summary.sum),
+      Expr::Aggregate(Aggregate::Min, _) => summary
+        .min
+        .map_or(Cell::Null, |min| Cell::Number(min as u128)),
+      Expr::Aggregate(Aggregate::Max, _) => summary
+        .max
+        .map_or(Cell::Null, |max| Cell::Number(max as u128)),
+      Expr::Aggregate(Aggregate::Avg, _) => {
+        summary.mean().map_or(Cell::Null, Cell::Float)
+      }
+    })
+    .collect()
+}
+
+impl FromStr for Query {
+  type Err = QueryError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let [select, from, filter, group_by, order_by, limit] = split_clauses(s)?;
+
+    let mut columns = vec![];
+    for item in select.unwrap_or_default().split(',') {
+      columns.extend(parse_columns(item)?);
+    }
+
+    let from = from.map(|from| {
+      from
+        .strip_prefix('\'')
+        .and_then(|from| from.strip_suffix('\''))
+        .or_else(|| from.strip_prefix('"')?.strip_suffix('"'))
+        .unwrap_or(from)
+        .to_string()
+    });
+
+    let filter = match filter {
+      Some(filter) => Filter::from_str(filter)?,
+      None => Filter::default(),
+    };
+
+    let group_by = match group_by {
+      Some(group_by) => group_by
+        .split(',')
+        .map(|field| Field::from_str(field.trim()))
+        .collect::<Result<Vec<_>, _>>()?,
+      None => vec![],
+    };
+
+    let order_by = match order_by {
+      Some(order_by) => order_by
+        .split(',')
+        .map(|item| parse_order_by(item, &columns))
+        .collect::<Result<Vec<_>, _>>()?,
+      None => vec![],
+    };
+
+    let limit = limit
+      .map(|limit| {
+        limit.parse::<usize>().map_err(|_| {
+          QueryError::Syntax(format!("Invalid LIMIT value {limit:?}"))
+        })
+      })
+      .transpose()?;
+
+    let query = Query {
+      columns,
+      from,
+      filter,
+      group_by,
+      order_by,
+      limit,
+    };
+    query.validate()?;
+
+    Ok(query)
+  }
+}
+
+/// Clause keyword starting at the position and the end of the keyword
+fn clause_at(query: &str, position: usize) -> Option<(usize, usize)> {
+  let is_word = |byte: u8| byte.is_ascii_alphanumeric() || byte == b'_';
+  let bytes = query.as_bytes();
+
+  if position > 0 && is_word(bytes[position - 1]) {
+    return None;
+  }
+
+  CLAUSES.iter().enumerate().find_map(|(index, clause)| {
+    let mut end = position;
+
+    for (word_index, word) in clause.split(' ').enumerate() {
+      if word_index > 0 {
+        let spaces = query[end..]
+          .bytes()
+          .take_while(u8::is_ascii_whitespace)
+          .count();
+        if spaces == 0 {
+          return None;
+        }
+        end += spaces;
+      }
+
+      let candidate = query.get(end..end + word.len())?;
+      if !candidate.eq_ignore_ascii_case(word) {
+        return None;
+      }
+      end += word.len();
+    }
+
+    match bytes.get(end) {
+      Some(byte) if is_word(*byte) => None,
+      _ => Some((index, end)),
+    }
+  })
+}
+
+/// Contents of every clause, keywords in quoted texts are ignored
+fn split_clauses(query: &str) -> Result<[Option<&str>; 6], QueryError> {
+  let mut markers: Vec<(usize, usize, usize)> = vec![];
+  let mut quote = None;
+
+  for (position, char) in query.char_indices() {
+    match quote {
+      Some(open) if char == open => quote = None,
+      Some(_) => {}
+      None if char == '\'' || char == '"' => quote = Some(char),
+      None => {
+        if let Some((clause, end)) = clause_at(query, position) {
+          markers.push((clause, position, end));
+        }
+      }
+    }
+  }
+
+  match markers.first() {
+    Some((0, start, _)) if query[..*start].trim().is_empty() => {}
+    _ => {
+      return Err(QueryError::Syntax(String::from(
+        "Query has to start with SELECT",
+      )));
+    }
+  }
+
+  let mut clauses = [None; 6];
+  for (index, (clause, _, end)) in markers.iter().enumerate() {
+    if index > 0 && markers[index - 1].0 >= *clause {
+      return Err(QueryError::Syntax(format!(
+        "{} clause is repeated or out of order",
+        CLAUSES[*clause]
+      )));
+    }
+
+    let next = markers.get(index + 1).map_or(query.len(), |next| next.1);
+    let content = query[*end..next].trim();
+    if content.is_empty() {
+      return Err(QueryError::Syntax(format!(
+        "{} clause is empty",
+        CLAUSES[*clause]
+      )));
+    }
+
+    clauses[*clause] = Some(content);
+  }
+
+  Ok(clauses)
+}
+
+/// Columns of the `SELECT` item, `*` stands for all record fields
+fn parse_columns(item: &str) -> Result<Vec<Column>, QueryError> {
+  let words: Vec<&str> = item.split_whitespace().collect();
+  let (expr, alias) = match words.as_slice() {
+    [expr @ .., keyword, alias]
+      if !expr.is_empty() && keyword.eq_ignore_ascii_case("AS") =>
+    {
+      (expr.concat(), Some(*alias))
+    }
+    expr => (expr.concat(), None),
+  };
+
+  if expr.is_empty() {
+    return Err(QueryError::Syntax(String::from("Empty SELECT column")));
+  }
+
+  if expr == "*" && alias.is_none() {
+    return Ok(
+      Field::ALL
+        .into_iter()
+        .map(|field| Column {
+          name: field.to_string(),
+          expr: Expr::Field(field),
+        })
+        .collect(),
+    );
+  }
+
+  let (name, expr) = match expr.split_once('(') {
+    Some((function, argument)) => {
+      let argument = argument.strip_suffix(')').ok_or_else(|| {
+        QueryError::Syntax(format!("Unclosed parenthesis in {expr:?}"))
+      })?;
+      let function = Aggregate::from_name(function).ok_or_else(|| {
+        QueryError::Syntax(format!("Unknown function {function:?}"))
+      })?;
+
+      match argument {
+        "*" if function == Aggregate::Count => {
+          (String::from("COUNT(*)"), Expr::Aggregate(function, None))
+        }
+        argument => {
+          let field = Field::from_str(argument)?;
+          (
+            format!("{}({field})", function.name()),
+            Expr::Aggregate(function, Some(field)),
+          )
+        }
+      }
+    }
+    None => {
+      let field = Field::from_str(&expr)?;
+      (field.to_string(), Expr::Field(field))
+    }
+  };
+
+  Ok(vec![Column {
+    name: alias.map(str::to_string).unwrap_or(name),
+    expr,
+  }])
+}
+
+/// Order of a column referenced by its name, alias or 1-based number
+fn parse_order_by(
+  item: &str,
+  columns: &[Column],
+) -> Result<OrderBy, QueryError> {
+  let words: Vec<&str> = item.split_whitespace().collect();
+  let (reference, descending) = match words.as_slice() {
+    [reference @ .., last] if last.eq_ignore_ascii_case("DESC") => {
+      (reference, true)
+    }
+    [reference @ .., last] if last.eq_ignore_ascii_case("ASC") => {
+      (reference, false)
+    }
+    reference => (reference, false),
+  };
+  let reference = reference.concat();
+  let unknown = || QueryError::UnknownColumn(reference.clone());
+
+  let column = match reference.parse::<usize>() {
+    Ok(number) => number
+      .checked_sub(1)
+      .filter(|index| *index < columns.len())
+      .ok_or_else(unknown)?,
+    Err(_) => columns
+      .iter()
+      .position(|column| column.name.eq_ignore_ascii_case(&reference))
+      .ok_or_else(unknown)?,
+  };
+
+  Ok(OrderBy { column, descending })
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Cell {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    match self {
+      Self::Null => serializer.serialize_none(),
+      Self::Number(number) => serializer.serialize_u128(*number),
+      Self::Float(number) => serializer.serialize_f64(*number),
+      Self::Text(text) => serializer.serialize_str(text),
+    }
+  }
+}
+
+/// Rows are serialized as maps of column names to values
+#[cfg(feature = "serde")]
+impl serde::Serialize for QueryResult {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    use serde::ser::{SerializeMap, SerializeSeq};
+
+    struct Row<'a>(&'a [String], &'a [Cell]);
+
+    impl serde::Serialize for Row<'_> {
+      fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+      ) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (column, cell) in self.0.iter().zip(self.1) {
+          map.serialize_entry(column, cell)?;
+        }
+        map.end()
+      }
+    }
+
+    let mut rows = serializer.serialize_seq(Some(self.rows.len()))?;
+    for row in &self.rows {
+      rows.serialize_element(&Row(&self.columns, row))?;
+    }
+    rows.end()
+  }
+}
+
+#[cfg(test)]
+mod query_test {
+  use crate::errors::QueryError;
+  use crate::query::{Cell, Query};
+  use crate::record::{BankRecord, Status, TxType};
+  use std::str::FromStr;
+
+  fn records() -> Vec<BankRecord> {
+    [
+      (1, TxType::Deposit, 100, Status::Success),
+      (2, TxType::Transfer, 40, Status::Failure),
+      (3, TxType::Deposit, 300, Status::Failure),
+      (4, TxType::Withdrawal, 20, Status::Failure),
+      (5, TxType::Deposit, 500, Status::Failure),
+    ]
+    .into_iter()
+    .map(|(tx_id, tx_type, amount, status)| BankRecord {
+      tx_id,
+      tx_type,
+      from_user_id: 0,
+      to_user_id: 2,
+      amount,
+      timestamp: 1633036860000 + tx_id,
+      status,
+      description: format!("Record number {tx_id}"),
+    })
+    .collect()
+  }
+
+  fn rows(query: &str) -> Vec<Vec<Cell>> {
+    Query::from_str(query).unwrap().execute(records()).rows
+  }
+
+  #[test]
+  fn test_aggregate_query() {
+    let query = Query::from_str(
+      "select tx_type, SUM(amount) AS total, count(*) FROM 'records.bin' \
+       WHERE status = 'FAILURE' GROUP BY tx_type ORDER BY total DESC",
+    )
+    .unwrap();
+
+    assert_eq!(query.from.as_deref(), Some("records.bin"));
+
+    let result = query.execute(records());
+    assert_eq!(result.columns, vec!["TX_TYPE", "total", "COUNT(*)"]);
+    assert_eq!(
+      result.rows,
+      vec![
+        vec![
+          Cell::Text(String::from("DEPOSIT")),
+          Cell::Number(800),
+          Cell::Number(2)
+        ],
+        vec![
+          Cell::Text(String::from("TRANSFER")),
+          Cell::Number(40),
+          Cell::Number(1)
+        ],
+        vec![
+          Cell::Text(String::from("WITHDRAWAL")),
+          Cell::Number(20),
+          Cell::Number(1)
+        ],
+      ]
+    );
+
+    assert_eq!(
+      rows("SELECT COUNT(*), AVG(amount), MIN(amount) WHERE amount > 1000"),
+      vec![vec![Cell::Number(0), Cell::Null, Cell::Null]]
+    );
+    assert_eq!(
+      rows("SELECT AVG(amount), MAX(amount) WHERE tx_type = DEPOSIT"),
+      vec![vec![Cell::Float(300.0), Cell::Number(500)]]
+    );
+  }
+
+  #[test]
+  fn test_projection_query() {
+    assert_eq!(
+      rows("SELECT tx_id, amount ORDER BY amount DESC, 1 LIMIT 2"),
+      vec![
+        vec![Cell::Number(5), Cell::Number(500)],
+        vec![Cell::Number(3), Cell::Number(300)],
+      ]
+    );
+    assert_eq!(
+      rows("SELECT description WHERE description contains 'limit 2' LIMIT 1"),
+      Vec::<Vec<Cell>>::new()
+    );
+
+    let result = Query::from_str("SELECT * LIMIT 1")
+      .unwrap()
+      .execute(records());
+    assert_eq!(result.columns.len(), 8);
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(
+      result.rows[0][7],
+      Cell::Text(String::from("Record number 1"))
+    );
+  }
+
+  #[test]
+  fn test_invalid_queries() {
+    let error = |query: &str| Query::from_str(query).unwrap_err();
+
+    assert!(matches!(error("FROM 'a' SELECT *"), QueryError::Syntax(_)));
+    assert!(matches!(
+      error("SELECT * WHERE amount > 1 WHERE amount < 5"),
+      QueryError::Syntax(_)
+    ));
+    assert!(matches!(
+      error("SELECT * LIMIT many"),
+      QueryError::Syntax(_)
+    ));
+    assert!(matches!(
+      error("SELECT status, COUNT(*)"),
+      QueryError::NotGrouped(_)
+    ));
+    assert!(matches!(
+      error("SELECT SUM(status)"),
+      QueryError::NotNumeric { .. }
+    ));
+    assert!(matches!(
+      error("SELECT tx_id ORDER BY amount"),
+      QueryError::UnknownColumn(_)
+    ));
+    assert!(matches!(
+      error("SELECT tx_id WHERE state = 1"),
+      QueryError::Filter(_)
+    ));
+    assert!(matches!(
+      error("SELECT MEDIAN(amount)"),
+      QueryError::Syntax(_)
+    ));
+  }
+}
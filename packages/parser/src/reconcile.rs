@@ -0,0 +1,291 @@
+//! Status lifecycle of transactions across a chronological series of reports.
+//!
+//! A transaction is reported as `PENDING` until it gets its final `SUCCESS`
+//! or `FAILURE` status in one of the later reports. Final statuses never
+//! change, and only the status may differ between reports of the same
+//! transaction.
+
+use crate::record::{BankRecord, Status};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Problem found in the series of reports, reports are identified by their
+/// position in the series
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconcileIssue {
+  /// Transaction is still pending longer than the threshold
+  Stuck {
+    tx_id: u64,
+    /// Last report the transaction is seen in
+    report: usize,
+    /// Milliseconds since the transaction timestamp
+    pending_for: u64,
+  },
+  /// Final status changed or the transaction became pending again
+  IllegalTransition {
+    tx_id: u64,
+    report: usize,
+    from: Status,
+    to: Status,
+  },
+  /// Record fields other than the status changed
+  ContentChanged {
+    tx_id: u64,
+    report: usize,
+    fields: Vec<&'static str>,
+  },
+}
+
+/// Latest record of the transaction and every status change
+#[derive(Debug)]
+pub struct Lifecycle {
+  pub record: BankRecord,
+  /// Report and the status the transaction got in it, the first entry is
+  /// the first report the transaction is seen in
+  pub statuses: Vec<(usize, Status)>,
+  /// Last report the transaction is seen in
+  pub last_report: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Reconciler {
+  transactions: HashMap<u64, Lifecycle>,
+  issues: Vec<ReconcileIssue>,
+  latest_timestamp: u64,
+}
+
+impl Display for ReconcileIssue {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Stuck {
+        tx_id, pending_for, ..
+      } => write!(
+        f,
+        "Transaction {tx_id} is pending for {} minutes",
+        pending_for / 60_000
+      ),
+      Self::IllegalTransition {
+        tx_id, from, to, ..
+      } => write!(f, "Transaction {tx_id} status changed from {from} to {to}"),
+      Self::ContentChanged { tx_id, fields, .. } => write!(
+        f,
+        "Transaction {tx_id} changed {} between status updates",
+        fields.join(", ")
+      ),
+    }
+  }
+}
+
+impl ReconcileIssue {
+  pub fn tx_id(&self) -> u64 {
+    match self {
+      Self::Stuck { tx_id, .. }
+      | Self::IllegalTransition { tx_id, .. }
+      | Self::ContentChanged { tx_id, .. } => *tx_id,
+    }
+  }
+
+  pub fn report(&self) -> usize {
+    match self {
+      Self::Stuck { report, .. }
+      | Self::IllegalTransition { report, .. }
+      | Self::ContentChanged { report, .. } => *report,
+    }
+  }
+}
+
+impl Lifecycle {
+  pub fn status(&self) -> &Status {
+    &self.record.status
+  }
+}
+
+/// `true` if a transaction may get the status `to` after the status `from`
+pub fn is_legal_transition(from: &Status, to: &Status) -> bool {
+  from == to || *from == Status::Pending
+}
+
+/// Names of the fields, except the status, which differ between the records
+fn changed_fields(old: &BankRecord, new: &BankRecord) -> Vec<&'static str> {
+  [
+    ("TX_TYPE", old.tx_type != new.tx_type),
+    ("FROM_USER_ID", old.from_user_id != new.from_user_id),
+    ("TO_USER_ID", old.to_user_id != new.to_user_id),
+    ("AMOUNT", old.amount != new.amount),
+    ("TIMESTAMP", old.timestamp != new.timestamp),
+    ("DESCRIPTION", old.description != new.description),
+  ]
+  .into_iter()
+  .filter_map(|(field, changed)| changed.then_some(field))
+  .collect()
+}
+
+impl Reconciler {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a record of the report, reports have to be added in chronological
+  /// order
+  pub fn add(&mut self, report: usize, record: BankRecord) {
+    self.latest_timestamp = self.latest_timestamp.max(record.timestamp);
+
+    let Some(lifecycle) = self.transactions.get_mut(&record.tx_id) else {
+      self.transactions.insert(
+        record.tx_id,
+        Lifecycle {
+          statuses: vec![(report, record.status.clone())],
+          last_report: report,
+          record,
+        },
+      );
+      return;
+    };
+
+    let tx_id = record.tx_id;
+    let fields = changed_fields(&lifecycle.record, &record);
+    if !fields.is_empty() {
+      self.issues.push(ReconcileIssue::ContentChanged {
+        tx_id,
+        report,
+        fields,
+      });
+    }
+
+    let from = lifecycle.status();
+    if !is_legal_transition(from, &record.status) {
+      self.issues.push(ReconcileIssue::IllegalTransition {
+        tx_id,
+        report,
+        from: from.clone(),
+        to: record.status.clone(),
+      });
+    }
+
+    if *from != record.status {
+      lifecycle.statuses.push((report, record.status.clone()));
+    }
+    lifecycle.last_report = report;
+    lifecycle.record = record;
+  }
+
+  pub fn lifecycle(&self, tx_id: u64) -> Option<&Lifecycle> {
+    self.transactions.get(&tx_id)
+  }
+
+  pub fn transactions(&self) -> impl Iterator<Item = &Lifecycle> {
+    self.transactions.values()
+  }
+
+  /// Latest record timestamp of all reports
+  pub fn latest_timestamp(&self) -> u64 {
+    self.latest_timestamp
+  }
+
+  /// Issues found while adding records followed by transactions pending
+  /// for more than `stuck_after` milliseconds at `now`, stuck transactions
+  /// are ordered by tx id
+  pub fn finish(self, now: u64, stuck_after: u64) -> Vec<ReconcileIssue> {
+    let mut stuck: Vec<ReconcileIssue> = self
+      .transactions
+      .values()
+      .filter(|lifecycle| *lifecycle.status() == Status::Pending)
+      .filter_map(|lifecycle| {
+        let pending_for = now.saturating_sub(lifecycle.record.timestamp);
+        (pending_for > stuck_after).then_some(ReconcileIssue::Stuck {
+          tx_id: lifecycle.record.tx_id,
+          report: lifecycle.last_report,
+          pending_for,
+        })
+      })
+      .collect();
+    stuck.sort_by_key(ReconcileIssue::tx_id);
+
+    let mut issues = self.issues;
+    issues.extend(stuck);
+    issues
+  }
+}
+
+#[cfg(test)]
+mod reconcile_test {
+  use crate::reconcile::{ReconcileIssue, Reconciler, is_legal_transition};
+  use crate::record::{BankRecord, Status, TxType};
+
+  const HOUR: u64 = 60 * 60 * 1000;
+
+  fn record(tx_id: u64, amount: u64, status: Status) -> BankRecord {
+    BankRecord {
+      tx_id,
+      tx_type: TxType::Transfer,
+      from_user_id: 2,
+      to_user_id: 3,
+      amount,
+      timestamp: 1633036860000 + tx_id * HOUR,
+      status,
+      description: String::from("Transfer"),
+    }
+  }
+
+  #[test]
+  fn test_status_transitions() {
+    assert!(is_legal_transition(&Status::Pending, &Status::Success));
+    assert!(is_legal_transition(&Status::Pending, &Status::Pending));
+    assert!(is_legal_transition(&Status::Failure, &Status::Failure));
+    assert!(!is_legal_transition(&Status::Failure, &Status::Success));
+    assert!(!is_legal_transition(&Status::Success, &Status::Pending));
+  }
+
+  #[test]
+  fn test_reconcile_reports() {
+    let mut reconciler = Reconciler::new();
+
+    for record in [
+      record(1, 100, Status::Pending),
+      record(2, 200, Status::Pending),
+      record(3, 300, Status::Failure),
+    ] {
+      reconciler.add(0, record);
+    }
+    for record in [
+      record(1, 100, Status::Success),
+      record(2, 200, Status::Pending),
+      record(3, 300, Status::Success),
+      record(4, 400, Status::Pending),
+    ] {
+      reconciler.add(1, record);
+    }
+    reconciler.add(2, record(1, 150, Status::Success));
+
+    let lifecycle = reconciler.lifecycle(1).unwrap();
+    assert_eq!(
+      lifecycle.statuses,
+      vec![(0, Status::Pending), (1, Status::Success)]
+    );
+    assert_eq!(lifecycle.last_report, 2);
+
+    // Transaction 4 is the latest one, 2 is pending for two hours
+    let now = reconciler.latest_timestamp();
+    assert_eq!(
+      reconciler.finish(now, HOUR),
+      vec![
+        ReconcileIssue::IllegalTransition {
+          tx_id: 3,
+          report: 1,
+          from: Status::Failure,
+          to: Status::Success,
+        },
+        ReconcileIssue::ContentChanged {
+          tx_id: 1,
+          report: 2,
+          fields: vec!["AMOUNT"],
+        },
+        ReconcileIssue::Stuck {
+          tx_id: 2,
+          report: 1,
+          pending_for: 2 * HOUR,
+        },
+      ]
+    );
+  }
+}
@@ -8,6 +8,8 @@ use std::io::{BufRead, ErrorKind, Write};
 use std::str::FromStr;
 
 #[derive(Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
 pub struct BankRecord {
   pub tx_id: u64,
   pub tx_type: TxType,
@@ -19,6 +21,21 @@ pub struct BankRecord {
   pub description: String,
 }
 
+/// Borrowed view of a [BankRecord], description points into the source
+/// buffer, so no allocations are made while it is read
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BankRecordRef<'a> {
+  pub tx_id: u64,
+  pub tx_type: TxType,
+  pub from_user_id: u64,
+  pub to_user_id: u64,
+  pub amount: u64,
+  pub timestamp: u64,
+  pub status: Status,
+  /// Description as stored in the source, surrounding quotes excluded
+  pub description: &'a str,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub enum TxType {
   #[default]
@@ -41,6 +58,28 @@ impl BankRecord {
   }
 }
 
+impl BankRecordRef<'_> {
+  pub fn to_owned_record(&self) -> BankRecord {
+    BankRecord {
+      tx_id: self.tx_id,
+      tx_type: self.tx_type.clone(),
+      from_user_id: self.from_user_id,
+      to_user_id: self.to_user_id,
+      amount: self.amount,
+      timestamp: self.timestamp,
+      status: self.status.clone(),
+      // Escaped quotes are not needed in model
+      description: self.description.replace("\"", ""),
+    }
+  }
+}
+
+impl From<BankRecordRef<'_>> for BankRecord {
+  fn from(record: BankRecordRef<'_>) -> Self {
+    record.to_owned_record()
+  }
+}
+
 pub trait BankRecordParser {
   fn from_read<R: BufRead>(buffer: &mut R) -> Result<BankRecord, ParsingError>;
   fn write_to<W: Write>(
@@ -73,7 +112,7 @@ impl Display for TxType {
 }
 
 impl TryFrom<u8> for TxType {
-  type Error = TxTypeError; // TODO Add enum error for TxType
+  type Error = TxTypeError;
 
   fn try_from(v: u8) -> Result<Self, Self::Error> {
     match v {
@@ -111,6 +150,49 @@ impl Display for Status {
   }
 }
 
+/// Enum values are serialized with the same names as in text reports
+#[cfg(feature = "serde")]
+impl serde::Serialize for TxType {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TxType {
+  fn deserialize<D: serde::Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    let value = String::deserialize(deserializer)?;
+
+    TxType::from_str(&value).map_err(serde::de::Error::custom)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Status {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Status {
+  fn deserialize<D: serde::Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    let value = String::deserialize(deserializer)?;
+
+    Status::from_str(&value).map_err(serde::de::Error::custom)
+  }
+}
+
 impl TryFrom<u8> for Status {
   type Error = StatusTypeError;
 
@@ -0,0 +1,445 @@
+//! Sorting of records by fields, e.g. `timestamp,tx_id`.
+//!
+//! [ExternalSorter] keeps up to `run_capacity` records in memory, larger
+//! inputs are sorted in runs which are spilled to temporary files and merged
+//! afterwards, so memory use doesn't depend on the report size. Sorting is
+//! stable, records with equal sort fields keep their input order.
+//! [MergeSorted] merges already sorted record streams, e.g. daily reports.
+
+use crate::filter::Field;
+use crate::record::{BankRecord, Status, TxType};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, Write};
+use std::num::NonZeroUsize;
+use std::rc::Rc;
+use std::vec;
+
+/// Number of records sorted in memory before they are spilled to disk
+pub const DEFAULT_RUN_CAPACITY: NonZeroUsize =
+  NonZeroUsize::new(1_000_000).expect("Run capacity is not zero");
+
+/// Compares records by fields in order, transaction types and statuses are
+/// ordered as they are declared
+pub fn compare_by(
+  fields: &[Field],
+  left: &BankRecord,
+  right: &BankRecord,
+) -> Ordering {
+  fields
+    .iter()
+    .map(|field| compare_field(*field, left, right))
+    .find(|ordering| ordering.is_ne())
+    .unwrap_or(Ordering::Equal)
+}
+
+fn compare_field(
+  field: Field,
+  left: &BankRecord,
+  right: &BankRecord,
+) -> Ordering {
+  match field {
+    Field::TxType => {
+      (left.tx_type.clone() as u8).cmp(&(right.tx_type.clone() as u8))
+    }
+    Field::Status => {
+      (left.status.clone() as u8).cmp(&(right.status.clone() as u8))
+    }
+    Field::Description => left.description.cmp(&right.description),
+    _ => field.number(left).cmp(&field.number(right)),
+  }
+}
+
+pub struct ExternalSorter {
+  fields: Rc<[Field]>,
+  run_capacity: NonZeroUsize,
+  buffer: Vec<BankRecord>,
+  runs: Vec<Run>,
+}
+
+/// Sorted records spilled to a temporary file
+struct Run {
+  reader: BufReader<File>,
+  remaining: usize,
+}
+
+/// Sorted records of an [ExternalSorter], reading spilled runs may fail
+pub struct SortedRecords {
+  inner: SortedInner,
+}
+
+enum SortedInner {
+  Memory(vec::IntoIter<BankRecord>),
+  Merge(MergeSorted<Run, io::Error>),
+}
+
+/// K-way merge of record streams which are sorted by the same fields.
+/// Records with equal sort fields are taken from earlier streams first.
+/// Merge stops after the first stream error.
+pub struct MergeSorted<I, E> {
+  fields: Rc<[Field]>,
+  inputs: Vec<I>,
+  heap: BinaryHeap<Reverse<MergeHead>>,
+  started: bool,
+  error: Option<E>,
+}
+
+/// Next record of a merged stream, ordered by sort fields and then by stream
+/// index
+struct MergeHead {
+  fields: Rc<[Field]>,
+  record: BankRecord,
+  input: usize,
+}
+
+impl ExternalSorter {
+  pub fn new(fields: Vec<Field>) -> Self {
+    Self {
+      fields: fields.into(),
+      run_capacity: DEFAULT_RUN_CAPACITY,
+      buffer: vec![],
+      runs: vec![],
+    }
+  }
+
+  pub fn with_run_capacity(mut self, run_capacity: NonZeroUsize) -> Self {
+    self.run_capacity = run_capacity;
+    self
+  }
+
+  pub fn push(&mut self, record: BankRecord) -> io::Result<()> {
+    self.buffer.push(record);
+
+    if self.buffer.len() >= self.run_capacity.get() {
+      self.spill()?;
+    }
+
+    Ok(())
+  }
+
+  pub fn finish(mut self) -> io::Result<SortedRecords> {
+    if self.runs.is_empty() {
+      self.sort_buffer();
+      return Ok(SortedRecords {
+        inner: SortedInner::Memory(self.buffer.into_iter()),
+      });
+    }
+
+    if !self.buffer.is_empty() {
+      self.spill()?;
+    }
+
+    Ok(SortedRecords {
+      inner: SortedInner::Merge(MergeSorted::with_fields(
+        self.fields,
+        self.runs,
+      )),
+    })
+  }
+
+  fn sort_buffer(&mut self) {
+    let fields = &self.fields;
+    self
+      .buffer
+      .sort_by(|left, right| compare_by(fields, left, right));
+  }
+
+  fn spill(&mut self) -> io::Result<()> {
+    self.sort_buffer();
+
+    let mut writer = BufWriter::new(tempfile::tempfile()?);
+    let remaining = self.buffer.len();
+    for record in self.buffer.drain(..) {
+      write_spilled(&record, &mut writer)?;
+    }
+
+    let mut file = writer.into_inner().map_err(|err| err.into_error())?;
+    file.rewind()?;
+
+    self.runs.push(Run {
+      reader: BufReader::new(file),
+      remaining,
+    });
+
+    Ok(())
+  }
+}
+
+impl Iterator for Run {
+  type Item = io::Result<BankRecord>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.remaining == 0 {
+      return None;
+    }
+
+    self.remaining -= 1;
+    Some(read_spilled(&mut self.reader))
+  }
+}
+
+impl Iterator for SortedRecords {
+  type Item = io::Result<BankRecord>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match &mut self.inner {
+      SortedInner::Memory(records) => records.next().map(Ok),
+      SortedInner::Merge(records) => records.next(),
+    }
+  }
+}
+
+impl<I, E> MergeSorted<I, E>
+where
+  I: Iterator<Item = Result<BankRecord, E>>,
+{
+  pub fn new(fields: Vec<Field>, inputs: Vec<I>) -> Self {
+    Self::with_fields(fields.into(), inputs)
+  }
+
+  fn with_fields(fields: Rc<[Field]>, inputs: Vec<I>) -> Self {
+    Self {
+      fields,
+      heap: BinaryHeap::with_capacity(inputs.len()),
+      inputs,
+      started: false,
+      error: None,
+    }
+  }
+
+  /// Moves the next record of the input to the heap
+  fn pull(&mut self, input: usize) {
+    match self.inputs[input].next() {
+      Some(Ok(record)) => self.heap.push(Reverse(MergeHead {
+        fields: self.fields.clone(),
+        record,
+        input,
+      })),
+      Some(Err(err)) => {
+        // Merge can't continue without the input
+        self.heap.clear();
+        self.error = Some(err);
+      }
+      None => (),
+    }
+  }
+}
+
+impl<I, E> Iterator for MergeSorted<I, E>
+where
+  I: Iterator<Item = Result<BankRecord, E>>,
+{
+  type Item = Result<BankRecord, E>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if !self.started {
+      self.started = true;
+      for input in 0..self.inputs.len() {
+        if self.error.is_some() {
+          break;
+        }
+        self.pull(input);
+      }
+    }
+
+    if let Some(err) = self.error.take() {
+      return Some(Err(err));
+    }
+
+    let Reverse(head) = self.heap.pop()?;
+    self.pull(head.input);
+
+    Some(Ok(head.record))
+  }
+}
+
+impl Ord for MergeHead {
+  fn cmp(&self, other: &Self) -> Ordering {
+    compare_by(&self.fields, &self.record, &other.record)
+      .then(self.input.cmp(&other.input))
+  }
+}
+
+impl PartialOrd for MergeHead {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl PartialEq for MergeHead {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other).is_eq()
+  }
+}
+
+impl Eq for MergeHead {}
+
+/// Spilled records are stored as is, unlike report formats no characters of
+/// descriptions are escaped or dropped
+fn write_spilled<W: Write>(
+  record: &BankRecord,
+  writer: &mut W,
+) -> io::Result<()> {
+  writer.write_all(&record.tx_id.to_be_bytes())?;
+  writer.write_all(&[record.tx_type.clone() as u8])?;
+  writer.write_all(&record.from_user_id.to_be_bytes())?;
+  writer.write_all(&record.to_user_id.to_be_bytes())?;
+  writer.write_all(&record.amount.to_be_bytes())?;
+  writer.write_all(&record.timestamp.to_be_bytes())?;
+  writer.write_all(&[record.status.clone() as u8])?;
+  writer.write_all(&(record.description.len() as u32).to_be_bytes())?;
+  writer.write_all(record.description.as_bytes())
+}
+
+fn read_spilled<R: Read>(reader: &mut R) -> io::Result<BankRecord> {
+  let invalid = |err| io::Error::new(ErrorKind::InvalidData, err);
+  let mut u64_buf = [0u8; 8];
+  let mut u32_buf = [0u8; 4];
+  let mut u8_buf = [0u8; 1];
+  let mut read_u64 = |reader: &mut R| -> io::Result<u64> {
+    reader.read_exact(&mut u64_buf)?;
+    Ok(u64::from_be_bytes(u64_buf))
+  };
+
+  let tx_id = read_u64(reader)?;
+  reader.read_exact(&mut u8_buf)?;
+  let tx_type =
+    TxType::try_from(u8_buf[0]).map_err(|err| invalid(format!("{err:?}")))?;
+  let from_user_id = read_u64(reader)?;
+  let to_user_id = read_u64(reader)?;
+  let amount = read_u64(reader)?;
+  let timestamp = read_u64(reader)?;
+  reader.read_exact(&mut u8_buf)?;
+  let status =
+    Status::try_from(u8_buf[0]).map_err(|err| invalid(format!("{err:?}")))?;
+  reader.read_exact(&mut u32_buf)?;
+  let mut description = vec![0u8; u32::from_be_bytes(u32_buf) as usize];
+  reader.read_exact(&mut description)?;
+  let description =
+    String::from_utf8(description).map_err(|err| invalid(err.to_string()))?;
+
+  Ok(BankRecord {
+    tx_id,
+    tx_type,
+    from_user_id,
+    to_user_id,
+    amount,
+    timestamp,
+    status,
+    description,
+  })
+}
+
+#[cfg(test)]
+mod sort_test {
+  use crate::filter::Field;
+  use crate::record::{BankRecord, Status, TxType};
+  use crate::sort::{ExternalSorter, MergeSorted};
+  use std::io;
+  use std::num::NonZeroUsize;
+
+  fn records() -> Vec<BankRecord> {
+    let record = |tx_id: u64, status: Status, timestamp: u64| BankRecord {
+      tx_id,
+      tx_type: TxType::Transfer,
+      from_user_id: 1,
+      to_user_id: 2,
+      amount: 100,
+      timestamp,
+      status,
+      description: format!("Record \"{tx_id}\""),
+    };
+
+    vec![
+      record(1, Status::Pending, 30),
+      record(2, Status::Success, 20),
+      record(3, Status::Failure, 30),
+      record(4, Status::Success, 10),
+      record(5, Status::Pending, 20),
+      record(6, Status::Success, 30),
+      record(7, Status::Failure, 10),
+    ]
+  }
+
+  fn sort(
+    fields: Vec<Field>,
+    run_capacity: usize,
+  ) -> io::Result<Vec<BankRecord>> {
+    let mut sorter = ExternalSorter::new(fields)
+      .with_run_capacity(NonZeroUsize::new(run_capacity).unwrap());
+    for record in records() {
+      sorter.push(record)?;
+    }
+
+    sorter.finish()?.collect()
+  }
+
+  #[test]
+  fn test_sort_in_memory() -> io::Result<()> {
+    let sorted = sort(vec![Field::Timestamp], 100)?;
+    let tx_ids: Vec<u64> = sorted.iter().map(|record| record.tx_id).collect();
+
+    // Equal timestamps keep input order
+    assert_eq!(tx_ids, vec![4, 7, 2, 5, 1, 3, 6]);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_sort_spilled_runs() -> io::Result<()> {
+    let fields = vec![Field::Status, Field::Timestamp];
+    let in_memory = sort(fields.clone(), 100)?;
+    let spilled = sort(fields, 2)?;
+    let tx_ids: Vec<u64> = spilled.iter().map(|record| record.tx_id).collect();
+
+    assert_eq!(tx_ids, vec![4, 2, 6, 7, 3, 5, 1]);
+    assert_eq!(spilled, in_memory);
+    // Spilled descriptions are not altered
+    assert_eq!(spilled[0].description, "Record \"4\"");
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_merge_sorted() {
+    let input = |tx_ids: &[u64]| -> Vec<Result<BankRecord, String>> {
+      tx_ids
+        .iter()
+        .map(|tx_id| {
+          Ok(BankRecord {
+            tx_id: *tx_id,
+            ..BankRecord::new()
+          })
+        })
+        .collect()
+    };
+    let merged: Result<Vec<u64>, String> = MergeSorted::new(
+      vec![Field::TxId],
+      vec![
+        input(&[1, 4, 5]).into_iter(),
+        input(&[]).into_iter(),
+        input(&[2, 3, 6]).into_iter(),
+      ],
+    )
+    .map(|record| record.map(|record| record.tx_id))
+    .collect();
+
+    assert_eq!(merged, Ok(vec![1, 2, 3, 4, 5, 6]));
+
+    let mut failed = input(&[2, 3]);
+    failed.push(Err(String::from("Broken record")));
+    let merged: Vec<Result<u64, String>> = MergeSorted::new(
+      vec![Field::TxId],
+      vec![input(&[1, 4]).into_iter(), failed.into_iter()],
+    )
+    .map(|record| record.map(|record| record.tx_id))
+    .collect();
+
+    assert_eq!(
+      merged,
+      vec![Ok(1), Ok(2), Ok(3), Err(String::from("Broken record"))]
+    );
+  }
+}
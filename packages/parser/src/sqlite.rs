@@ -0,0 +1,374 @@
+//! SQLite databases of records.
+//!
+//! Records are stored in a table with a column for every record field, the
+//! table has indexes on `tx_id`, user ids and `timestamp`, so the database
+//! can be queried right away. Transaction types and statuses are stored as
+//! text, numbers have to fit into SQLite signed 64-bit integers, records
+//! with larger ones are not written.
+
+use crate::errors::SqliteError;
+use crate::record::{BankRecord, Status, TxType};
+use rusqlite::{Connection, OpenFlags, Row, params};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+/// Default table records are written to and read from
+pub const SQLITE_TABLE: &str = "transactions";
+
+/// Columns in the order of record fields, queries have to return them
+pub const SQLITE_COLUMNS: [&str; 8] = [
+  "tx_id",
+  "tx_type",
+  "from_user_id",
+  "to_user_id",
+  "amount",
+  "timestamp",
+  "status",
+  "description",
+];
+
+/// First bytes of every SQLite database file
+pub const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Writes records to a table, all records are inserted in a single
+/// transaction committed by `finish`
+pub struct SqliteWriter {
+  connection: Connection,
+  insert: String,
+}
+
+/// Quotes the identifier, so any table name can be used
+fn quote_identifier(identifier: &str) -> String {
+  format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// Query which reads all records of the table in insertion order
+pub fn table_query(table: &str) -> String {
+  format!(
+    "SELECT {} FROM {} ORDER BY rowid",
+    SQLITE_COLUMNS.join(", "),
+    quote_identifier(table)
+  )
+}
+
+impl SqliteWriter {
+  /// Opens or creates the database and the table, records of an existing
+  /// table are replaced once the written records are committed
+  pub fn create(path: &Path, table: &str) -> Result<Self, SqliteError> {
+    Self::open(path, table, false)
+  }
+
+  /// Same as `create`, but records are appended to an existing table
+  pub fn append(path: &Path, table: &str) -> Result<Self, SqliteError> {
+    Self::open(path, table, true)
+  }
+
+  fn open(path: &Path, table: &str, append: bool) -> Result<Self, SqliteError> {
+    let connection = Connection::open(path)?;
+    let quoted = quote_identifier(table);
+
+    let mut schema = format!(
+      "CREATE TABLE IF NOT EXISTS {quoted} (
+        tx_id INTEGER NOT NULL,
+        tx_type TEXT NOT NULL,
+        from_user_id INTEGER NOT NULL,
+        to_user_id INTEGER NOT NULL,
+        amount INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        status TEXT NOT NULL,
+        description TEXT NOT NULL
+      );"
+    );
+    for column in ["tx_id", "from_user_id", "to_user_id", "timestamp"] {
+      schema.push_str(&format!(
+        "CREATE INDEX IF NOT EXISTS {} ON {quoted} ({column});",
+        quote_identifier(&format!("{table}_{column}"))
+      ));
+    }
+    schema.push_str("BEGIN;");
+    if !append {
+      schema.push_str(&format!("DELETE FROM {quoted};"));
+    }
+    connection.execute_batch(&schema)?;
+
+    Ok(Self {
+      connection,
+      insert: format!(
+        "INSERT INTO {quoted} ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        SQLITE_COLUMNS.join(", ")
+      ),
+    })
+  }
+
+  pub fn write(&mut self, record: &BankRecord) -> Result<(), SqliteError> {
+    let integer = |column: &'static str, value: u64| {
+      i64::try_from(value).map_err(|_| SqliteError::OutOfRange {
+        column,
+        value,
+        tx_id: record.tx_id,
+      })
+    };
+
+    self
+      .connection
+      .prepare_cached(&self.insert)?
+      .execute(params![
+        integer("tx_id", record.tx_id)?,
+        record.tx_type.to_string(),
+        integer("from_user_id", record.from_user_id)?,
+        integer("to_user_id", record.to_user_id)?,
+        integer("amount", record.amount)?,
+        integer("timestamp", record.timestamp)?,
+        record.status.to_string(),
+        record.description,
+      ])?;
+
+    Ok(())
+  }
+
+  /// Commits written records
+  pub fn finish(self) -> Result<(), SqliteError> {
+    self.connection.execute_batch("COMMIT")?;
+
+    Ok(())
+  }
+}
+
+fn record_from_row(row: &Row) -> Result<BankRecord, SqliteError> {
+  let text = |column: &'static str| -> Result<String, SqliteError> {
+    Ok(row.get::<_, String>(column)?)
+  };
+  let invalid = |column: &'static str, value: String| {
+    SqliteError::InvalidValue { column, value }
+  };
+
+  let tx_type = text("tx_type")?;
+  let status = text("status")?;
+
+  Ok(BankRecord {
+    tx_id: row.get("tx_id")?,
+    tx_type: TxType::from_str(&tx_type)
+      .map_err(|_| invalid("tx_type", tx_type))?,
+    from_user_id: row.get("from_user_id")?,
+    to_user_id: row.get("to_user_id")?,
+    amount: row.get("amount")?,
+    timestamp: row.get("timestamp")?,
+    status: Status::from_str(&status).map_err(|_| invalid("status", status))?,
+    description: text("description")?,
+  })
+}
+
+/// Reads records returned by the query row by row, the database is opened
+/// read-only. Columns are matched by `SQLITE_COLUMNS` names, so any query
+/// returning them can be used, e.g. `table_query`.
+///
+/// Rows are read ahead by a thread owning the connection, at most
+/// `SQLITE_READ_AHEAD` records are kept in memory. Rows which can't be
+/// converted are returned as errors and reading continues, other errors end
+/// the records.
+pub struct SqliteReader {
+  records: Receiver<Result<BankRecord, SqliteError>>,
+}
+
+/// Records read ahead by `SqliteReader`
+pub const SQLITE_READ_AHEAD: usize = 1024;
+
+impl SqliteReader {
+  /// Opens the database and checks the query, rows are read by the iterator
+  pub fn open(path: &Path, query: &str) -> Result<Self, SqliteError> {
+    let connection = Connection::open_with_flags(
+      path,
+      OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    connection.prepare(query)?;
+
+    let (sender, records) = mpsc::sync_channel(SQLITE_READ_AHEAD);
+    let query = query.to_string();
+    thread::spawn(move || {
+      let read = || -> Result<(), SqliteError> {
+        let mut statement = connection.prepare(&query)?;
+        let mut rows = statement.query([])?;
+
+        while let Some(row) = rows.next()? {
+          // Reader is dropped, the remaining rows aren't needed
+          if sender.send(record_from_row(row)).is_err() {
+            return Ok(());
+          }
+        }
+
+        Ok(())
+      };
+
+      if let Err(err) = read() {
+        let _ = sender.send(Err(err));
+      }
+    });
+
+    Ok(Self { records })
+  }
+}
+
+impl Iterator for SqliteReader {
+  type Item = Result<BankRecord, SqliteError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.records.recv().ok()
+  }
+}
+
+/// Reads all records returned by the query, see `SqliteReader`
+pub fn read_records(
+  path: &Path,
+  query: &str,
+) -> Result<Vec<BankRecord>, SqliteError> {
+  SqliteReader::open(path, query)?.collect()
+}
+
+#[cfg(test)]
+mod sqlite_test {
+  use crate::errors::SqliteError;
+  use crate::record::{BankRecord, Status, TxType};
+  use crate::sqlite::{
+    SQLITE_HEADER, SqliteReader, SqliteWriter, read_records, table_query,
+  };
+  use std::fs;
+
+  fn record(tx_id: u64, status: Status) -> BankRecord {
+    BankRecord {
+      tx_id,
+      tx_type: TxType::Transfer,
+      from_user_id: 599094029349995112,
+      to_user_id: 2,
+      amount: 100 * tx_id,
+      timestamp: 1633036860000 + tx_id,
+      status,
+      description: format!("Record \"number\" {tx_id}"),
+    }
+  }
+
+  #[test]
+  fn test_sqlite_round_trip() -> Result<(), SqliteError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("records.sqlite");
+
+    for records in [
+      vec![record(4, Status::Success)],
+      vec![record(1, Status::Success), record(2, Status::Failure)],
+    ] {
+      let mut writer = SqliteWriter::create(&path, "daily \"records\"")?;
+      for record in &records {
+        writer.write(record)?;
+      }
+      writer.finish()?;
+    }
+
+    let mut writer = SqliteWriter::append(&path, "daily \"records\"")?;
+    writer.write(&record(3, Status::Pending))?;
+    writer.finish()?;
+
+    assert!(fs::read(&path).unwrap().starts_with(SQLITE_HEADER));
+    assert_eq!(
+      read_records(&path, &table_query("daily \"records\""))?,
+      vec![
+        record(1, Status::Success),
+        record(2, Status::Failure),
+        record(3, Status::Pending)
+      ]
+    );
+    assert_eq!(
+      read_records(
+        &path,
+        "SELECT * FROM \"daily \"\"records\"\"\" WHERE status = 'FAILURE'"
+      )?,
+      vec![record(2, Status::Failure)]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_sqlite_invalid_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("records.sqlite");
+    SqliteWriter::create(&path, "transactions")
+      .unwrap()
+      .finish()
+      .unwrap();
+
+    assert!(matches!(
+      read_records(&path, &table_query("missing")),
+      Err(SqliteError::Sqlite(_))
+    ));
+    assert!(matches!(
+      read_records(
+        &path,
+        "SELECT 1 AS tx_id, 'REFUND' AS tx_type, 0 AS from_user_id, \
+         2 AS to_user_id, 10 AS amount, 1 AS timestamp, \
+         'SUCCESS' AS status, '' AS description"
+      ),
+      Err(SqliteError::InvalidValue {
+        column: "tx_type",
+        ..
+      })
+    ));
+    assert!(matches!(
+      read_records(&path, "SELECT -1 AS tx_id"),
+      Err(SqliteError::Sqlite(_))
+    ));
+
+    // Reading continues after rows which can't be converted
+    let rows: Vec<_> = SqliteReader::open(
+      &path,
+      "SELECT 1 AS tx_id, 'REFUND' AS tx_type, 0 AS from_user_id, \
+       2 AS to_user_id, 10 AS amount, 1 AS timestamp, \
+       'SUCCESS' AS status, '' AS description \
+       UNION ALL SELECT 2, 'DEPOSIT', 0, 2, 10, 1, 'SUCCESS', ''",
+    )
+    .unwrap()
+    .collect();
+    assert!(matches!(rows[0], Err(SqliteError::InvalidValue { .. })));
+    assert!(matches!(&rows[1], Ok(record) if record.tx_id == 2));
+  }
+
+  #[test]
+  fn test_sqlite_out_of_range() -> Result<(), SqliteError> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("records.sqlite");
+
+    let mut writer = SqliteWriter::create(&path, "transactions")?;
+    writer.write(&record(1, Status::Success))?;
+    writer.finish()?;
+
+    let mut writer = SqliteWriter::create(&path, "transactions")?;
+    let result = writer.write(&BankRecord {
+      amount: u64::MAX,
+      ..record(2, Status::Success)
+    });
+
+    assert!(matches!(
+      result,
+      Err(SqliteError::OutOfRange {
+        column: "amount",
+        value: u64::MAX,
+        tx_id: 2,
+      })
+    ));
+    assert_eq!(
+      result.unwrap_err().to_string(),
+      "amount value 18446744073709551615 of transaction 2 does not fit into a \
+       signed 64-bit integer"
+    );
+
+    // Records of the table are kept if writing fails
+    drop(writer);
+    assert_eq!(
+      read_records(&path, &table_query("transactions"))?,
+      vec![record(1, Status::Success)]
+    );
+
+    Ok(())
+  }
+}
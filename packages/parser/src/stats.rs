@@ -0,0 +1,255 @@
+//! Aggregate statistics of record amounts, computed in a single pass over
+//! the records.
+//!
+//! Only per group accumulators are kept, so memory use depends on the number
+//! of groups and not on the number of records.
+
+use crate::date::Date;
+use crate::record::BankRecord;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+const MILLIS_PER_HOUR: u64 = 60 * 60 * 1000;
+
+/// Record property statistics are broken down by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GroupBy {
+  TxType,
+  Status,
+  /// Sender and receiver, a transfer is counted for both users
+  UserId,
+  /// Calendar day of the timestamp in UTC
+  Day,
+  /// Hour of the timestamp in UTC, e.g. `2021-10-01T13`
+  Hour,
+}
+
+/// Value of the grouped property, numbers are ordered by value
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GroupKey {
+  Number(u64),
+  Text(String),
+}
+
+/// Number of records and their amounts
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Summary {
+  pub count: u64,
+  pub sum: u128,
+  pub min: Option<u64>,
+  pub max: Option<u64>,
+}
+
+/// Summaries of all records and of every group of the breakdown property
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Breakdown {
+  pub by: GroupBy,
+  pub groups: BTreeMap<GroupKey, Summary>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+  pub total: Summary,
+  pub breakdowns: Vec<Breakdown>,
+}
+
+impl GroupBy {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::TxType => "tx_type",
+      Self::Status => "status",
+      Self::UserId => "user_id",
+      Self::Day => "day",
+      Self::Hour => "hour",
+    }
+  }
+
+  /// Groups the record belongs to, a record belongs to two user groups if
+  /// the sender and the receiver differ
+  pub fn keys(&self, record: &BankRecord) -> Vec<GroupKey> {
+    match self {
+      Self::TxType => vec![GroupKey::Text(record.tx_type.to_string())],
+      Self::Status => vec![GroupKey::Text(record.status.to_string())],
+      Self::UserId if record.from_user_id == record.to_user_id => {
+        vec![GroupKey::Number(record.from_user_id)]
+      }
+      Self::UserId => vec![
+        GroupKey::Number(record.from_user_id),
+        GroupKey::Number(record.to_user_id),
+      ],
+      Self::Day => {
+        vec![GroupKey::Text(
+          Date::from_timestamp(record.timestamp).to_string(),
+        )]
+      }
+      Self::Hour => vec![GroupKey::Text(format!(
+        "{}T{:02}",
+        Date::from_timestamp(record.timestamp),
+        record.timestamp / MILLIS_PER_HOUR % 24
+      ))],
+    }
+  }
+}
+
+impl Display for GroupKey {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Number(number) => write!(f, "{number}"),
+      Self::Text(text) => write!(f, "{text}"),
+    }
+  }
+}
+
+impl Summary {
+  pub fn add(&mut self, amount: u64) {
+    self.count += 1;
+    self.sum += amount as u128;
+    self.min = Some(self.min.map_or(amount, |min| min.min(amount)));
+    self.max = Some(self.max.map_or(amount, |max| max.max(amount)));
+  }
+
+  pub fn mean(&self) -> Option<f64> {
+    (self.count > 0).then(|| self.sum as f64 / self.count as f64)
+  }
+}
+
+impl Stats {
+  pub fn new(breakdowns: &[GroupBy]) -> Self {
+    Self {
+      total: Summary::default(),
+      breakdowns: breakdowns
+        .iter()
+        .map(|by| Breakdown {
+          by: *by,
+          groups: BTreeMap::new(),
+        })
+        .collect(),
+    }
+  }
+
+  pub fn add(&mut self, record: &BankRecord) {
+    self.total.add(record.amount);
+
+    for breakdown in &mut self.breakdowns {
+      for key in breakdown.by.keys(record) {
+        breakdown.groups.entry(key).or_default().add(record.amount);
+      }
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for GroupKey {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Summary {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeStruct;
+
+    let mut summary = serializer.serialize_struct("Summary", 5)?;
+    summary.serialize_field("count", &self.count)?;
+    summary.serialize_field("sum", &self.sum)?;
+    summary.serialize_field("min", &self.min)?;
+    summary.serialize_field("max", &self.max)?;
+    summary.serialize_field("mean", &self.mean())?;
+    summary.end()
+  }
+}
+
+#[cfg(test)]
+mod stats_test {
+  use crate::record::{BankRecord, Status, TxType};
+  use crate::stats::{GroupBy, GroupKey, Stats, Summary};
+
+  fn record(
+    tx_type: TxType,
+    from_user_id: u64,
+    to_user_id: u64,
+    amount: u64,
+    timestamp: u64,
+  ) -> BankRecord {
+    BankRecord {
+      tx_id: 1,
+      tx_type,
+      from_user_id,
+      to_user_id,
+      amount,
+      timestamp,
+      status: Status::Success,
+      description: String::new(),
+    }
+  }
+
+  #[test]
+  fn test_stats_breakdowns() {
+    let mut stats =
+      Stats::new(&[GroupBy::TxType, GroupBy::UserId, GroupBy::Hour]);
+
+    for record in [
+      record(TxType::Deposit, 0, 2, 100, 1633036860000),
+      record(TxType::Transfer, 2, 10, 40, 1633040460000),
+      record(TxType::Deposit, 0, 10, 300, 1633040520000),
+    ] {
+      stats.add(&record);
+    }
+
+    assert_eq!(
+      stats.total,
+      Summary {
+        count: 3,
+        sum: 440,
+        min: Some(40),
+        max: Some(300),
+      }
+    );
+    assert_eq!(stats.total.mean(), Some(440.0 / 3.0));
+
+    let deposits =
+      &stats.breakdowns[0].groups[&GroupKey::Text("DEPOSIT".into())];
+    assert_eq!((deposits.count, deposits.sum), (2, 400));
+
+    // User ids are ordered by value, transfer is counted for both users
+    let users: Vec<_> = stats.breakdowns[1]
+      .groups
+      .iter()
+      .map(|(key, summary)| (key.to_string(), summary.count))
+      .collect();
+    assert_eq!(
+      users,
+      vec![
+        (String::from("0"), 2),
+        (String::from("2"), 2),
+        (String::from("10"), 2),
+      ]
+    );
+
+    let hours: Vec<_> = stats.breakdowns[2]
+      .groups
+      .keys()
+      .map(GroupKey::to_string)
+      .collect();
+    assert_eq!(hours, vec!["2021-09-30T21", "2021-09-30T22"]);
+  }
+
+  #[test]
+  fn test_empty_summary() {
+    let stats = Stats::new(&[GroupBy::Day]);
+
+    assert_eq!(stats.total.mean(), None);
+    assert!(stats.breakdowns[0].groups.is_empty());
+  }
+}
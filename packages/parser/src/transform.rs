@@ -0,0 +1,217 @@
+//! Record transformations applied between reading and writing a report, e.g.
+//! to share production data without exposing users.
+//!
+//! User ids are pseudonymised with HMAC-SHA256 keyed hash, so the same id
+//! always maps to the same pseudonym for a key and the mapping can't be
+//! reversed without it. Zero user id stands for the bank in deposits and
+//! withdrawals and is kept as is.
+
+use crate::errors::TransformError;
+use crate::record::BankRecord;
+use regex::Regex;
+use std::borrow::Cow;
+
+/// Scale factors are parsed with up to this number of fractional digits
+const MAX_SCALE_DIGITS: u32 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserField {
+  FromUserId,
+  ToUserId,
+}
+
+#[derive(Debug, Clone)]
+pub enum Transform {
+  /// Replaces user id with a keyed hash of it
+  Pseudonymize { field: UserField, key: Vec<u8> },
+  /// Replaces every description match of the pattern
+  RedactDescription { pattern: Regex, replacement: String },
+  /// Shifts timestamp by milliseconds, saturating at the type bounds
+  ShiftTimestamp(i64),
+  /// Multiplies amount by `numerator / denominator`, rounding half up
+  ScaleAmount { numerator: u64, denominator: u64 },
+}
+
+impl Transform {
+  pub fn pseudonymize(field: UserField, key: impl Into<Vec<u8>>) -> Self {
+    Self::Pseudonymize {
+      field,
+      key: key.into(),
+    }
+  }
+
+  pub fn redact_description(
+    pattern: &str,
+    replacement: impl Into<String>,
+  ) -> Result<Self, TransformError> {
+    Ok(Self::RedactDescription {
+      pattern: Regex::new(pattern)?,
+      replacement: replacement.into(),
+    })
+  }
+
+  /// Parses decimal scale factor, e.g. `0.01` or `100`, without precision loss
+  pub fn scale_amount(factor: &str) -> Result<Self, TransformError> {
+    let invalid = || TransformError::InvalidFactor(factor.to_string());
+    let factor = factor.trim();
+    let (integer, fraction) = factor.split_once('.').unwrap_or((factor, ""));
+
+    if (integer.is_empty() && fraction.is_empty())
+      || fraction.len() > MAX_SCALE_DIGITS as usize
+      || !integer
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+      return Err(invalid());
+    }
+
+    let denominator = 10u64.pow(fraction.len() as u32);
+    let numerator = format!("{integer}{fraction}")
+      .parse::<u64>()
+      .map_err(|_| invalid())?;
+
+    Ok(Self::ScaleAmount {
+      numerator,
+      denominator,
+    })
+  }
+
+  pub fn apply(&self, record: &mut BankRecord) {
+    match self {
+      Self::Pseudonymize { field, key } => {
+        let user_id = match field {
+          UserField::FromUserId => &mut record.from_user_id,
+          UserField::ToUserId => &mut record.to_user_id,
+        };
+        *user_id = pseudonym(*user_id, key);
+      }
+      Self::RedactDescription {
+        pattern,
+        replacement,
+      } => {
+        if let Cow::Owned(description) =
+          pattern.replace_all(&record.description, replacement.as_str())
+        {
+          record.description = description;
+        }
+      }
+      Self::ShiftTimestamp(shift) => {
+        record.timestamp = record.timestamp.saturating_add_signed(*shift);
+      }
+      Self::ScaleAmount {
+        numerator,
+        denominator,
+      } => {
+        let denominator = *denominator as u128;
+        let scaled = (record.amount as u128 * *numerator as u128
+          + denominator / 2)
+          / denominator;
+        record.amount = u64::try_from(scaled).unwrap_or(u64::MAX);
+      }
+    }
+  }
+}
+
+/// Applies transformations to the record in order
+pub fn apply_all(transforms: &[Transform], record: &mut BankRecord) {
+  for transform in transforms {
+    transform.apply(record);
+  }
+}
+
+fn pseudonym(user_id: u64, key: &[u8]) -> u64 {
+  if user_id == 0 {
+    return 0;
+  }
+
+  let hash = hmac_sha256::HMAC::mac(user_id.to_be_bytes(), key);
+  let pseudonym = u64::from_be_bytes(hash[..8].try_into().unwrap());
+
+  // Zero is reserved for the bank
+  pseudonym.max(1)
+}
+
+#[cfg(test)]
+mod transform_test {
+  use crate::errors::TransformError;
+  use crate::record::{BankRecord, Status, TxType};
+  use crate::transform::{Transform, UserField, apply_all};
+
+  fn record() -> BankRecord {
+    BankRecord {
+      tx_id: 1000000000000000,
+      tx_type: TxType::Transfer,
+      from_user_id: 10,
+      to_user_id: 0,
+      amount: 1505,
+      timestamp: 1633036860000,
+      status: Status::Success,
+      description: String::from("Card 4111-1111 payment"),
+    }
+  }
+
+  #[test]
+  fn test_pseudonymize() {
+    let mut first = record();
+    let mut second = BankRecord {
+      to_user_id: 10,
+      ..record()
+    };
+    let transforms = [
+      Transform::pseudonymize(UserField::FromUserId, "key"),
+      Transform::pseudonymize(UserField::ToUserId, "key"),
+    ];
+
+    apply_all(&transforms, &mut first);
+    apply_all(&transforms, &mut second);
+
+    assert_ne!(first.from_user_id, 10);
+    // Same id maps to the same pseudonym, bank id is kept
+    assert_eq!(first.from_user_id, second.to_user_id);
+    assert_eq!(first.to_user_id, 0);
+
+    let mut other_key = record();
+    Transform::pseudonymize(UserField::FromUserId, "other")
+      .apply(&mut other_key);
+    assert_ne!(first.from_user_id, other_key.from_user_id);
+  }
+
+  #[test]
+  fn test_redact_and_shift() -> Result<(), TransformError> {
+    let mut record = record();
+    let transforms = [
+      Transform::redact_description(r"\d{4}-\d{4}", "****")?,
+      Transform::ShiftTimestamp(-60000),
+    ];
+
+    apply_all(&transforms, &mut record);
+
+    assert_eq!(record.description, "Card **** payment");
+    assert_eq!(record.timestamp, 1633036800000);
+
+    Transform::ShiftTimestamp(i64::MIN).apply(&mut record);
+    assert_eq!(record.timestamp, 0);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_scale_amount() -> Result<(), TransformError> {
+    let scale = |factor: &str| -> Result<u64, TransformError> {
+      let mut record = record();
+      Transform::scale_amount(factor)?.apply(&mut record);
+      Ok(record.amount)
+    };
+
+    assert_eq!(scale("2")?, 3010);
+    assert_eq!(scale("0.01")?, 15);
+    assert_eq!(scale(".1")?, 151);
+    assert_eq!(scale("1.")?, 1505);
+    assert!(scale("-1").is_err());
+    assert!(scale("1e3").is_err());
+    assert!(scale(".").is_err());
+
+    Ok(())
+  }
+}
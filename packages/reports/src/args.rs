@@ -0,0 +1,220 @@
+//! Arguments and value parsers shared by commands
+
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use crate::sources::InputSource;
+use clap::{Args, ValueEnum};
+use parser::compression::Compression;
+use parser::parsers::Mt940Accounts;
+use std::ffi::OsStr;
+use std::io;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Path value which stands for stdin
+pub const STDIN_PATH: &str = "-";
+
+/// Sources a single `--input` value is expanded into, directories and glob
+/// patterns are expanded into sorted lists of report files
+#[derive(Debug, Clone)]
+pub struct InputSources(pub Vec<InputSource>);
+
+/// Mapping of MT940 statement accounts to user ids
+#[derive(Debug, Args)]
+pub struct Mt940Args {
+  /// Map an MT940 statement account to a user id, e.g.
+  /// `NL91ABNA0417164300=42`, numeric accounts which aren't mapped are user
+  /// ids
+  #[arg(
+    long = "mt940-account",
+    value_name = "Account=User id",
+    value_parser = account_validation
+  )]
+  pub mt940_accounts: Vec<(String, u64)>,
+}
+
+impl Mt940Args {
+  pub fn accounts(self) -> Mt940Accounts {
+    self.mt940_accounts.into_iter().collect()
+  }
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum CompressFormat {
+  Gzip,
+  Zstd,
+}
+
+impl From<CompressFormat> for Compression {
+  fn from(format: CompressFormat) -> Self {
+    match format {
+      CompressFormat::Gzip => Compression::Gzip,
+      CompressFormat::Zstd => Compression::Zstd,
+    }
+  }
+}
+
+/// Flattens expanded `--input` values into sources in order
+pub fn flatten_sources(inputs: Vec<InputSources>) -> Vec<InputSource> {
+  inputs.into_iter().flat_map(|sources| sources.0).collect()
+}
+
+/// Report file path or `-` for stdin
+pub fn source_validation(input: &str) -> Result<InputSource, ReportsError> {
+  if input == STDIN_PATH {
+    return Ok(InputSource::Stdin);
+  }
+
+  Ok(InputSource::File(path_validation(input)?))
+}
+
+/// Report file path, directory or glob pattern, `-` for stdin
+pub fn input_validation(input: &str) -> Result<InputSources, ReportsError> {
+  if input == STDIN_PATH {
+    return Ok(InputSources(vec![InputSource::Stdin]));
+  }
+
+  let paths: Vec<PathBuf> = if Path::new(input).is_dir() {
+    let mut paths = Path::new(input)
+      .read_dir()?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    paths
+  } else if input.contains(['*', '?', '[']) {
+    glob::glob(input)
+      .map_err(|err| {
+        ReportsError::IO(io::Error::new(ErrorKind::InvalidInput, err))
+      })?
+      .filter_map(Result::ok)
+      .collect()
+  } else {
+    return Ok(InputSources(vec![source_validation(input)?]));
+  };
+
+  let files: Vec<InputSource> = paths
+    .into_iter()
+    .filter(|path| {
+      path.is_file()
+        && DataFormat::from_path(path)
+          .is_some_and(|format| format.readable().is_ok())
+    })
+    .map(InputSource::File)
+    .collect();
+
+  if files.is_empty() {
+    return Err(ReportsError::IO(io::Error::new(
+      ErrorKind::NotFound,
+      format!("No report files found: {input}"),
+    )));
+  }
+
+  Ok(InputSources(files))
+}
+
+#[allow(clippy::collapsible_if)]
+fn path_validation(path: &str) -> Result<PathBuf, ReportsError> {
+  let path = PathBuf::from_str(path).map_err(|_| {
+    ReportsError::IO(io::Error::new(
+      ErrorKind::NotFound,
+      format!("Failed reading provided file path: {path}"),
+    ))
+  })?;
+
+  if !path.exists() {
+    return Err(ReportsError::IO(io::Error::new(
+      ErrorKind::NotFound,
+      format!("Failed reading provided file path: {path:?}"),
+    )));
+  }
+
+  // Compressed reports keep the data extension, e.g. `records.csv.gz`,
+  // write-only formats are rejected
+  match DataFormat::from_path(&path) {
+    Some(format) => {
+      format.readable()?;
+      Ok(path)
+    }
+    None => Err(ReportsError::InvalidSourceFile),
+  }
+}
+
+/// Uncompressed binary report file path, the file is read by record offsets
+pub fn bin_path_validation(input: &str) -> Result<PathBuf, ReportsError> {
+  let path = path_validation(input)?;
+
+  if path.extension().and_then(OsStr::to_str)
+    != Some(DataFormat::Bin.extension())
+  {
+    return Err(ReportsError::IO(io::Error::new(
+      ErrorKind::InvalidInput,
+      format!("Only uncompressed binary reports are supported: {path:?}"),
+    )));
+  }
+
+  Ok(path)
+}
+
+/// Account mapping, `ACCOUNT=USER_ID`
+pub fn account_validation(value: &str) -> Result<(String, u64), ReportsError> {
+  value
+    .rsplit_once('=')
+    .filter(|(account, _)| !account.is_empty())
+    .and_then(|(account, user_id)| {
+      Some((account.to_string(), user_id.parse().ok()?))
+    })
+    .ok_or_else(|| ReportsError::InvalidAccount(value.to_string()))
+}
+
+#[cfg(test)]
+mod args_test {
+  use crate::args::{bin_path_validation, input_validation};
+  use crate::errors::ReportsError;
+  use std::fs;
+  use std::io::ErrorKind;
+
+  #[test]
+  fn test_input_write_only_format() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    for name in ["records.ofx", "records.qif", "records.csv"] {
+      fs::write(dir.path().join(name), "")?;
+    }
+
+    for name in ["records.ofx", "records.qif"] {
+      let path = dir.path().join(name);
+      assert!(matches!(
+        input_validation(path.to_str().unwrap()),
+        Err(ReportsError::IO(err)) if err.kind() == ErrorKind::Unsupported
+      ));
+    }
+
+    // Directories skip write-only reports
+    let sources = input_validation(dir.path().to_str().unwrap())?;
+    assert_eq!(sources.0.len(), 1);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_bin_path() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    for name in ["records.bin", "records.bin.gz", "records.csv"] {
+      fs::write(dir.path().join(name), "")?;
+    }
+
+    assert!(
+      bin_path_validation(dir.path().join("records.bin").to_str().unwrap())
+        .is_ok()
+    );
+    for name in ["records.bin.gz", "records.csv"] {
+      let path = dir.path().join(name);
+      assert!(matches!(
+        bin_path_validation(path.to_str().unwrap()),
+        Err(ReportsError::IO(err)) if err.kind() == ErrorKind::InvalidInput
+      ));
+    }
+
+    Ok(())
+  }
+}
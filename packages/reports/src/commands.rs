@@ -0,0 +1,11 @@
+pub mod compare;
+pub mod convert;
+pub mod index;
+pub mod ledger;
+pub mod merge;
+pub mod query;
+pub mod reconcile;
+pub mod repair;
+pub mod split;
+pub mod stats;
+pub mod validate;
@@ -0,0 +1,325 @@
+use crate::args::{Mt940Args, source_validation};
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use crate::sources::{InputSource, Source, SourceOptions};
+use clap::Args;
+use parser::record::BankRecord;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::io;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::num::NonZeroUsize;
+
+/*
+For the purposes if this implementation I'd like to assume following things:
+- Hashed records are unique since they have timestamp field (at least no
+duplicates were found in data examples)
+- Reports may be subset of each other, so line by line comparison is not an option
+- Reports may have some intersection in records, and unique records of their own
+*/
+
+#[derive(Debug, Args)]
+pub struct CompareArgs {
+  /// First report path, `-` reads stdin
+  #[arg(long, value_name = "File path", value_parser = source_validation)]
+  pub file1: InputSource,
+  /// Format of the first report, detected if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub format1: Option<DataFormat>,
+  /// Second report path, `-` reads stdin
+  #[arg(long, value_name = "File path", value_parser = source_validation)]
+  pub file2: InputSource,
+  /// Format of the second report, detected if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub format2: Option<DataFormat>,
+  /// Number of threads used for parsing binary, csv and json reports
+  #[arg(short = 'j', long, value_name = "Threads", default_value = "1")]
+  pub jobs: NonZeroUsize,
+  #[command(flatten)]
+  pub mt940: Mt940Args,
+}
+
+/// Compares records of two reports and writes the differences to stdout
+pub fn run(args: CompareArgs) -> Result<(), ReportsError> {
+  let CompareArgs {
+    file1,
+    format1,
+    file2,
+    format2,
+    jobs,
+    mt940,
+  } = args;
+
+  if file1 == InputSource::Stdin && file2 == InputSource::Stdin {
+    return Err(ReportsError::IO(io::Error::new(
+      ErrorKind::InvalidInput,
+      "Only one report can be read from stdin",
+    )));
+  }
+
+  let options = SourceOptions {
+    mt940_accounts: mt940.accounts(),
+    ..SourceOptions::default()
+  };
+  let source1 = Source {
+    name: report_name(&file1, "File 1"),
+    ..Source::open_with(&file1, format1, &options)?
+  };
+  let source2 = Source {
+    name: report_name(&file2, "File 2"),
+    ..Source::open_with(&file2, format2, &options)?
+  };
+
+  let stdout = io::stdout().lock();
+  compare(source1, source2, &mut BufWriter::new(stdout), jobs)
+}
+
+fn report_name(input: &InputSource, default: &str) -> String {
+  match input {
+    InputSource::Stdin => input.to_string(),
+    InputSource::File(path) => path
+      .file_name()
+      .unwrap_or(OsStr::new(default))
+      .to_string_lossy()
+      .into_owned(),
+  }
+}
+
+/// Writes records which are missing in one of the reports, records are
+/// compared regardless of their order
+fn compare(
+  source1: Source,
+  source2: Source,
+  buf_writer: &mut impl Write,
+  jobs: NonZeroUsize,
+) -> Result<(), ReportsError> {
+  let (name1, name2) = (source1.name.clone(), source2.name.clone());
+  let records1_set: HashSet<BankRecord> =
+    source1.parallel_records(jobs).collect::<Result<_, _>>()?;
+  let records2_set: HashSet<BankRecord> =
+    source2.parallel_records(jobs).collect::<Result<_, _>>()?;
+
+  let file1_diff = records1_set.difference(&records2_set);
+  let file2_diff = records2_set.difference(&records1_set);
+  let file1_diff_count = file1_diff.clone().count();
+  let file2_diff_count = file2_diff.clone().count();
+
+  if file1_diff_count == 0 && file2_diff_count == 0 {
+    writeln!(
+      buf_writer,
+      "The transaction records in {:?} and {:?} are identical.\nGreat job, now you can go home!",
+      name1, name2,
+    )?;
+  } else {
+    writeln!(
+      buf_writer,
+      "The following transactions didn't match between files:",
+    )?;
+    writeln!(buf_writer)?;
+
+    for record in file1_diff {
+      writeln!(
+        buf_writer,
+        "File: {:?}\nRecord id: {} ",
+        name1, record.tx_id,
+      )
+      .expect("Failed writing to stdout");
+      writeln!(buf_writer)?;
+    }
+    for record in file2_diff {
+      writeln!(
+        buf_writer,
+        "File: {:?}\nRecord id: {} ",
+        name2, record.tx_id,
+      )
+      .expect("Failed writing to stdout");
+      writeln!(buf_writer)?;
+    }
+
+    writeln!(
+      buf_writer,
+      "Please revise your files and don't upset your manager",
+    )?;
+  }
+
+  buf_writer.flush()?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod compare_test {
+  use crate::commands::compare::compare;
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::sources::Source;
+  use std::ffi::OsStr;
+  use std::fs::File;
+  use std::io::BufReader;
+  use std::num::NonZeroUsize;
+  use std::path::Path;
+
+  #[test]
+  fn test_matrix() -> Result<(), ReportsError> {
+    let file_configs = [
+      (Path::new("./tests/stub_files/records.csv"), DataFormat::Csv),
+      (Path::new("./tests/stub_files/records.bin"), DataFormat::Bin),
+      (Path::new("./tests/stub_files/records.txt"), DataFormat::Txt),
+    ];
+
+    // Run matrix of tests for bin, csv and test files
+    for (file_path_1, data_format_1) in &file_configs {
+      for (file_path_2, data_format_2) in &file_configs {
+        let file_1 = File::open(file_path_1)?;
+        let file_2 = File::open(file_path_2)?;
+        let file_1_name = file_path_1
+          .file_name()
+          .unwrap_or(OsStr::new("File 1"))
+          .to_str()
+          .unwrap();
+        let file_2_name = file_path_2
+          .file_name()
+          .unwrap_or(OsStr::new("File 2"))
+          .to_str()
+          .unwrap();
+        let mut output_buffer: Vec<u8> = vec![];
+
+        let result = compare(
+          Source::new(
+            file_1_name.to_string(),
+            BufReader::new(file_1),
+            *data_format_1,
+          ),
+          Source::new(
+            file_2_name.to_string(),
+            BufReader::new(file_2),
+            *data_format_2,
+          ),
+          &mut output_buffer,
+          NonZeroUsize::MIN,
+        );
+
+        let assert_output = format!(
+          "The transaction records in {:?} and {:?} are identical.\nGreat job, now you can go home!\n",
+          file_1_name, file_2_name
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(output_buffer, assert_output.as_bytes());
+      }
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_parallel_matrix() -> Result<(), ReportsError> {
+    let file_configs = [
+      (Path::new("./tests/stub_files/records.csv"), DataFormat::Csv),
+      (Path::new("./tests/stub_files/records.bin"), DataFormat::Bin),
+      (Path::new("./tests/stub_files/records.txt"), DataFormat::Txt),
+    ];
+    let jobs = NonZeroUsize::new(4).unwrap();
+
+    // Text reports are parsed sequentially, the others are split into chunks
+    for (file_path_1, data_format_1) in &file_configs {
+      for (file_path_2, data_format_2) in &file_configs {
+        let mut output_buffer: Vec<u8> = vec![];
+
+        compare(
+          Source::new(
+            "File 1",
+            BufReader::new(File::open(file_path_1)?),
+            *data_format_1,
+          ),
+          Source::new(
+            "File 2",
+            BufReader::new(File::open(file_path_2)?),
+            *data_format_2,
+          ),
+          &mut output_buffer,
+          jobs,
+        )?;
+
+        assert_eq!(
+          String::from_utf8(output_buffer).unwrap(),
+          "The transaction records in \"File 1\" and \"File 2\" are identical.\nGreat job, now you can go home!\n"
+        );
+      }
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_reports_diffs() -> Result<(), ReportsError> {
+    let file_configs_1 = [
+      (Path::new("./tests/stub_files/records.bin"), DataFormat::Bin),
+      (Path::new("./tests/stub_files/records.csv"), DataFormat::Csv),
+      (Path::new("./tests/stub_files/records.txt"), DataFormat::Txt),
+    ];
+    let file_configs_2 = [
+      (
+        Path::new("./tests/stub_files/records_short.bin"),
+        DataFormat::Bin,
+      ),
+      (
+        Path::new("./tests/stub_files/records_short.csv"),
+        DataFormat::Csv,
+      ),
+      (
+        Path::new("./tests/stub_files/records_short.txt"),
+        DataFormat::Txt,
+      ),
+    ];
+
+    for (file_path_1, data_format_1) in &file_configs_1 {
+      for (file_path_2, data_format_2) in &file_configs_2 {
+        let file_1 = File::open(file_path_1)?;
+        let file_2 = File::open(file_path_2)?;
+        let file_1_name = file_path_1
+          .file_name()
+          .unwrap_or(OsStr::new("File 1"))
+          .to_str()
+          .unwrap();
+        let file_2_name = file_path_2
+          .file_name()
+          .unwrap_or(OsStr::new("File 2"))
+          .to_str()
+          .unwrap();
+        let mut output_buffer: Vec<u8> = vec![];
+
+        let result = compare(
+          Source::new(
+            file_1_name.to_string(),
+            BufReader::new(file_1),
+            *data_format_1,
+          ),
+          Source::new(
+            file_2_name.to_string(),
+            BufReader::new(file_2),
+            *data_format_2,
+          ),
+          &mut output_buffer,
+          NonZeroUsize::MIN,
+        );
+
+        let mut assert_output = String::from(
+          "The following transactions didn't match between files:\n\n",
+        );
+        // With multiple missing records order of reporting was quite not stable for order of records, so I left only 1 record missing
+        assert_output.push_str(&format!(
+          "File: {:?}\nRecord id: {} \n\n",
+          file_1_name, 1000000000000003u64
+        ));
+        assert_output
+          .push_str("Please revise your files and don't upset your manager\n");
+
+        assert!(result.is_ok());
+        assert_eq!(output_buffer, assert_output.as_bytes());
+      }
+    }
+
+    Ok(())
+  }
+}
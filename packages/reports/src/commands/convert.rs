@@ -0,0 +1,1204 @@
+mod args;
+
+pub use args::{
+  ConvertArgs, DEFAULT_REDACTION, DedupeBy, FilterArgs, PseudonymizeField,
+  TransformArgs, user_filter,
+};
+
+use crate::args::flatten_sources;
+use crate::errors::ReportsError;
+use crate::formats::{DataFormat, ReportWriter, now};
+use crate::sources::{Source, SourceOptions};
+use parser::columnar::{BatchSink, ColumnarWriter};
+use parser::compression::{CompressedWriter, Compression};
+use parser::dedupe::{DedupeKey, DedupeReport, Deduplicator};
+use parser::filter::{Field, Filter};
+use parser::parsers::{OfxSyntax, OfxWriter, QifWriter};
+use parser::record::BankRecord;
+use parser::sort::{DEFAULT_RUN_CAPACITY, ExternalSorter};
+use parser::sqlite::{SqliteWriter, table_query};
+use parser::transform::{Transform, apply_all};
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::num::NonZeroUsize;
+
+/// Conversion settings shared by all sources
+struct ConvertOptions {
+  output_format: DataFormat,
+  checksum: bool,
+  jobs: NonZeroUsize,
+  filter: Filter,
+  transforms: Vec<Transform>,
+  sort_by: Vec<Field>,
+  sort_buffer: NonZeroUsize,
+  dedupe_by: Option<DedupeKey>,
+}
+
+impl ConvertOptions {
+  fn new(output_format: DataFormat) -> Self {
+    Self {
+      output_format,
+      checksum: false,
+      jobs: NonZeroUsize::MIN,
+      filter: Filter::default(),
+      transforms: vec![],
+      sort_by: vec![],
+      sort_buffer: DEFAULT_RUN_CAPACITY,
+      dedupe_by: None,
+    }
+  }
+}
+
+/// Converts input reports into a single report written to stdout or the
+/// output file
+pub fn run(args: ConvertArgs) -> Result<(), ReportsError> {
+  let ConvertArgs {
+    input,
+    input_format,
+    output_format,
+    checksum,
+    jobs,
+    compress,
+    output,
+    for_user,
+    sqlite_table,
+    sqlite_append,
+    sqlite_query,
+    mt940,
+    sort_by,
+    sort_buffer,
+    dedupe_by,
+    filter,
+    transform,
+  } = args;
+
+  let source_options = SourceOptions {
+    sqlite_query: sqlite_query.unwrap_or_else(|| table_query(&sqlite_table)),
+    mt940_accounts: mt940.accounts(),
+  };
+  // Sources are opened one by one, so only one file is open at a time
+  let sources = flatten_sources(input)
+    .into_iter()
+    .map(move |input| Source::open_with(&input, input_format, &source_options));
+
+  let options = ConvertOptions {
+    checksum,
+    jobs,
+    filter: match for_user {
+      Some(user_id) => filter.into_filter().and(user_filter(user_id)),
+      None => filter.into_filter(),
+    },
+    transforms: transform.into_transforms()?,
+    sort_by,
+    sort_buffer,
+    dedupe_by: dedupe_by.map(DedupeKey::from),
+    ..ConvertOptions::new(output_format)
+  };
+
+  let compression = compress.map_or(Compression::None, Compression::from);
+  let dedupe_report = match (output_format, output) {
+    (DataFormat::Sqlite, Some(path)) => {
+      if compression != Compression::None {
+        return Err(ReportsError::IO(io::Error::new(
+          ErrorKind::InvalidInput,
+          "SQLite output can't be compressed",
+        )));
+      }
+
+      let mut sqlite_writer = if sqlite_append {
+        SqliteWriter::append(&path, &sqlite_table)?
+      } else {
+        SqliteWriter::create(&path, &sqlite_table)?
+      };
+      let dedupe_report = convert_records(sources, &options, |record| {
+        Ok(sqlite_writer.write(&record)?)
+      })?;
+      sqlite_writer.finish()?;
+      dedupe_report
+    }
+    (format, output) => {
+      // Parquet writers have to be sendable, so stdout isn't locked
+      let writer: Box<dyn Write + Send> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+      };
+      let buf_writer =
+        CompressedWriter::new(BufWriter::new(writer), compression)?;
+
+      let (dedupe_report, buf_writer) = match format {
+        DataFormat::Arrow => {
+          convert_columnar(sources, ColumnarWriter::ipc(buf_writer)?, &options)?
+        }
+        DataFormat::Parquet => convert_columnar(
+          sources,
+          ColumnarWriter::parquet(buf_writer)?,
+          &options,
+        )?,
+        DataFormat::Ofx | DataFormat::OfxXml | DataFormat::Qif => {
+          let user_id = for_user.ok_or_else(|| {
+            io::Error::new(
+              ErrorKind::InvalidInput,
+              "OFX and QIF statements are written for a user, set --for-user",
+            )
+          })?;
+          convert_for_user(sources, buf_writer, user_id, &options)?
+        }
+        _ => {
+          let mut buf_writer = buf_writer;
+          (convert(sources, &mut buf_writer, &options)?, buf_writer)
+        }
+      };
+      buf_writer.finish()?.flush()?;
+      dedupe_report
+    }
+  };
+
+  if let Some(report) = dedupe_report {
+    writeln!(io::stderr().lock(), "{report}")?;
+  }
+
+  Ok(())
+}
+
+/// Converts sources in order into a single output stream, errors are
+/// attributed to the source they happened in. Report of removed duplicates
+/// is returned if deduplication is enabled.
+fn convert<'a>(
+  sources: impl IntoIterator<Item = Result<Source<'a>, ReportsError>>,
+  writer: &mut impl Write,
+  options: &ConvertOptions,
+) -> Result<Option<DedupeReport>, ReportsError> {
+  let mut report_writer =
+    ReportWriter::new(options.output_format, options.checksum);
+  report_writer.write_header(writer)?;
+
+  let dedupe_report = convert_records(sources, options, |record| {
+    Ok(report_writer.write_record(writer, record)?)
+  })?;
+
+  report_writer.write_footer(writer)?;
+  writer.flush()?;
+
+  Ok(dedupe_report)
+}
+
+/// Converts sources in order into a columnar file, the underlying writer of
+/// the completed file is returned with the report of removed duplicates
+fn convert_columnar<'a, S: BatchSink>(
+  sources: impl IntoIterator<Item = Result<Source<'a>, ReportsError>>,
+  mut writer: ColumnarWriter<S>,
+  options: &ConvertOptions,
+) -> Result<(Option<DedupeReport>, S::Inner), ReportsError> {
+  let dedupe_report =
+    convert_records(sources, options, |record| Ok(writer.write(&record)?))?;
+
+  Ok((dedupe_report, writer.finish()?))
+}
+
+/// Converts sources in order into a statement of the user for personal
+/// finance tools, the underlying writer of the completed statement is
+/// returned with the report of removed duplicates
+fn convert_for_user<'a, W: Write>(
+  sources: impl IntoIterator<Item = Result<Source<'a>, ReportsError>>,
+  writer: W,
+  user_id: u64,
+  options: &ConvertOptions,
+) -> Result<(Option<DedupeReport>, W), ReportsError> {
+  if options.output_format == DataFormat::Qif {
+    let mut writer = QifWriter::new(writer, user_id)?;
+    let dedupe_report =
+      convert_records(sources, options, |record| Ok(writer.write(&record)?))?;
+
+    return Ok((dedupe_report, writer.finish()?));
+  }
+
+  let syntax = match options.output_format {
+    DataFormat::OfxXml => OfxSyntax::Xml,
+    _ => OfxSyntax::Sgml,
+  };
+  let mut writer = OfxWriter::new(writer, syntax, user_id, now())?;
+  let dedupe_report =
+    convert_records(sources, options, |record| Ok(writer.write(&record)?))?;
+
+  Ok((dedupe_report, writer.finish()?))
+}
+
+/// Passes records of all sources in output order to the `write` function
+fn convert_records<'a>(
+  sources: impl IntoIterator<Item = Result<Source<'a>, ReportsError>>,
+  options: &ConvertOptions,
+  mut write: impl FnMut(BankRecord) -> Result<(), ReportsError>,
+) -> Result<Option<DedupeReport>, ReportsError> {
+  let mut deduplicator = options.dedupe_by.map(Deduplicator::new);
+  // Sorted output can be written only after all sources are read
+  let mut sorter = (!options.sort_by.is_empty()).then(|| {
+    ExternalSorter::new(options.sort_by.clone())
+      .with_run_capacity(options.sort_buffer)
+  });
+
+  // Records are streamed into the sorter, which spills sorted runs to disk
+  for source in sources {
+    for record in source?.parallel_records(options.jobs) {
+      let mut record = record?;
+      if !options.filter.matches(&record) {
+        continue;
+      }
+      apply_all(&options.transforms, &mut record);
+
+      if let Some(deduplicator) = &mut deduplicator
+        && !deduplicator.insert(&record)
+      {
+        continue;
+      }
+
+      match &mut sorter {
+        Some(sorter) => sorter.push(record)?,
+        None => write(record)?,
+      }
+    }
+  }
+
+  if let Some(sorter) = sorter {
+    for record in sorter.finish()? {
+      write(record?)?;
+    }
+  }
+
+  Ok(deduplicator.map(Deduplicator::into_report))
+}
+
+#[cfg(test)]
+mod convert_test {
+  use crate::commands::convert::{ConvertArgs, ConvertOptions, convert, run};
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::sources::{InputSource, Source, SourceOptions};
+  use crate::test_utils::{bin_report, record};
+  use clap::{Args, Command, FromArgMatches};
+  use parser::dedupe::DedupeKey;
+  use parser::filter::{Field, Filter};
+  use parser::parsers::{
+    BIN_CHECKED_RECORD_HEADER, BIN_RECORD_HEADER, BinRecord,
+  };
+  use parser::record::{BankRecord, BankRecordParser, Status, TxType};
+  use parser::transform::Transform;
+  use std::fs;
+  use std::io::BufRead;
+  use std::io::Cursor;
+  use std::num::NonZeroUsize;
+  use std::str::FromStr;
+
+  fn parse_args(args: &[&str]) -> Result<ConvertArgs, clap::Error> {
+    let command = ConvertArgs::augment_args(Command::new("convert"));
+    ConvertArgs::from_arg_matches(&command.try_get_matches_from(args)?)
+  }
+
+  fn source<'a>(
+    reader: impl BufRead + 'a,
+    format: DataFormat,
+  ) -> Result<Source<'a>, ReportsError> {
+    Ok(Source::new("input", reader, format))
+  }
+
+  #[test]
+  fn test_convert_txt_to_csv() {
+    let source_data = String::from(
+      "# Record 1 (DEPOSIT)
+TX_TYPE: DEPOSIT
+TO_USER_ID: 9223372036854775807
+FROM_USER_ID: 0
+TIMESTAMP: 1633036860000
+DESCRIPTION: \"Record number 1\"
+TX_ID: 1000000000000000
+AMOUNT: 100
+STATUS: FAILURE
+
+# Record 2 (TRANSFER)
+DESCRIPTION: \"Record number 2\"
+TIMESTAMP: 1633036920000
+STATUS: PENDING
+AMOUNT: 200
+TX_ID: 1000000000000001
+TX_TYPE: TRANSFER
+FROM_USER_ID: 9223372036854775807
+TO_USER_ID: 9223372036854775807
+
+",
+    );
+    let assert_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1000000000000000,DEPOSIT,0,9223372036854775807,100,1633036860000,FAILURE,\"Record number 1\"
+1000000000000001,TRANSFER,9223372036854775807,9223372036854775807,200,1633036920000,PENDING,\"Record number 2\"
+"
+    );
+
+    let mut input_buffer: Cursor<String> = Cursor::new(source_data.clone());
+    let mut output_buffer: Vec<u8> = vec![];
+    let input_format = DataFormat::Txt;
+    let output_format = DataFormat::Csv;
+
+    let result = convert(
+      [source(&mut input_buffer, input_format)],
+      &mut output_buffer,
+      &ConvertOptions::new(output_format),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output_buffer, assert_data.as_bytes());
+  }
+
+  #[test]
+  fn test_convert_csv_to_bin() {
+    let source_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1000000000000000,DEPOSIT,0,9223372036854775807,100,1633036860000,FAILURE,\"Record number 1\"
+1000000000000001,TRANSFER,9223372036854775807,9223372036854775807,200,1633036920000,PENDING,\"Record number 2\"
+"
+    );
+    let mut input_buffer = Cursor::new(source_data);
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let mut assert_data: Vec<u8> = vec![];
+
+    let record1_desc = String::from("Record number 1");
+    assert_data.extend_from_slice(BIN_RECORD_HEADER);
+    assert_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    assert_data.extend_from_slice(&0u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&100u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    assert_data
+      .extend_from_slice(&((record1_desc.len() + 2) as u32).to_be_bytes()[..]);
+    assert_data.extend_from_slice("\"".as_bytes());
+    assert_data.extend_from_slice(record1_desc.as_bytes());
+    assert_data.extend_from_slice("\"".as_bytes());
+
+    let record2_desc = String::from("Record number 2");
+    assert_data.extend_from_slice(BIN_RECORD_HEADER);
+    assert_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1000000000000001u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(TxType::Transfer as u8).to_be_bytes()[..]);
+    assert_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&200u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1633036920000u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(Status::Pending as u8).to_be_bytes()[..]);
+    assert_data
+      .extend_from_slice(&((record2_desc.len() + 2) as u32).to_be_bytes()[..]);
+    assert_data.extend_from_slice("\"".as_bytes());
+    assert_data.extend_from_slice(record2_desc.as_bytes());
+    assert_data.extend_from_slice("\"".as_bytes());
+
+    let input_format = DataFormat::Csv;
+    let output_format = DataFormat::Bin;
+
+    let result = convert(
+      [source(&mut input_buffer, input_format)],
+      &mut output_buffer,
+      &ConvertOptions::new(output_format),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output_buffer, assert_data);
+  }
+
+  #[test]
+  fn test_convert_bin_to_txt() {
+    let mut input_data: Vec<u8> = vec![];
+
+    let record1_desc = String::from("Record number 1");
+    input_data.extend_from_slice(BIN_RECORD_HEADER);
+    input_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    input_data.extend_from_slice(&0u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&100u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    input_data
+      .extend_from_slice(&((record1_desc.len() + 2) as u32).to_be_bytes()[..]);
+    input_data.extend_from_slice("\"".as_bytes());
+    input_data.extend_from_slice(record1_desc.as_bytes());
+    input_data.extend_from_slice("\"".as_bytes());
+
+    let record2_desc = String::from("Record number 2");
+    input_data.extend_from_slice(BIN_RECORD_HEADER);
+    input_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1000000000000001u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(TxType::Transfer as u8).to_be_bytes()[..]);
+    input_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&200u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1633036920000u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(Status::Pending as u8).to_be_bytes()[..]);
+    input_data
+      .extend_from_slice(&((record2_desc.len() + 2) as u32).to_be_bytes()[..]);
+    input_data.extend_from_slice("\"".as_bytes());
+    input_data.extend_from_slice(record2_desc.as_bytes());
+    input_data.extend_from_slice("\"".as_bytes());
+
+    let mut input_buffer = Cursor::new(input_data);
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let assert_data = String::from(
+      "# Record 1 (DEPOSIT)
+TX_ID: 1000000000000000
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 9223372036854775807
+AMOUNT: 100
+TIMESTAMP: 1633036860000
+STATUS: FAILURE
+DESCRIPTION: \"Record number 1\"
+
+# Record 2 (TRANSFER)
+TX_ID: 1000000000000001
+TX_TYPE: TRANSFER
+FROM_USER_ID: 9223372036854775807
+TO_USER_ID: 9223372036854775807
+AMOUNT: 200
+TIMESTAMP: 1633036920000
+STATUS: PENDING
+DESCRIPTION: \"Record number 2\"
+
+",
+    );
+
+    let input_format = DataFormat::Bin;
+    let output_format = DataFormat::Txt;
+
+    let result = convert(
+      [source(&mut input_buffer, input_format)],
+      &mut output_buffer,
+      &ConvertOptions::new(output_format),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output_buffer, assert_data.as_bytes());
+  }
+
+  #[test]
+  fn test_convert_txt_to_bin() {
+    let source_data = String::from(
+      "# Record 1 (DEPOSIT)
+TX_TYPE: DEPOSIT
+TO_USER_ID: 9223372036854775807
+FROM_USER_ID: 0
+TIMESTAMP: 1633036860000
+DESCRIPTION: \"Record number 1\"
+TX_ID: 1000000000000000
+AMOUNT: 100
+STATUS: FAILURE
+
+# Record 2 (TRANSFER)
+DESCRIPTION: \"Record number 2\"
+TIMESTAMP: 1633036920000
+STATUS: PENDING
+AMOUNT: 200
+TX_ID: 1000000000000001
+TX_TYPE: TRANSFER
+FROM_USER_ID: 9223372036854775807
+TO_USER_ID: 9223372036854775807
+
+",
+    );
+    let mut input_buffer = Cursor::new(source_data);
+
+    let mut assert_data: Vec<u8> = vec![];
+
+    let record1_desc = String::from("Record number 1");
+    assert_data.extend_from_slice(BIN_RECORD_HEADER);
+    assert_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    assert_data.extend_from_slice(&0u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&100u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    assert_data
+      .extend_from_slice(&((record1_desc.len() + 2) as u32).to_be_bytes()[..]);
+    assert_data.extend_from_slice("\"".as_bytes());
+    assert_data.extend_from_slice(record1_desc.as_bytes());
+    assert_data.extend_from_slice("\"".as_bytes());
+
+    let record2_desc = String::from("Record number 2");
+    assert_data.extend_from_slice(BIN_RECORD_HEADER);
+    assert_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1000000000000001u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(TxType::Transfer as u8).to_be_bytes()[..]);
+    assert_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&200u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&1633036920000u64.to_be_bytes()[..]);
+    assert_data.extend_from_slice(&(Status::Pending as u8).to_be_bytes()[..]);
+    assert_data
+      .extend_from_slice(&((record2_desc.len() + 2) as u32).to_be_bytes()[..]);
+    assert_data.extend_from_slice("\"".as_bytes());
+    assert_data.extend_from_slice(record2_desc.as_bytes());
+    assert_data.extend_from_slice("\"".as_bytes());
+
+    let mut output_buffer: Vec<u8> = vec![];
+    let input_format = DataFormat::Txt;
+    let output_format = DataFormat::Bin;
+
+    let result = convert(
+      [source(&mut input_buffer, input_format)],
+      &mut output_buffer,
+      &ConvertOptions::new(output_format),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output_buffer, assert_data);
+  }
+
+  #[test]
+  fn test_convert_bin_to_csv() {
+    let mut input_data: Vec<u8> = vec![];
+
+    let record1_desc = String::from("Record number 1");
+    input_data.extend_from_slice(BIN_RECORD_HEADER);
+    input_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1000000000000000u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(TxType::Deposit as u8).to_be_bytes()[..]);
+    input_data.extend_from_slice(&0u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&100u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1633036860000u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(Status::Failure as u8).to_be_bytes()[..]);
+    input_data
+      .extend_from_slice(&((record1_desc.len() + 2) as u32).to_be_bytes()[..]);
+    input_data.extend_from_slice("\"".as_bytes());
+    input_data.extend_from_slice(record1_desc.as_bytes());
+    input_data.extend_from_slice("\"".as_bytes());
+
+    let record2_desc = String::from("Record number 2");
+    input_data.extend_from_slice(BIN_RECORD_HEADER);
+    input_data.extend_from_slice(&63u32.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1000000000000001u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(TxType::Transfer as u8).to_be_bytes()[..]);
+    input_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&9223372036854775807u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&200u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&1633036920000u64.to_be_bytes()[..]);
+    input_data.extend_from_slice(&(Status::Pending as u8).to_be_bytes()[..]);
+    input_data
+      .extend_from_slice(&((record2_desc.len() + 2) as u32).to_be_bytes()[..]);
+    input_data.extend_from_slice("\"".as_bytes());
+    input_data.extend_from_slice(record2_desc.as_bytes());
+    input_data.extend_from_slice("\"".as_bytes());
+
+    let mut input_buffer = Cursor::new(input_data);
+
+    let assert_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1000000000000000,DEPOSIT,0,9223372036854775807,100,1633036860000,FAILURE,\"Record number 1\"
+1000000000000001,TRANSFER,9223372036854775807,9223372036854775807,200,1633036920000,PENDING,\"Record number 2\"
+"
+    );
+
+    let mut output_buffer: Vec<u8> = vec![];
+    let input_format = DataFormat::Bin;
+    let output_format = DataFormat::Csv;
+
+    let result = convert(
+      [source(&mut input_buffer, input_format)],
+      &mut output_buffer,
+      &ConvertOptions::new(output_format),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output_buffer, assert_data.as_bytes());
+  }
+
+  #[test]
+  fn test_convert_csv_to_txt() {
+    let source_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1000000000000000,DEPOSIT,0,9223372036854775807,100,1633036860000,FAILURE,\"Record number 1\"
+1000000000000001,TRANSFER,9223372036854775807,9223372036854775807,200,1633036920000,PENDING,\"Record number 2\"
+"
+    );
+    let assert_data = String::from(
+      "# Record 1 (DEPOSIT)
+TX_ID: 1000000000000000
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 9223372036854775807
+AMOUNT: 100
+TIMESTAMP: 1633036860000
+STATUS: FAILURE
+DESCRIPTION: \"Record number 1\"
+
+# Record 2 (TRANSFER)
+TX_ID: 1000000000000001
+TX_TYPE: TRANSFER
+FROM_USER_ID: 9223372036854775807
+TO_USER_ID: 9223372036854775807
+AMOUNT: 200
+TIMESTAMP: 1633036920000
+STATUS: PENDING
+DESCRIPTION: \"Record number 2\"
+
+",
+    );
+
+    let mut input_buffer: Cursor<String> = Cursor::new(source_data.clone());
+    let mut output_buffer: Vec<u8> = vec![];
+    let input_format = DataFormat::Csv;
+    let output_format = DataFormat::Txt;
+
+    let result = convert(
+      [source(&mut input_buffer, input_format)],
+      &mut output_buffer,
+      &ConvertOptions::new(output_format),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output_buffer, assert_data.as_bytes());
+  }
+
+  #[test]
+  fn test_convert_csv_to_checked_bin() {
+    let source_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1000000000000000,DEPOSIT,0,9223372036854775807,100,1633036860000,FAILURE,\"Record number 1\"
+1000000000000001,TRANSFER,9223372036854775807,9223372036854775807,200,1633036920000,PENDING,\"Record number 2\"
+"
+    );
+    let mut input_buffer = Cursor::new(source_data);
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = convert(
+      [source(&mut input_buffer, DataFormat::Csv)],
+      &mut output_buffer,
+      &ConvertOptions {
+        checksum: true,
+        ..ConvertOptions::new(DataFormat::Bin)
+      },
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(&output_buffer[..4], BIN_CHECKED_RECORD_HEADER);
+
+    let mut output_reader = Cursor::new(output_buffer);
+    let record1 = BinRecord::from_read(&mut output_reader).unwrap();
+    let record2 = BinRecord::from_read(&mut output_reader).unwrap();
+
+    assert_eq!(record1.tx_id, 1000000000000000);
+    assert_eq!(record1.status, Status::Failure);
+    assert_eq!(record2.tx_id, 1000000000000001);
+    assert_eq!(record2.tx_type, TxType::Transfer);
+    assert!(BinRecord::from_read(&mut output_reader).is_err());
+  }
+
+  #[test]
+  fn test_convert_bin_in_parallel() {
+    let source_data = bin_report((0..20).map(|tx_id| BankRecord {
+      to_user_id: tx_id,
+      ..record(tx_id)
+    }));
+
+    let mut sequential_output: Vec<u8> = vec![];
+    let mut parallel_output: Vec<u8> = vec![];
+
+    convert(
+      [source(&mut Cursor::new(&source_data), DataFormat::Bin)],
+      &mut sequential_output,
+      &ConvertOptions::new(DataFormat::Csv),
+    )
+    .unwrap();
+    convert(
+      [source(&mut Cursor::new(&source_data), DataFormat::Bin)],
+      &mut parallel_output,
+      &ConvertOptions {
+        jobs: NonZeroUsize::new(4).unwrap(),
+        ..ConvertOptions::new(DataFormat::Csv)
+      },
+    )
+    .unwrap();
+
+    assert_eq!(parallel_output, sequential_output);
+    assert_eq!(
+      String::from_utf8(parallel_output).unwrap().lines().count(),
+      21
+    );
+  }
+
+  #[test]
+  fn test_convert_damaged_bin() {
+    let source_data = fs::read("./tests/stub_files/records.bin").unwrap();
+    let first_record_size =
+      u32::from_be_bytes(source_data[4..8].try_into().unwrap()) as usize;
+    let second_size_offset = 8 + first_record_size + 4;
+
+    // Size field of the second record is damaged
+    let mut damaged_data = source_data.clone();
+    damaged_data[second_size_offset..second_size_offset + 4]
+      .copy_from_slice(&0x7fffffffu32.to_be_bytes());
+    // Last record is cut off
+    let truncated_data = &source_data[..source_data.len() - 5];
+
+    for data in [&damaged_data[..], truncated_data] {
+      for jobs in [1, 4] {
+        let result = convert(
+          [source(&mut Cursor::new(data), DataFormat::Bin)],
+          &mut vec![],
+          &ConvertOptions {
+            jobs: NonZeroUsize::new(jobs).unwrap(),
+            ..ConvertOptions::new(DataFormat::Csv)
+          },
+        );
+
+        assert!(result.is_err(), "{jobs} jobs");
+      }
+    }
+  }
+
+  #[test]
+  fn test_convert_csv_to_json_and_back() {
+    let source_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1000000000000000,DEPOSIT,0,9223372036854775807,100,1633036860000,FAILURE,\"Record number 1\"
+1000000000000001,TRANSFER,9223372036854775807,9223372036854775807,200,1633036920000,PENDING,\"Record number 2\"
+",
+    );
+    let mut json_output: Vec<u8> = vec![];
+    let mut csv_output: Vec<u8> = vec![];
+
+    let result = convert(
+      [source(&mut Cursor::new(&source_data), DataFormat::Csv)],
+      &mut json_output,
+      &ConvertOptions::new(DataFormat::Json),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(
+      String::from_utf8(json_output.clone())
+        .unwrap()
+        .lines()
+        .next(),
+      Some(
+        r#"{"TX_ID":1000000000000000,"TX_TYPE":"DEPOSIT","FROM_USER_ID":0,"TO_USER_ID":9223372036854775807,"AMOUNT":100,"TIMESTAMP":1633036860000,"STATUS":"FAILURE","DESCRIPTION":"Record number 1"}"#
+      )
+    );
+
+    let result = convert(
+      [source(&mut Cursor::new(json_output), DataFormat::Json)],
+      &mut csv_output,
+      &ConvertOptions::new(DataFormat::Csv),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(csv_output, source_data.as_bytes());
+  }
+
+  #[test]
+  fn test_convert_multiple_sources() {
+    let csv_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,FAILURE,\"Record number 1\"
+",
+    );
+    let txt_data = String::from(
+      "# Record 2 (TRANSFER)
+TX_TYPE: TRANSFER
+TO_USER_ID: 3
+FROM_USER_ID: 2
+TIMESTAMP: 1633036920000
+DESCRIPTION: \"Record number 2\"
+TX_ID: 2
+AMOUNT: 200
+STATUS: PENDING
+",
+    );
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = convert(
+      [
+        source(Cursor::new(csv_data), DataFormat::Csv),
+        source(Cursor::new(txt_data), DataFormat::Txt),
+      ],
+      &mut output_buffer,
+      &ConvertOptions::new(DataFormat::Csv),
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,FAILURE,\"Record number 1\"
+2,TRANSFER,2,3,200,1633036920000,PENDING,\"Record number 2\"
+"
+    );
+  }
+
+  #[test]
+  fn test_convert_source_error_attribution() {
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = convert(
+      [
+        source(Cursor::new(String::new()), DataFormat::Csv),
+        Err(ReportsError::UnknownFormat.for_source("records.dat")),
+      ],
+      &mut output_buffer,
+      &ConvertOptions::new(DataFormat::Csv),
+    );
+
+    assert!(matches!(
+      result,
+      Err(ReportsError::Source { ref name, .. }) if name == "records.dat"
+    ));
+  }
+
+  #[test]
+  fn test_convert_with_filter() {
+    let source_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,FAILURE,\"Record number 1\"
+2,TRANSFER,2,3,2000,1633036920000,FAILURE,\"Record number 2\"
+3,TRANSFER,3,2,3000,1633036980000,SUCCESS,\"Record number 3\"
+",
+    );
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = convert(
+      [source(Cursor::new(source_data), DataFormat::Csv)],
+      &mut output_buffer,
+      &ConvertOptions {
+        filter: Filter::from_str("status == FAILURE && amount > 1000").unwrap(),
+        ..ConvertOptions::new(DataFormat::Csv)
+      },
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+2,TRANSFER,2,3,2000,1633036920000,FAILURE,\"Record number 2\"
+"
+    );
+  }
+
+  #[test]
+  fn test_convert_with_transforms() {
+    let source_data = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,FAILURE,\"Card 4111-1111\"
+2,TRANSFER,2,3,2000,1633036920000,FAILURE,\"Record number 2\"
+",
+    );
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = convert(
+      [source(Cursor::new(source_data), DataFormat::Csv)],
+      &mut output_buffer,
+      &ConvertOptions {
+        filter: Filter::from_str("amount > 1000").unwrap(),
+        transforms: vec![
+          Transform::redact_description(r"\d+", "*").unwrap(),
+          Transform::ShiftTimestamp(-60000),
+          Transform::scale_amount("0.01").unwrap(),
+        ],
+        ..ConvertOptions::new(DataFormat::Csv)
+      },
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+2,TRANSFER,2,3,20,1633036860000,FAILURE,\"Record number *\"
+"
+    );
+  }
+
+  #[test]
+  fn test_transform_args() {
+    let args = parse_args(&[
+      "convert",
+      "--input",
+      "-",
+      "--output-format",
+      "csv",
+      "--pseudonymize",
+      "user-ids",
+      "--pseudonymize",
+      "to-user-id",
+      "--pseudonymize-key",
+      "key",
+      "--shift-timestamp",
+      "-1000",
+    ])
+    .unwrap();
+    let transforms = args.transform.into_transforms().unwrap();
+
+    assert_eq!(transforms.len(), 3);
+    assert!(matches!(transforms[2], Transform::ShiftTimestamp(-1000)));
+    assert!(
+      parse_args(&[
+        "convert",
+        "--input",
+        "-",
+        "--output-format",
+        "csv",
+        "--pseudonymize",
+        "user-ids",
+      ])
+      .is_err()
+    );
+  }
+
+  #[test]
+  fn test_convert_sorted_and_deduplicated() {
+    let first_source = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+3,TRANSFER,3,2,3000,1633036980000,SUCCESS,\"Record number 3\"
+1,DEPOSIT,0,2,100,1633036920000,FAILURE,\"Record number 1\"
+",
+    );
+    let second_source = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+2,TRANSFER,2,3,2000,1633036920000,FAILURE,\"Record number 2\"
+1,DEPOSIT,0,2,100,1633036920000,FAILURE,\"Record number 1\"
+3,TRANSFER,3,2,3500,1633036980000,SUCCESS,\"Record number 3\"
+",
+    );
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let report = convert(
+      [
+        source(Cursor::new(first_source), DataFormat::Csv),
+        source(Cursor::new(second_source), DataFormat::Csv),
+      ],
+      &mut output_buffer,
+      &ConvertOptions {
+        sort_by: vec![Field::Timestamp, Field::TxId],
+        sort_buffer: NonZeroUsize::MIN,
+        dedupe_by: Some(DedupeKey::TxId),
+        ..ConvertOptions::new(DataFormat::Csv)
+      },
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036920000,FAILURE,\"Record number 1\"
+2,TRANSFER,2,3,2000,1633036920000,FAILURE,\"Record number 2\"
+3,TRANSFER,3,2,3000,1633036980000,SUCCESS,\"Record number 3\"
+"
+    );
+    assert_eq!(report.removed, 2);
+    assert_eq!(report.conflicts.into_iter().collect::<Vec<_>>(), vec![3]);
+  }
+
+  #[test]
+  fn test_convert_sqlite_round_trip() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("records.csv");
+    let database = dir.path().join("records.sqlite");
+    let records =
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+2,TRANSFER,2,3,40,1633046400000,FAILURE,\"Record number 2\"
+3,WITHDRAWAL,3,0,50,1633046520000,FAILURE,\"Record number 3\"
+";
+    fs::write(&input, records)?;
+
+    assert!(
+      parse_args(&["convert", "-i", "-", "--output-format", "sqlite"]).is_err()
+    );
+    let args = [
+      "convert",
+      "-i",
+      input.to_str().unwrap(),
+      "--output-format",
+      "sqlite",
+      "-o",
+      database.to_str().unwrap(),
+    ];
+    // Records of the table are replaced the same way as files are
+    run(parse_args(&args).unwrap())?;
+    run(parse_args(&args).unwrap())?;
+
+    let convert_database =
+      |query: Option<&str>| -> Result<String, ReportsError> {
+        let input = InputSource::File(database.clone());
+        let source = match query {
+          Some(query) => Source::open_with(
+            &input,
+            None,
+            &SourceOptions {
+              sqlite_query: query.to_string(),
+              ..SourceOptions::default()
+            },
+          )?,
+          None => Source::open(&input, None)?,
+        };
+        let mut output_buffer: Vec<u8> = vec![];
+        convert(
+          [Ok(source)],
+          &mut output_buffer,
+          &ConvertOptions::new(DataFormat::Csv),
+        )?;
+
+        Ok(String::from_utf8(output_buffer).unwrap())
+      };
+
+    assert_eq!(convert_database(None)?, records);
+    assert_eq!(
+      convert_database(Some(
+        "SELECT * FROM transactions WHERE status = 'FAILURE' ORDER BY amount"
+      ))?,
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+2,TRANSFER,2,3,40,1633046400000,FAILURE,\"Record number 2\"
+3,WITHDRAWAL,3,0,50,1633046520000,FAILURE,\"Record number 3\"
+"
+    );
+
+    run(parse_args(&[&args[..], &["--sqlite-append"]].concat()).unwrap())?;
+    assert_eq!(
+      convert_database(None)?.lines().count(),
+      records.lines().count() * 2 - 1
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_convert_columnar_round_trip() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("records.csv");
+    let records =
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+2,TRANSFER,2,3,40,1633046400000,FAILURE,\"Record number 2\"
+3,WITHDRAWAL,3,0,50,1633046520000,PENDING,\"Record number 3\"
+";
+    fs::write(&input, records)?;
+
+    // Formats of files without known extensions are detected by content
+    for (format, file_name) in
+      [("arrow", "records.arrow"), ("parquet", "records.data")]
+    {
+      let output = dir.path().join(file_name);
+      run(
+        parse_args(&[
+          "convert",
+          "-i",
+          input.to_str().unwrap(),
+          "--output-format",
+          format,
+          "-o",
+          output.to_str().unwrap(),
+        ])
+        .unwrap(),
+      )?;
+
+      let mut output_buffer: Vec<u8> = vec![];
+      convert(
+        [Source::open(&InputSource::File(output), None)],
+        &mut output_buffer,
+        &ConvertOptions::new(DataFormat::Csv),
+      )?;
+
+      assert_eq!(String::from_utf8(output_buffer).unwrap(), records);
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_convert_for_user() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("records.csv");
+    fs::write(
+      &input,
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+2,TRANSFER,2,3,40,1633046400000,SUCCESS,\"Record number 2\"
+3,WITHDRAWAL,3,0,50,1633046520000,PENDING,\"Record number 3\"
+",
+    )?;
+
+    assert!(
+      parse_args(&[
+        "convert",
+        "-i",
+        input.to_str().unwrap(),
+        "--output-format",
+        "qif"
+      ])
+      .is_err()
+    );
+
+    let output = dir.path().join("records.qif");
+    run(
+      parse_args(&[
+        "convert",
+        "-i",
+        input.to_str().unwrap(),
+        "--output-format",
+        "qif",
+        "--for-user",
+        "3",
+        "-o",
+        output.to_str().unwrap(),
+      ])
+      .unwrap(),
+    )?;
+    assert_eq!(
+      fs::read_to_string(&output)?,
+      "!Type:Bank
+D10/01/2021
+T0.40
+N2
+PUser 2
+MRecord number 2
+C*
+^
+D10/01/2021
+T-0.50
+N3
+PYPBank
+MRecord number 3
+^
+"
+    );
+
+    let output = dir.path().join("records.ofx");
+    run(
+      parse_args(&[
+        "convert",
+        "-i",
+        input.to_str().unwrap(),
+        "--output-format",
+        "ofx-xml",
+        "--for-user",
+        "2",
+        "-o",
+        output.to_str().unwrap(),
+      ])
+      .unwrap(),
+    )?;
+    let statement = fs::read_to_string(&output)?;
+    assert!(statement.starts_with("<?xml"));
+    assert!(statement.contains("<TRNAMT>1.00</TRNAMT>"));
+    assert!(statement.contains("<TRNAMT>-0.40</TRNAMT>"));
+    assert!(!statement.contains("<FITID>3</FITID>"));
+    assert!(statement.contains("<BALAMT>0.60</BALAMT>"));
+
+    // Statements can't be read back
+    assert!(Source::open(&InputSource::File(output), None).is_err());
+
+    Ok(())
+  }
+}
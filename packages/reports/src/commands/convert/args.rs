@@ -0,0 +1,298 @@
+use crate::args::{CompressFormat, InputSources, Mt940Args, input_validation};
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use clap::{ArgGroup, Args, ValueEnum};
+use parser::dedupe::DedupeKey;
+use parser::errors::{FilterError, TransformError};
+use parser::filter::{CmpOp, Field, Filter, Value};
+use parser::sort::DEFAULT_RUN_CAPACITY;
+use parser::sqlite::SQLITE_TABLE;
+use parser::transform::{Transform, UserField};
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum PseudonymizeField {
+  FromUserId,
+  ToUserId,
+  /// Both sender and receiver ids
+  UserIds,
+}
+
+impl PseudonymizeField {
+  fn user_fields(self) -> &'static [UserField] {
+    match self {
+      Self::FromUserId => &[UserField::FromUserId],
+      Self::ToUserId => &[UserField::ToUserId],
+      Self::UserIds => &[UserField::FromUserId, UserField::ToUserId],
+    }
+  }
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum DedupeBy {
+  /// Records with the same transaction id, the first one is kept
+  #[value(name = "tx_id", alias = "tx-id")]
+  TxId,
+  /// Records with the same contents
+  Full,
+}
+
+impl From<DedupeBy> for DedupeKey {
+  fn from(dedupe_by: DedupeBy) -> Self {
+    match dedupe_by {
+      DedupeBy::TxId => DedupeKey::TxId,
+      DedupeBy::Full => DedupeKey::Full,
+    }
+  }
+}
+
+pub const DEFAULT_REDACTION: &str = "[REDACTED]";
+
+#[derive(Debug, Args)]
+pub struct ConvertArgs {
+  /// Report files, directories or glob patterns, `-` reads stdin
+  #[arg(short = 'i', long, value_name = "File path", required = true, num_args = 1.., value_parser = input_validation)]
+  pub input: Vec<InputSources>,
+  /// Format of all input reports, detected for every file if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub output_format: DataFormat,
+  /// Append CRC32C checksum to every binary output record
+  #[arg(long)]
+  pub checksum: bool,
+  /// Number of threads used for parsing binary and csv input
+  #[arg(short = 'j', long, value_name = "Threads", default_value = "1")]
+  pub jobs: NonZeroUsize,
+  /// Compress output with selected format
+  #[arg(long, value_enum, value_name = "Compression Format")]
+  pub compress: Option<CompressFormat>,
+  /// Write the report to the file instead of stdout, required for SQLite
+  /// output
+  #[arg(
+    short = 'o',
+    long,
+    value_name = "File path",
+    required_if_eq("output_format", "sqlite")
+  )]
+  pub output: Option<PathBuf>,
+  /// Convert records sent or received by the user, OFX and QIF statements
+  /// are rendered from their perspective
+  #[arg(
+    long,
+    value_name = "User Id",
+    required_if_eq_any([
+      ("output_format", "ofx"),
+      ("output_format", "ofx-xml"),
+      ("output_format", "qif"),
+    ])
+  )]
+  pub for_user: Option<u64>,
+  /// Table of SQLite databases records are written to and read from
+  #[arg(long, value_name = "Table", default_value = SQLITE_TABLE)]
+  pub sqlite_table: String,
+  /// Append records to the SQLite output table instead of replacing its
+  /// records
+  #[arg(long)]
+  pub sqlite_append: bool,
+  /// Query SQLite input records are read with instead of the table, it has
+  /// to return the table columns, e.g. `SELECT * FROM transactions WHERE
+  /// status = 'FAILURE'`
+  #[arg(long, value_name = "SQL")]
+  pub sqlite_query: Option<String>,
+  #[command(flatten)]
+  pub mt940: Mt940Args,
+  /// Sort records by comma separated fields, e.g. `timestamp,tx_id`
+  #[arg(long, value_name = "Fields", value_delimiter = ',', value_parser = Field::from_str)]
+  pub sort_by: Vec<Field>,
+  /// Number of records sorted in memory, larger inputs are sorted in parts
+  /// stored in temporary files
+  #[arg(long, value_name = "Records", default_value_t = DEFAULT_RUN_CAPACITY, requires = "sort_by")]
+  pub sort_buffer: NonZeroUsize,
+  /// Remove duplicate records, number of removed records and transaction ids
+  /// of duplicates with conflicting contents are reported to stderr
+  #[arg(long, value_enum, value_name = "Key")]
+  pub dedupe_by: Option<DedupeBy>,
+  #[command(flatten)]
+  pub filter: FilterArgs,
+  #[command(flatten)]
+  pub transform: TransformArgs,
+}
+
+/// Record filters, all of them have to match for a record to be converted
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Filters")]
+pub struct FilterArgs {
+  /// Filter expression, e.g. `status == FAILURE && amount > 1000`
+  #[arg(long, value_name = "Expression", value_parser = Filter::from_str)]
+  pub filter: Option<Filter>,
+  /// Keep records of the transaction type
+  #[arg(long, value_name = "Tx Type", value_parser = tx_type_filter)]
+  pub tx_type: Option<Filter>,
+  /// Keep records with the status
+  #[arg(long, value_name = "Status", value_parser = status_filter)]
+  pub status: Option<Filter>,
+  /// Keep records sent or received by the user
+  #[arg(long, value_name = "User Id")]
+  pub user_id: Option<u64>,
+  #[arg(long, value_name = "User Id")]
+  pub from_user_id: Option<u64>,
+  #[arg(long, value_name = "User Id")]
+  pub to_user_id: Option<u64>,
+  #[arg(long, value_name = "Amount")]
+  pub min_amount: Option<u64>,
+  #[arg(long, value_name = "Amount")]
+  pub max_amount: Option<u64>,
+  /// Keep records with timestamp at or after the value, in milliseconds
+  #[arg(long, value_name = "Timestamp")]
+  pub since: Option<u64>,
+  /// Keep records with timestamp at or before the value, in milliseconds
+  #[arg(long, value_name = "Timestamp")]
+  pub until: Option<u64>,
+  #[arg(long, value_name = "Text", value_parser = description_contains_filter)]
+  pub description_contains: Option<Filter>,
+  /// Keep records with description matching the regular expression
+  #[arg(long, value_name = "Regex", value_parser = Filter::description_matches)]
+  pub description_regex: Option<Filter>,
+}
+
+impl FilterArgs {
+  /// Combines all set filters into a single one
+  pub fn into_filter(self) -> Filter {
+    let compare = |field: Field, op: CmpOp, value: Option<u64>| {
+      value.map(|value| Filter::Compare {
+        field,
+        op,
+        value: Value::Number(value),
+      })
+    };
+    [
+      self.filter,
+      self.tx_type,
+      self.status,
+      self.user_id.map(user_filter),
+      compare(Field::FromUserId, CmpOp::Eq, self.from_user_id),
+      compare(Field::ToUserId, CmpOp::Eq, self.to_user_id),
+      compare(Field::Amount, CmpOp::Ge, self.min_amount),
+      compare(Field::Amount, CmpOp::Le, self.max_amount),
+      compare(Field::Timestamp, CmpOp::Ge, self.since),
+      compare(Field::Timestamp, CmpOp::Le, self.until),
+      self.description_contains,
+      self.description_regex,
+    ]
+    .into_iter()
+    .flatten()
+    .fold(Filter::default(), Filter::and)
+  }
+}
+
+/// Keeps records sent or received by the user
+pub fn user_filter(user_id: u64) -> Filter {
+  Filter::Any(vec![
+    Filter::Compare {
+      field: Field::FromUserId,
+      op: CmpOp::Eq,
+      value: Value::Number(user_id),
+    },
+    Filter::Compare {
+      field: Field::ToUserId,
+      op: CmpOp::Eq,
+      value: Value::Number(user_id),
+    },
+  ])
+}
+
+/// Record transformations, applied to records left after filtering
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Transformations")]
+#[command(group = ArgGroup::new("key_source").args(["pseudonymize_key", "pseudonymize_key_file"]))]
+pub struct TransformArgs {
+  /// Replace user ids with pseudonyms keyed by `--pseudonymize-key`, the same
+  /// id is always replaced with the same pseudonym for a key
+  #[arg(long, value_enum, value_name = "Field", requires = "key_source")]
+  pub pseudonymize: Vec<PseudonymizeField>,
+  /// Secret key for user id pseudonyms
+  #[arg(long, value_name = "Key")]
+  pub pseudonymize_key: Option<String>,
+  /// File with the secret key for user id pseudonyms
+  #[arg(long, value_name = "File path")]
+  pub pseudonymize_key_file: Option<PathBuf>,
+  /// Replace description parts matching the regular expression
+  #[arg(long, value_name = "Regex", value_parser = redaction_pattern)]
+  pub redact_description: Vec<Transform>,
+  /// Text redacted description parts are replaced with
+  #[arg(long, value_name = "Text", default_value = DEFAULT_REDACTION)]
+  pub redaction: String,
+  /// Shift timestamps by milliseconds, negative values shift back
+  #[arg(long, value_name = "Milliseconds", allow_negative_numbers = true)]
+  pub shift_timestamp: Option<i64>,
+  /// Multiply amounts by the decimal factor, e.g. `0.01`
+  #[arg(long, value_name = "Factor", value_parser = Transform::scale_amount)]
+  pub scale_amount: Option<Transform>,
+}
+
+impl TransformArgs {
+  /// Builds transformations in the order they are applied: pseudonymisation,
+  /// description redaction, timestamp shift and amount scaling
+  pub fn into_transforms(self) -> Result<Vec<Transform>, ReportsError> {
+    let mut transforms = vec![];
+
+    if !self.pseudonymize.is_empty() {
+      let key = match (self.pseudonymize_key, self.pseudonymize_key_file) {
+        (Some(key), _) => key.into_bytes(),
+        (None, Some(path)) => fs::read(path)?,
+        (None, None) => unreachable!("Key is required by the argument group"),
+      };
+      let mut fields: Vec<UserField> = vec![];
+      for field in self
+        .pseudonymize
+        .iter()
+        .flat_map(|field| field.user_fields())
+      {
+        if !fields.contains(field) {
+          fields.push(*field);
+        }
+      }
+      transforms.extend(
+        fields
+          .into_iter()
+          .map(|field| Transform::pseudonymize(field, key.clone())),
+      );
+    }
+
+    transforms.extend(self.redact_description.into_iter().map(|transform| {
+      match transform {
+        Transform::RedactDescription { pattern, .. } => {
+          Transform::RedactDescription {
+            pattern,
+            replacement: self.redaction.clone(),
+          }
+        }
+        transform => transform,
+      }
+    }));
+    transforms.extend(self.shift_timestamp.map(Transform::ShiftTimestamp));
+    transforms.extend(self.scale_amount);
+
+    Ok(transforms)
+  }
+}
+
+fn redaction_pattern(pattern: &str) -> Result<Transform, TransformError> {
+  Transform::redact_description(pattern, DEFAULT_REDACTION)
+}
+
+fn tx_type_filter(tx_type: &str) -> Result<Filter, FilterError> {
+  Filter::compare(Field::TxType, CmpOp::Eq, tx_type)
+}
+
+fn status_filter(status: &str) -> Result<Filter, FilterError> {
+  Filter::compare(Field::Status, CmpOp::Eq, status)
+}
+
+fn description_contains_filter(text: &str) -> Result<Filter, FilterError> {
+  Ok(Filter::description_contains(text))
+}
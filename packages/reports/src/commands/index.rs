@@ -0,0 +1,343 @@
+use crate::args::bin_path_validation;
+use crate::errors::ReportsError;
+use crate::formats::{DataFormat, ReportWriter};
+use clap::{Args, Subcommand};
+use parser::errors::ParsingError;
+use parser::index::{BinIndex, IndexedBinReader};
+use parser::record::BankRecord;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, Write};
+use std::ops::Bound;
+use std::path::PathBuf;
+
+/// Extension of sidecar index files
+pub const INDEX_EXTENSION: &str = "idx";
+
+#[derive(Debug, Args)]
+pub struct IndexArgs {
+  #[command(subcommand)]
+  pub command: IndexCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum IndexCommand {
+  /// Build sidecar index of a binary report
+  Build(IndexFiles),
+  /// Check that sidecar index matches the binary report
+  Verify(IndexFiles),
+  /// Read records from a binary report using its sidecar index
+  Lookup(LookupArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct IndexFiles {
+  /// Uncompressed binary report file
+  #[arg(short = 'i', long, value_name = "File path", value_parser = bin_path_validation)]
+  pub input: PathBuf,
+  /// Index file path, defaults to the report path with `idx` extension
+  #[arg(long, value_name = "File path")]
+  pub index: Option<PathBuf>,
+}
+
+impl IndexFiles {
+  pub fn index_path(&self) -> PathBuf {
+    self
+      .index
+      .clone()
+      .unwrap_or_else(|| self.input.with_extension(INDEX_EXTENSION))
+  }
+}
+
+#[derive(Debug, Args)]
+pub struct LookupArgs {
+  #[command(flatten)]
+  pub files: IndexFiles,
+  #[arg(long, value_name = "Transaction id", conflicts_with_all = ["from_tx_id", "to_tx_id", "from_timestamp", "to_timestamp"])]
+  pub tx_id: Option<u64>,
+  #[arg(long, value_name = "Transaction id")]
+  pub from_tx_id: Option<u64>,
+  #[arg(long, value_name = "Transaction id")]
+  pub to_tx_id: Option<u64>,
+  #[arg(long, value_name = "Timestamp", conflicts_with_all = ["from_tx_id", "to_tx_id"])]
+  pub from_timestamp: Option<u64>,
+  #[arg(long, value_name = "Timestamp", conflicts_with_all = ["from_tx_id", "to_tx_id"])]
+  pub to_timestamp: Option<u64>,
+  #[arg(long, value_enum, value_name = "File Format", default_value = "txt")]
+  pub output_format: DataFormat,
+}
+
+/// Builds, verifies or reads sidecar indexes of binary reports, results are
+/// written to stdout
+pub fn run(args: IndexArgs) -> Result<(), ReportsError> {
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+
+  match args.command {
+    IndexCommand::Build(files) => {
+      let mut file_reader = BufReader::new(File::open(&files.input)?);
+      let mut index_writer = BufWriter::new(File::create(files.index_path())?);
+
+      build(&mut file_reader, &mut index_writer, &mut buf_writer)
+    }
+    IndexCommand::Verify(files) => {
+      let mut file_reader = BufReader::new(File::open(&files.input)?);
+      let mut index_reader = BufReader::new(File::open(files.index_path())?);
+
+      verify(&mut file_reader, &mut index_reader, &mut buf_writer)
+    }
+    IndexCommand::Lookup(args) => {
+      let file_reader = BufReader::new(File::open(&args.files.input)?);
+      let mut index_reader =
+        BufReader::new(File::open(args.files.index_path())?);
+
+      lookup(file_reader, &mut index_reader, &mut buf_writer, &args)
+    }
+  }
+}
+
+fn build(
+  reader: &mut impl BufRead,
+  index_writer: &mut impl Write,
+  report_writer: &mut impl Write,
+) -> Result<(), ReportsError> {
+  let index = BinIndex::build(reader)?;
+
+  index.write_to(index_writer)?;
+  index_writer.flush()?;
+
+  writeln!(report_writer, "Indexed records: {}", index.len())?;
+  report_writer.flush()?;
+
+  Ok(())
+}
+
+fn verify(
+  reader: &mut impl BufRead,
+  index_reader: &mut impl Read,
+  report_writer: &mut impl Write,
+) -> Result<(), ReportsError> {
+  let index = BinIndex::read_from(index_reader)?;
+  let verification = index.verify(reader)?;
+
+  if verification.is_valid() {
+    writeln!(
+      report_writer,
+      "Index matches the report, indexed records: {}",
+      index.len()
+    )?;
+    report_writer.flush()?;
+
+    return Ok(());
+  }
+
+  writeln!(report_writer, "Index does not match the report:")?;
+  writeln!(report_writer)?;
+
+  for entry in &verification.missing {
+    writeln!(
+      report_writer,
+      "Missing record id: {}\nOffset: {}",
+      entry.tx_id, entry.offset
+    )?;
+    writeln!(report_writer)?;
+  }
+  for entry in &verification.stale {
+    writeln!(
+      report_writer,
+      "Stale record id: {}\nOffset: {}",
+      entry.tx_id, entry.offset
+    )?;
+    writeln!(report_writer)?;
+  }
+
+  report_writer.flush()?;
+
+  Err(ReportsError::InvalidIndex)
+}
+
+fn lookup<R: BufRead + Seek>(
+  reader: R,
+  index_reader: &mut impl Read,
+  writer: &mut impl Write,
+  args: &LookupArgs,
+) -> Result<(), ReportsError> {
+  let mut report_writer =
+    ReportWriter::new(args.output_format.stream_output()?, false);
+  let index = BinIndex::read_from(index_reader)?;
+  let mut indexed_reader = IndexedBinReader::new(reader, index);
+
+  let records: Box<dyn Iterator<Item = Result<BankRecord, ParsingError>>> =
+    if let Some(tx_id) = args.tx_id {
+      Box::new(indexed_reader.range_by_tx_id(tx_id..=tx_id))
+    } else if args.from_timestamp.is_some() || args.to_timestamp.is_some() {
+      Box::new(
+        indexed_reader
+          .range_by_timestamp(bounds(args.from_timestamp, args.to_timestamp)),
+      )
+    } else {
+      Box::new(
+        indexed_reader.range_by_tx_id(bounds(args.from_tx_id, args.to_tx_id)),
+      )
+    };
+
+  report_writer.write_header(writer)?;
+  for record in records {
+    report_writer.write_record(writer, record?)?;
+  }
+  report_writer.write_footer(writer)?;
+
+  writer.flush()?;
+
+  Ok(())
+}
+
+fn bounds(from: Option<u64>, to: Option<u64>) -> (Bound<u64>, Bound<u64>) {
+  (
+    from.map_or(Bound::Unbounded, Bound::Included),
+    to.map_or(Bound::Unbounded, Bound::Included),
+  )
+}
+
+#[cfg(test)]
+mod index_test {
+  use crate::commands::index::{IndexFiles, LookupArgs, build, lookup, verify};
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::test_utils::{bin_report, record};
+  use parser::record::BankRecord;
+  use std::io::Cursor;
+  use std::path::PathBuf;
+
+  /// Binary report of records with timestamps following their ids
+  fn report(tx_ids: &[u64]) -> Vec<u8> {
+    bin_report(tx_ids.iter().map(|&tx_id| BankRecord {
+      timestamp: 1633036860000 + tx_id,
+      ..record(tx_id)
+    }))
+  }
+
+  fn lookup_args() -> LookupArgs {
+    LookupArgs {
+      files: IndexFiles {
+        input: PathBuf::from("records.bin"),
+        index: None,
+      },
+      tx_id: None,
+      from_tx_id: None,
+      to_tx_id: None,
+      from_timestamp: None,
+      to_timestamp: None,
+      output_format: DataFormat::Csv,
+    }
+  }
+
+  #[test]
+  fn test_index_path() {
+    assert_eq!(
+      lookup_args().files.index_path(),
+      PathBuf::from("records.idx")
+    );
+  }
+
+  #[test]
+  fn test_build_and_verify() -> Result<(), ReportsError> {
+    let data = report(&[1, 2, 3]);
+    let mut index_buffer: Vec<u8> = vec![];
+    let mut report_buffer: Vec<u8> = vec![];
+
+    build(
+      &mut Cursor::new(data.clone()),
+      &mut index_buffer,
+      &mut report_buffer,
+    )?;
+
+    assert_eq!(report_buffer, b"Indexed records: 3\n");
+
+    let mut report_buffer: Vec<u8> = vec![];
+
+    verify(
+      &mut Cursor::new(data),
+      &mut Cursor::new(index_buffer.clone()),
+      &mut report_buffer,
+    )?;
+
+    assert_eq!(
+      report_buffer,
+      b"Index matches the report, indexed records: 3\n"
+    );
+
+    let mut report_buffer: Vec<u8> = vec![];
+    let result = verify(
+      &mut Cursor::new(report(&[1, 2, 3, 4])),
+      &mut Cursor::new(index_buffer),
+      &mut report_buffer,
+    );
+
+    assert!(matches!(result, Err(ReportsError::InvalidIndex)));
+    assert_eq!(
+      String::from_utf8(report_buffer).unwrap(),
+      format!(
+        "Index does not match the report:\n\nMissing record id: 4\nOffset: {}\n\n",
+        report(&[1, 2, 3]).len()
+      )
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_lookup() -> Result<(), ReportsError> {
+    let data = report(&[5, 1, 3, 2]);
+    let mut index_buffer: Vec<u8> = vec![];
+
+    build(
+      &mut Cursor::new(data.clone()),
+      &mut index_buffer,
+      &mut vec![],
+    )?;
+
+    let mut output_buffer: Vec<u8> = vec![];
+    let args = LookupArgs {
+      tx_id: Some(3),
+      ..lookup_args()
+    };
+
+    lookup(
+      Cursor::new(data.clone()),
+      &mut Cursor::new(index_buffer.clone()),
+      &mut output_buffer,
+      &args,
+    )?;
+
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+3,DEPOSIT,0,2,100,1633036860003,SUCCESS,\"Record number 3\"
+"
+    );
+
+    let mut output_buffer: Vec<u8> = vec![];
+    let args = LookupArgs {
+      from_timestamp: Some(1633036860002),
+      to_timestamp: Some(1633036860004),
+      output_format: DataFormat::Json,
+      ..lookup_args()
+    };
+
+    lookup(
+      Cursor::new(data),
+      &mut Cursor::new(index_buffer),
+      &mut output_buffer,
+      &args,
+    )?;
+
+    let output = String::from_utf8(output_buffer).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("\"TX_ID\":2"));
+    assert!(lines[1].contains("\"TX_ID\":3"));
+
+    Ok(())
+  }
+}
@@ -0,0 +1,141 @@
+use crate::args::{InputSources, flatten_sources, input_validation};
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use crate::sources::Source;
+use clap::Args;
+use parser::filter::Field;
+use parser::ledger::{Ledger, read_balances, write_balances};
+use parser::record::Status;
+use parser::sort::ExternalSorter;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct LedgerArgs {
+  /// Report files, directories or glob patterns, `-` reads stdin
+  #[arg(short = 'i', long, value_name = "File path", required = true, num_args = 1.., value_parser = input_validation)]
+  pub input: Vec<InputSources>,
+  /// Format of all input reports, detected for every file if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  /// `USER_ID,BALANCE` file with balances before the first transaction
+  #[arg(long, value_name = "File path")]
+  pub opening_balances: Option<PathBuf>,
+  /// Timestamp in milliseconds, later transactions are not applied
+  #[arg(long, value_name = "Timestamp")]
+  pub as_of: Option<u64>,
+}
+
+/// Replays successful transactions of all input reports in timestamp order,
+/// writes the balance sheet to stdout and ledger issues to stderr
+pub fn run(args: LedgerArgs) -> Result<(), ReportsError> {
+  let mut ledger = match &args.opening_balances {
+    Some(path) => {
+      let name = path.display().to_string();
+      let file = File::open(path)
+        .map_err(|err| ReportsError::from(err).for_source(&name))?;
+      let balances = read_balances(BufReader::new(file))
+        .map_err(|err| ReportsError::from(err).for_source(&name))?;
+      Ledger::with_opening_balances(balances)
+    }
+    None => Ledger::new(),
+  };
+
+  // Transactions of the same timestamp are applied in tx id order
+  let mut sorter = ExternalSorter::new(vec![Field::Timestamp, Field::TxId]);
+  for input in flatten_sources(args.input) {
+    let source = Source::open(&input, args.input_format)?;
+    collect(source, args.as_of, &mut sorter)?;
+  }
+
+  for record in sorter.finish()? {
+    ledger.apply(&record?);
+  }
+
+  let mut stderr = io::stderr().lock();
+  for issue in ledger.issues() {
+    writeln!(stderr, "{issue}")?;
+  }
+
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+  write_balances(&mut buf_writer, ledger.balances())?;
+  buf_writer.flush()?;
+
+  Ok(())
+}
+
+/// Pushes successful records not later than `as_of` to the sorter, other
+/// records don't change balances
+fn collect(
+  source: Source,
+  as_of: Option<u64>,
+  sorter: &mut ExternalSorter,
+) -> Result<(), ReportsError> {
+  for record in source.records() {
+    let record = record?;
+    if record.status == Status::Success
+      && as_of.is_none_or(|as_of| record.timestamp <= as_of)
+    {
+      sorter.push(record)?;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod ledger_test {
+  use crate::commands::ledger::collect;
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::test_utils::source;
+  use parser::filter::Field;
+  use parser::ledger::{Balances, Ledger, LedgerIssue};
+  use parser::sort::ExternalSorter;
+
+  // Records are out of timestamp order, withdrawal of user 2 happens after
+  // the deposit and doesn't overdraw the account
+  const SOURCE: &str =
+    "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+2,WITHDRAWAL,2,0,60,1633036870000,SUCCESS,\"Record number 2\"
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+3,TRANSFER,2,3,50,1633036880000,SUCCESS,\"Record number 3\"
+4,DEPOSIT,0,3,300,1633036890000,FAILURE,\"Record number 4\"
+5,DEPOSIT,0,3,500,1633036900000,SUCCESS,\"Record number 5\"
+";
+
+  fn replay(as_of: Option<u64>) -> Result<Ledger, ReportsError> {
+    let mut sorter = ExternalSorter::new(vec![Field::Timestamp, Field::TxId]);
+    collect(source(SOURCE, DataFormat::Csv), as_of, &mut sorter)?;
+
+    let mut ledger = Ledger::new();
+    for record in sorter.finish()? {
+      ledger.apply(&record?);
+    }
+
+    Ok(ledger)
+  }
+
+  #[test]
+  fn test_ledger_as_of() -> Result<(), ReportsError> {
+    let ledger = replay(Some(1633036890000))?;
+
+    assert_eq!(ledger.balances(), &Balances::from([(2, -10), (3, 50)]));
+    assert_eq!(
+      ledger.issues(),
+      &[LedgerIssue::Overdraft {
+        tx_id: 3,
+        user_id: 2,
+        balance: -10
+      }]
+    );
+
+    let ledger = replay(None)?;
+    assert_eq!(ledger.balance(3), 550);
+
+    Ok(())
+  }
+}
@@ -0,0 +1,302 @@
+use crate::args::{InputSources, flatten_sources, input_validation};
+use crate::errors::ReportsError;
+use crate::formats::{DataFormat, ReportWriter};
+use crate::sources::{InputSource, Source};
+use clap::{Args, ValueEnum};
+use parser::filter::Field;
+use parser::record::BankRecord;
+use parser::sort::MergeSorted;
+use std::io;
+use std::io::{BufWriter, ErrorKind, Write};
+
+/// Field all input reports are sorted by
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum MergeKey {
+  #[value(name = "tx_id", alias = "tx-id")]
+  TxId,
+  Timestamp,
+}
+
+impl From<MergeKey> for Field {
+  fn from(key: MergeKey) -> Self {
+    match key {
+      MergeKey::TxId => Field::TxId,
+      MergeKey::Timestamp => Field::Timestamp,
+    }
+  }
+}
+
+#[derive(Debug, Args)]
+pub struct MergeArgs {
+  /// Report files, directories or glob patterns sorted by the merge key,
+  /// formats may differ, `-` reads stdin
+  #[arg(short = 'i', long, value_name = "File path", required = true, num_args = 1.., value_parser = input_validation)]
+  pub input: Vec<InputSources>,
+  /// Format of all input reports, detected for every file if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub output_format: DataFormat,
+  /// Field input reports are sorted by
+  #[arg(long, value_enum, value_name = "Field", default_value = "tx_id")]
+  pub by: MergeKey,
+  /// Append CRC32C checksum to every binary output record
+  #[arg(long)]
+  pub checksum: bool,
+}
+
+type Records<'a> =
+  Box<dyn Iterator<Item = Result<BankRecord, ReportsError>> + 'a>;
+
+/// Merges sorted input reports into a single sorted report written to stdout
+pub fn run(args: MergeArgs) -> Result<(), ReportsError> {
+  let inputs = flatten_sources(args.input);
+
+  let stdin_inputs = inputs
+    .iter()
+    .filter(|input| **input == InputSource::Stdin)
+    .count();
+  if stdin_inputs > 1 {
+    return Err(ReportsError::IO(io::Error::new(
+      ErrorKind::InvalidInput,
+      "Only one report can be read from stdin",
+    )));
+  }
+
+  // All reports are read at the same time
+  let sources = inputs
+    .iter()
+    .map(|input| Source::open(input, args.input_format))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+
+  merge(
+    sources,
+    &mut buf_writer,
+    args.output_format.stream_output()?,
+    args.by,
+    args.checksum,
+  )
+}
+
+/// Merges sorted sources into a single sorted output stream, records with
+/// equal keys are written in order of sources
+fn merge<'a>(
+  sources: Vec<Source<'a>>,
+  writer: &mut impl Write,
+  output_format: DataFormat,
+  key: MergeKey,
+  checksum: bool,
+) -> Result<(), ReportsError> {
+  let mut report_writer = ReportWriter::new(output_format, checksum);
+  report_writer.write_header(writer)?;
+
+  let field = Field::from(key);
+  let mut inputs: Vec<Records<'a>> = Vec::with_capacity(sources.len());
+  for source in sources {
+    inputs.push(sorted_records(source, field));
+  }
+
+  for record in MergeSorted::new(vec![field], inputs) {
+    report_writer.write_record(writer, record?)?;
+  }
+
+  report_writer.write_footer(writer)?;
+  writer.flush()?;
+
+  Ok(())
+}
+
+/// Records of the source, reading fails on a record which can't be parsed
+/// or precedes the previous one
+fn sorted_records<'a>(source: Source<'a>, field: Field) -> Records<'a> {
+  let name = source.name.clone();
+  let mut last_key = None;
+
+  Box::new(source.records().map(move |record| {
+    let record = record?;
+    let key = field.number(&record);
+
+    if key < last_key {
+      return Err(
+        ReportsError::Unsorted {
+          tx_id: record.tx_id,
+        }
+        .for_source(&name),
+      );
+    }
+    last_key = key;
+
+    Ok(record)
+  }))
+}
+
+#[cfg(test)]
+mod merge_test {
+  use crate::commands::merge::{MergeKey, merge};
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::sources::Source;
+  use crate::test_utils::{bin_report, record};
+  use parser::record::{BankRecord, TxType};
+  use std::io::Cursor;
+
+  /// Binary report of deposits of user 1 with their timestamps
+  fn timed_report(records: &[(u64, u64)]) -> Vec<u8> {
+    bin_report(records.iter().map(|(tx_id, timestamp)| BankRecord {
+      to_user_id: 1,
+      timestamp: *timestamp,
+      ..record(*tx_id)
+    }))
+  }
+
+  #[test]
+  fn test_merge_mixed_formats() {
+    let csv_source = String::from(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+2,DEPOSIT,0,1,100,1633036920000,SUCCESS,\"Record number 2\"
+5,DEPOSIT,0,1,100,1633036860000,SUCCESS,\"Record number 5\"
+",
+    );
+    let json_source = String::from(
+      "{\"TX_ID\":3,\"TX_TYPE\":\"DEPOSIT\",\"FROM_USER_ID\":0,\"TO_USER_ID\":1,\"AMOUNT\":100,\"TIMESTAMP\":1633036990000,\"STATUS\":\"SUCCESS\",\"DESCRIPTION\":\"Record number 3\"}
+",
+    );
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = merge(
+      vec![
+        Source::new(
+          "first.bin",
+          Cursor::new(timed_report(&[(1, 1633036980000), (4, 1633036800000)])),
+          DataFormat::Bin,
+        ),
+        Source::new("second.csv", Cursor::new(csv_source), DataFormat::Csv),
+        Source::new("third.jsonl", Cursor::new(json_source), DataFormat::Json),
+      ],
+      &mut output_buffer,
+      DataFormat::Csv,
+      MergeKey::TxId,
+      false,
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,1,100,1633036980000,SUCCESS,\"Record number 1\"
+2,DEPOSIT,0,1,100,1633036920000,SUCCESS,\"Record number 2\"
+3,DEPOSIT,0,1,100,1633036990000,SUCCESS,\"Record number 3\"
+4,DEPOSIT,0,1,100,1633036800000,SUCCESS,\"Record number 4\"
+5,DEPOSIT,0,1,100,1633036860000,SUCCESS,\"Record number 5\"
+"
+    );
+  }
+
+  #[test]
+  fn test_merge_unsorted_input() {
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = merge(
+      vec![
+        Source::new(
+          "first.bin",
+          Cursor::new(timed_report(&[(1, 1633036800000), (2, 1633036920000)])),
+          DataFormat::Bin,
+        ),
+        Source::new(
+          "second.bin",
+          Cursor::new(timed_report(&[(3, 1633036860000), (4, 1633036810000)])),
+          DataFormat::Bin,
+        ),
+      ],
+      &mut output_buffer,
+      DataFormat::Csv,
+      MergeKey::Timestamp,
+      false,
+    );
+
+    assert!(matches!(
+      result,
+      Err(ReportsError::Source { name, source })
+        if name == "second.bin"
+          && matches!(*source, ReportsError::Unsorted { tx_id: 4 })
+    ));
+  }
+
+  #[test]
+  fn test_merge_parsing_error() {
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = merge(
+      vec![
+        Source::new(
+          "first.bin",
+          Cursor::new(timed_report(&[(1, 1633036800000)])),
+          DataFormat::Bin,
+        ),
+        Source::new(
+          "second.csv",
+          Cursor::new(
+            "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+2,REFUND,0,1,100,1633036920000,SUCCESS,\"Record number 2\"
+",
+          ),
+          DataFormat::Csv,
+        ),
+      ],
+      &mut output_buffer,
+      DataFormat::Csv,
+      MergeKey::Timestamp,
+      false,
+    );
+
+    assert!(matches!(
+      result,
+      Err(ReportsError::Source { name, source })
+        if name == "second.csv"
+          && matches!(*source, ReportsError::Parsing(_))
+    ));
+  }
+
+  #[test]
+  fn test_merge_mt940_balance() {
+    let withdrawal = BankRecord {
+      tx_type: TxType::Withdrawal,
+      from_user_id: 1,
+      to_user_id: 0,
+      amount: 250,
+      timestamp: 1633123200000,
+      ..record(2)
+    };
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let result = merge(
+      vec![
+        Source::new(
+          "first.bin",
+          Cursor::new(timed_report(&[(1, 1633036800000), (3, 1633036900000)])),
+          DataFormat::Bin,
+        ),
+        Source::new(
+          "second.bin",
+          Cursor::new(bin_report([withdrawal])),
+          DataFormat::Bin,
+        ),
+      ],
+      &mut output_buffer,
+      DataFormat::Mt940,
+      MergeKey::TxId,
+      false,
+    );
+
+    assert!(result.is_ok());
+    let written = String::from_utf8(output_buffer).unwrap();
+    // Statement opens with the date of the first line and closes with the net
+    // of its lines dated by the latest line
+    assert!(written.contains(":60F:C210930EUR0,00\n"));
+    assert!(written.ends_with(":62F:D211001EUR0,50\n-\n"));
+  }
+}
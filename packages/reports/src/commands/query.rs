@@ -0,0 +1,213 @@
+use crate::args::{InputSources, flatten_sources, input_validation};
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use crate::sources::Source;
+use crate::table;
+use crate::table::Align;
+use clap::{Args, ValueEnum};
+use parser::query::{Cell, Execution, Query, QueryResult};
+use std::io;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum QueryFormat {
+  /// Aligned table
+  Table,
+  Csv,
+  /// Array of objects keyed by column names
+  Json,
+}
+
+#[derive(Debug, Args)]
+pub struct QueryArgs {
+  /// SQL-like query, e.g. `SELECT tx_type, SUM(amount) FROM 'records.bin'
+  /// WHERE status = 'FAILURE' GROUP BY tx_type`
+  #[arg(value_name = "Query", value_parser = query_validation)]
+  pub query: Query,
+  /// Report files, directories or glob patterns, `-` reads stdin, replaces
+  /// the FROM clause
+  #[arg(short = 'i', long, value_name = "File path", num_args = 1.., value_parser = input_validation)]
+  pub input: Vec<InputSources>,
+  /// Format of all input reports, detected for every file if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  /// Query result output format
+  #[arg(long, value_enum, value_name = "Format", default_value = "table")]
+  pub format: QueryFormat,
+}
+
+fn query_validation(query: &str) -> Result<Query, ReportsError> {
+  Ok(Query::from_str(query)?)
+}
+
+/// Runs the query over all input reports and writes the result to stdout
+pub fn run(args: QueryArgs) -> Result<(), ReportsError> {
+  let inputs = match (args.input.is_empty(), &args.query.from) {
+    (false, _) => args.input,
+    (true, Some(from)) => vec![input_validation(from)?],
+    (true, None) => return Err(ReportsError::NoInput),
+  };
+
+  let mut execution = args.query.execution();
+  for input in flatten_sources(inputs) {
+    let source = Source::open(&input, args.input_format)?;
+    if !collect(source, &mut execution)? {
+      break;
+    }
+  }
+  let result = execution.finish();
+
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+
+  match args.format {
+    QueryFormat::Table => write_table(&mut buf_writer, &result)?,
+    QueryFormat::Csv => write_csv(&mut buf_writer, &result)?,
+    QueryFormat::Json => {
+      serde_json::to_writer_pretty(&mut buf_writer, &result)
+        .map_err(io::Error::from)?;
+      writeln!(buf_writer)?;
+    }
+  }
+
+  buf_writer.flush()?;
+
+  Ok(())
+}
+
+/// Pushes every record of the source to the query, returns `false` if the
+/// query doesn't need more records
+fn collect(
+  source: Source,
+  execution: &mut Execution,
+) -> Result<bool, ReportsError> {
+  for record in source.records() {
+    if !execution.push(&record?) {
+      return Ok(false);
+    }
+  }
+
+  Ok(true)
+}
+
+/// Texts with separators or quotes are quoted
+fn csv_cell(cell: &Cell) -> String {
+  match cell {
+    Cell::Text(text) if text.contains([',', '"', '\n', '\r']) => {
+      format!("\"{}\"", text.replace('"', "\"\""))
+    }
+    cell => cell.to_string(),
+  }
+}
+
+fn write_csv(writer: &mut impl Write, result: &QueryResult) -> io::Result<()> {
+  writeln!(writer, "{}", result.columns.join(","))?;
+
+  for row in &result.rows {
+    let cells: Vec<String> = row.iter().map(csv_cell).collect();
+    writeln!(writer, "{}", cells.join(","))?;
+  }
+
+  Ok(())
+}
+
+/// Writes the table with texts aligned left and numbers aligned right
+fn write_table(
+  writer: &mut impl Write,
+  result: &QueryResult,
+) -> io::Result<()> {
+  let align = |cell: &Cell| match cell {
+    Cell::Number(_) | Cell::Float(_) => Align::Right,
+    _ => Align::Left,
+  };
+
+  // Headers are aligned as the values of the first row
+  let header_aligns: Vec<Align> = match result.rows.first() {
+    Some(row) => row.iter().map(align).collect(),
+    None => vec![Align::Left; result.columns.len()],
+  };
+  let header = result.columns.iter().cloned().zip(header_aligns).collect();
+  let rows = result
+    .rows
+    .iter()
+    .map(|row| {
+      row
+        .iter()
+        .map(|cell| (cell.to_string(), align(cell)))
+        .collect()
+    })
+    .collect();
+
+  table::write_table(writer, header, rows)
+}
+
+#[cfg(test)]
+mod query_test {
+  use crate::commands::query::{collect, csv_cell, write_csv, write_table};
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::test_utils::source;
+  use parser::query::{Cell, Query, QueryResult};
+  use std::str::FromStr;
+
+  const SOURCE: &str =
+    "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+2,TRANSFER,2,3,40,1633046400000,FAILURE,\"Record number 2\"
+3,DEPOSIT,0,3,300,1633046460000,FAILURE,\"Record number 3\"
+4,WITHDRAWAL,3,0,50,1633046520000,FAILURE,\"Record number 4\"
+";
+
+  fn query(query: &str) -> Result<QueryResult, ReportsError> {
+    let query = Query::from_str(query)?;
+    let mut execution = query.execution();
+    collect(source(SOURCE, DataFormat::Csv), &mut execution)?;
+
+    Ok(execution.finish())
+  }
+
+  #[test]
+  fn test_query_table() -> Result<(), ReportsError> {
+    let result = query(
+      "SELECT tx_type, SUM(amount), AVG(amount) AS mean FROM 'input.csv' \
+       WHERE status = 'FAILURE' GROUP BY tx_type ORDER BY 2 DESC",
+    )?;
+    let mut output_buffer: Vec<u8> = vec![];
+
+    write_table(&mut output_buffer, &result)?;
+
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_TYPE     SUM(AMOUNT)    mean
+DEPOSIT             300  300.00
+WITHDRAWAL           50   50.00
+TRANSFER             40   40.00
+"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_query_csv() -> Result<(), ReportsError> {
+    let result = query("SELECT tx_id, description WHERE amount < 100")?;
+    let mut output_buffer: Vec<u8> = vec![];
+
+    write_csv(&mut output_buffer, &result)?;
+
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "TX_ID,DESCRIPTION
+2,Record number 2
+4,Record number 4
+"
+    );
+    assert_eq!(
+      csv_cell(&Cell::Text(String::from("Record, \"number\" 2"))),
+      "\"Record, \"\"number\"\" 2\""
+    );
+
+    Ok(())
+  }
+}
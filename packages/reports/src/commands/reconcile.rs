@@ -0,0 +1,212 @@
+use crate::args::{InputSources, flatten_sources, input_validation};
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use crate::sources::Source;
+use clap::Args;
+use parser::reconcile::{ReconcileIssue, Reconciler};
+use parser::record::Status;
+use std::io;
+use std::io::{BufWriter, Write};
+
+#[derive(Debug, Args)]
+pub struct ReconcileArgs {
+  /// Report files, directories or glob patterns in chronological order,
+  /// files of a directory are ordered by name
+  #[arg(short = 'i', long, value_name = "File path", required = true, num_args = 1.., value_parser = input_validation)]
+  pub input: Vec<InputSources>,
+  /// Format of all input reports, detected for every file if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  /// Pending transactions older than the duration are reported as stuck,
+  /// number with `s`, `m`, `h` or `d` suffix
+  #[arg(long, value_name = "Duration", default_value = "24h", value_parser = duration_validation)]
+  pub stuck_after: u64,
+  /// Timestamp in milliseconds pending durations are measured at, the latest
+  /// record timestamp if omitted
+  #[arg(long, value_name = "Timestamp")]
+  pub as_of: Option<u64>,
+}
+
+/// Duration in milliseconds
+fn duration_validation(duration: &str) -> Result<u64, ReportsError> {
+  let invalid = || ReportsError::InvalidDuration(duration.to_string());
+  let duration = duration.trim();
+  let (number, multiplier) = match duration.char_indices().last() {
+    Some((index, 's')) => (&duration[..index], 1000),
+    Some((index, 'm')) => (&duration[..index], 60 * 1000),
+    Some((index, 'h')) => (&duration[..index], 60 * 60 * 1000),
+    Some((index, 'd')) => (&duration[..index], 24 * 60 * 60 * 1000),
+    _ => return Err(invalid()),
+  };
+
+  number
+    .trim()
+    .parse::<u64>()
+    .ok()
+    .and_then(|number| number.checked_mul(multiplier))
+    .ok_or_else(invalid)
+}
+
+/// Tracks status lifecycle of every transaction across input reports and
+/// writes found issues to stdout
+pub fn run(args: ReconcileArgs) -> Result<(), ReportsError> {
+  let mut reconciler = Reconciler::new();
+  let mut names = vec![];
+
+  for input in flatten_sources(args.input) {
+    let source = Source::open(&input, args.input_format)?;
+    names.push(source.name.clone());
+    collect(source, names.len() - 1, &mut reconciler)?;
+  }
+
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+  let issues = write_issues(
+    &mut buf_writer,
+    reconciler,
+    &names,
+    args.as_of,
+    args.stuck_after,
+  )?;
+  buf_writer.flush()?;
+
+  if issues > 0 {
+    return Err(ReportsError::Unreconciled { issues });
+  }
+
+  Ok(())
+}
+
+/// Adds every record of the source to the reconciler
+fn collect(
+  source: Source,
+  report: usize,
+  reconciler: &mut Reconciler,
+) -> Result<(), ReportsError> {
+  for record in source.records() {
+    reconciler.add(report, record?);
+  }
+
+  Ok(())
+}
+
+fn issue_code(issue: &ReconcileIssue) -> &'static str {
+  match issue {
+    ReconcileIssue::Stuck { .. } => "stuck",
+    ReconcileIssue::IllegalTransition { .. } => "illegal-transition",
+    ReconcileIssue::ContentChanged { .. } => "content-changed",
+  }
+}
+
+/// Writes issues prefixed by the report name and a summary line, returns the
+/// number of issues
+fn write_issues(
+  writer: &mut impl Write,
+  reconciler: Reconciler,
+  names: &[String],
+  as_of: Option<u64>,
+  stuck_after: u64,
+) -> io::Result<usize> {
+  let (mut success, mut failure, mut pending) = (0, 0, 0);
+  for lifecycle in reconciler.transactions() {
+    match lifecycle.status() {
+      Status::Success => success += 1,
+      Status::Failure => failure += 1,
+      Status::Pending => pending += 1,
+    }
+  }
+
+  let now = as_of.unwrap_or(reconciler.latest_timestamp());
+  let issues = reconciler.finish(now, stuck_after);
+  for issue in &issues {
+    writeln!(
+      writer,
+      "{}: {}: {issue}",
+      names[issue.report()],
+      issue_code(issue)
+    )?;
+  }
+
+  writeln!(
+    writer,
+    "Reconciled {} transactions: {success} success, {failure} failure, {pending} pending, {} issues",
+    success + failure + pending,
+    issues.len()
+  )?;
+
+  Ok(issues.len())
+}
+
+#[cfg(test)]
+mod reconcile_test {
+  use crate::commands::reconcile::{
+    collect, duration_validation, write_issues,
+  };
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::sources::Source;
+  use parser::reconcile::Reconciler;
+  use std::io::Cursor;
+
+  const DAY1: &str =
+    "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,PENDING,\"Record number 1\"
+2,TRANSFER,2,3,40,1633046400000,PENDING,\"Record number 2\"
+3,WITHDRAWAL,3,0,300,1633046460000,FAILURE,\"Record number 3\"
+";
+
+  const DAY2: &str =
+    "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+3,WITHDRAWAL,3,0,350,1633046460000,SUCCESS,\"Record number 3\"
+4,DEPOSIT,0,3,500,1633133000000,PENDING,\"Record number 4\"
+";
+
+  #[test]
+  fn test_duration_validation() {
+    assert_eq!(duration_validation("90s").unwrap(), 90_000);
+    assert_eq!(duration_validation("2d").unwrap(), 172_800_000);
+    assert!(duration_validation("24").is_err());
+    assert!(duration_validation("h").is_err());
+  }
+
+  #[test]
+  fn test_reconcile() -> Result<(), ReportsError> {
+    let mut reconciler = Reconciler::new();
+    let names = vec![String::from("day1.csv"), String::from("day2.csv")];
+
+    for (report, content) in [DAY1, DAY2].into_iter().enumerate() {
+      collect(
+        Source::new(
+          names[report].clone(),
+          Cursor::new(content),
+          DataFormat::Csv,
+        ),
+        report,
+        &mut reconciler,
+      )?;
+    }
+
+    let mut output_buffer: Vec<u8> = vec![];
+
+    let issues = write_issues(
+      &mut output_buffer,
+      reconciler,
+      &names,
+      None,
+      duration_validation("12h")?,
+    )?;
+
+    assert_eq!(issues, 3);
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "day2.csv: content-changed: Transaction 3 changed AMOUNT between status updates
+day2.csv: illegal-transition: Transaction 3 status changed from FAILURE to SUCCESS
+day1.csv: stuck: Transaction 2 is pending for 1443 minutes
+Reconciled 4 transactions: 2 success, 0 failure, 2 pending, 3 issues
+"
+    );
+
+    Ok(())
+  }
+}
@@ -0,0 +1,200 @@
+use crate::args::bin_path_validation;
+use crate::errors::ReportsError;
+use clap::Args;
+use parser::parsers::{BinRecord, BinRecoveryReader, CheckedBinRecord};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct RepairArgs {
+  /// Uncompressed binary report file
+  #[arg(short = 'i', long, value_name = "File path", value_parser = bin_path_validation)]
+  pub input: PathBuf,
+  /// Append CRC32C checksum to every recovered record
+  #[arg(long)]
+  pub checksum: bool,
+}
+
+/// Writes recovered records to stdout and skipped spans to stderr
+pub fn run(args: RepairArgs) -> Result<(), ReportsError> {
+  let mut file_reader = BufReader::new(File::open(&args.input)?);
+
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+  let mut stderr = io::stderr().lock();
+
+  repair(
+    &mut file_reader,
+    &mut buf_writer,
+    &mut stderr,
+    args.checksum,
+  )
+}
+
+/// Writes every intact record to `writer` and reports skipped spans of the
+/// source to `report_writer`
+fn repair(
+  reader: &mut impl BufRead,
+  writer: &mut impl Write,
+  report_writer: &mut impl Write,
+  checksum: bool,
+) -> Result<(), ReportsError> {
+  let mut recovery_reader = BinRecoveryReader::new(reader);
+  let mut records_count = 0;
+
+  // Extension area is kept, so fields of newer format versions are not lost
+  while let Some((record, extension)) =
+    recovery_reader.read_record_extended()?
+  {
+    if checksum {
+      CheckedBinRecord(record).write_extended_to(&extension, writer)?;
+    } else {
+      BinRecord(record).write_extended_to(&extension, writer)?;
+    }
+
+    records_count += 1;
+  }
+
+  writer.flush()?;
+
+  let resync_events = recovery_reader.resync_events();
+
+  if resync_events.is_empty() {
+    writeln!(
+      report_writer,
+      "No damaged data found, recovered records: {records_count}"
+    )?;
+  } else {
+    writeln!(
+      report_writer,
+      "Damaged data was skipped at following spans:"
+    )?;
+    writeln!(report_writer)?;
+
+    for event in resync_events {
+      writeln!(
+        report_writer,
+        "Offset: {}\nSkipped bytes: {}",
+        event.offset, event.skipped
+      )?;
+      writeln!(report_writer)?;
+    }
+
+    let skipped_bytes: u64 =
+      resync_events.iter().map(|event| event.skipped).sum();
+
+    writeln!(
+      report_writer,
+      "Recovered records: {records_count}, skipped bytes: {skipped_bytes}"
+    )?;
+  }
+
+  report_writer.flush()?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod repair_test {
+  use crate::commands::repair::repair;
+  use crate::test_utils::bin_report;
+  use parser::parsers::{BIN_CHECKED_RECORD_HEADER, BinRecord};
+  use parser::record::{BankRecord, BankRecordParser, Status, TxType};
+  use std::io::Cursor;
+
+  fn records() -> Vec<BankRecord> {
+    vec![
+      BankRecord {
+        tx_id: 1000000000000000,
+        tx_type: TxType::Deposit,
+        from_user_id: 0,
+        to_user_id: 9223372036854775807,
+        amount: 100,
+        timestamp: 1633036860000,
+        status: Status::Failure,
+        description: String::from("Record number 1"),
+      },
+      BankRecord {
+        tx_id: 1000000000000001,
+        tx_type: TxType::Transfer,
+        from_user_id: 9223372036854775807,
+        to_user_id: 9223372036854775807,
+        amount: 200,
+        timestamp: 1633036920000,
+        status: Status::Pending,
+        description: String::from("Record number 2"),
+      },
+    ]
+  }
+
+  #[test]
+  fn test_repair_damaged_file() {
+    let clean_data = bin_report(records());
+    let first_record_len = clean_data.len() / 2;
+    let mut input_data: Vec<u8> = vec![];
+
+    input_data.extend_from_slice(b"Hello Kitty");
+    input_data.extend_from_slice(&clean_data[..first_record_len]);
+    // Truncated copy of the first record
+    input_data.extend_from_slice(&clean_data[..30]);
+    input_data.extend_from_slice(&clean_data[first_record_len..]);
+
+    let mut input_buffer = Cursor::new(input_data);
+    let mut output_buffer: Vec<u8> = vec![];
+    let mut report_buffer: Vec<u8> = vec![];
+
+    let result = repair(
+      &mut input_buffer,
+      &mut output_buffer,
+      &mut report_buffer,
+      false,
+    );
+
+    let assert_report = format!(
+      "Damaged data was skipped at following spans:
+
+Offset: 0
+Skipped bytes: 11
+
+Offset: {}
+Skipped bytes: 30
+
+Recovered records: 2, skipped bytes: 41
+",
+      11 + first_record_len
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(output_buffer, clean_data);
+    assert_eq!(String::from_utf8(report_buffer).unwrap(), assert_report);
+  }
+
+  #[test]
+  fn test_repair_intact_file_with_checksum() {
+    let mut input_buffer = Cursor::new(bin_report(records()));
+    let mut output_buffer: Vec<u8> = vec![];
+    let mut report_buffer: Vec<u8> = vec![];
+
+    let result = repair(
+      &mut input_buffer,
+      &mut output_buffer,
+      &mut report_buffer,
+      true,
+    );
+
+    assert!(result.is_ok());
+    assert_eq!(&output_buffer[..4], BIN_CHECKED_RECORD_HEADER);
+    assert_eq!(
+      report_buffer,
+      b"No damaged data found, recovered records: 2\n"
+    );
+
+    let mut output_reader = Cursor::new(output_buffer);
+
+    for record in records() {
+      assert_eq!(BinRecord::from_read(&mut output_reader).unwrap(), record);
+    }
+  }
+}
@@ -0,0 +1,634 @@
+use crate::args::source_validation;
+use crate::errors::ReportsError;
+use crate::formats::{DataFormat, ReportWriter};
+use crate::sources::{InputSource, Source};
+use clap::{ArgGroup, Args, ValueEnum};
+use parser::date::Date;
+use parser::parsers::Mt940Balance;
+use parser::record::BankRecord;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{BufWriter, Write};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+pub const TEMPLATE_PLACEHOLDERS: &[&str] =
+  &["{stem}", "{key}", "{index}", "{ext}"];
+
+/// Keyed parts open at a time, parts written least recently are closed
+pub const MAX_OPEN_PARTS: NonZeroUsize = NonZeroUsize::new(128).unwrap();
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum Period {
+  Day,
+  Month,
+  Year,
+}
+
+impl Period {
+  /// Calendar period of the timestamp in UTC, e.g. `2021-10` for a month
+  pub fn key(&self, timestamp: u64) -> String {
+    let date = Date::from_timestamp(timestamp);
+
+    match self {
+      Self::Day => date.to_string(),
+      Self::Month => format!("{:04}-{:02}", date.year, date.month),
+      Self::Year => format!("{:04}", date.year),
+    }
+  }
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum SplitField {
+  #[value(name = "tx_type", alias = "tx-type")]
+  TxType,
+  Status,
+}
+
+impl SplitField {
+  pub fn key(&self, record: &BankRecord) -> String {
+    match self {
+      Self::TxType => record.tx_type.to_string(),
+      Self::Status => record.status.to_string(),
+    }
+  }
+}
+
+/// Rule records are distributed to output parts by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitBy {
+  /// Parts of at most the number of records
+  Records(NonZeroUsize),
+  /// Parts of at most the number of bytes, a record larger than the limit
+  /// takes a part of its own
+  Bytes(u64),
+  Period(Period),
+  Field(SplitField),
+}
+
+#[derive(Debug, Args)]
+pub struct SplitArgs {
+  /// Report file path, `-` reads stdin
+  #[arg(short = 'i', long, value_name = "File path", value_parser = source_validation)]
+  pub input: InputSource,
+  /// Format of the input report, detected if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  /// Format of the output parts, defaults to the input format
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub output_format: Option<DataFormat>,
+  /// Output part path template, `{stem}` is the input file name without
+  /// extensions, `{key}` is the part key, e.g. `2021-10` for a month or
+  /// `DEPOSIT` for a transaction type, `{index}` is the part number and
+  /// `{ext}` is the output format extension
+  #[arg(short = 'o', long, value_name = "Template", default_value = "{stem}-{key}.{ext}", value_parser = template_validation)]
+  pub output: String,
+  /// Append CRC32C checksum to every binary output record
+  #[arg(long)]
+  pub checksum: bool,
+  #[command(flatten)]
+  pub split: SplitRuleArgs,
+}
+
+#[derive(Debug, Args)]
+#[command(group = ArgGroup::new("split_by").required(true))]
+pub struct SplitRuleArgs {
+  /// Split into parts of at most the number of records
+  #[arg(long, value_name = "Records", group = "split_by")]
+  pub records: Option<NonZeroUsize>,
+  /// Split into parts of at most the size, e.g. `512K`, `10M` or `1G`
+  #[arg(long, value_name = "Size", group = "split_by", value_parser = size_validation)]
+  pub bytes: Option<u64>,
+  /// Split by calendar period of the record timestamp in UTC
+  #[arg(long, value_enum, value_name = "Period", group = "split_by")]
+  pub period: Option<Period>,
+  /// Split by value of the record field
+  #[arg(long, value_enum, value_name = "Field", group = "split_by")]
+  pub by: Option<SplitField>,
+}
+
+impl SplitRuleArgs {
+  pub fn split_by(&self) -> SplitBy {
+    match (self.records, self.bytes, self.period, self.by) {
+      (Some(records), ..) => SplitBy::Records(records),
+      (_, Some(bytes), ..) => SplitBy::Bytes(bytes),
+      (_, _, Some(period), _) => SplitBy::Period(period),
+      (_, _, _, Some(field)) => SplitBy::Field(field),
+      _ => unreachable!("Split rule is required by the argument group"),
+    }
+  }
+}
+
+fn template_validation(template: &str) -> Result<String, ReportsError> {
+  let invalid = || ReportsError::InvalidTemplate(template.to_string());
+  let mut rest = template;
+
+  // Every placeholder has to be known
+  while let Some(start) = rest.find('{') {
+    let end = rest[start..].find('}').ok_or_else(invalid)? + start;
+    if !TEMPLATE_PLACEHOLDERS.contains(&&rest[start..=end]) {
+      return Err(invalid());
+    }
+    rest = &rest[end + 1..];
+  }
+
+  // Parts would overwrite each other without a distinct placeholder
+  if !template.contains("{key}") && !template.contains("{index}") {
+    return Err(invalid());
+  }
+
+  Ok(template.to_string())
+}
+
+fn size_validation(size: &str) -> Result<u64, ReportsError> {
+  let invalid = || ReportsError::InvalidSize(size.to_string());
+  let upper = size.trim().to_ascii_uppercase();
+  let number = upper.strip_suffix('B').unwrap_or(&upper);
+  let (number, multiplier) = match number.char_indices().last() {
+    Some((index, 'K')) => (&number[..index], 1 << 10),
+    Some((index, 'M')) => (&number[..index], 1 << 20),
+    Some((index, 'G')) => (&number[..index], 1 << 30),
+    _ => (number, 1),
+  };
+
+  number
+    .trim()
+    .parse::<u64>()
+    .ok()
+    .and_then(|number| number.checked_mul(multiplier))
+    .filter(|size| *size > 0)
+    .ok_or_else(invalid)
+}
+
+/// Split settings
+struct SplitOptions {
+  output_format: DataFormat,
+  checksum: bool,
+  split_by: SplitBy,
+  /// Output part path template
+  template: String,
+  /// Input file name without extensions
+  stem: String,
+  max_open_parts: NonZeroUsize,
+}
+
+/// Output part which records are written to
+struct Part {
+  path: PathBuf,
+  /// Writer of the open part, closed parts are reopened for appending
+  writer: Option<BufWriter<File>>,
+  /// Keeps what the footer is written with when the part is finished
+  report_writer: ReportWriter,
+  finished: bool,
+  records: usize,
+  bytes: u64,
+}
+
+/// Written part of the report
+#[derive(Debug, PartialEq)]
+struct PartSummary {
+  path: PathBuf,
+  records: usize,
+}
+
+/// Splits the input report into part files and writes their summary to
+/// stdout
+pub fn run(args: SplitArgs) -> Result<(), ReportsError> {
+  let source = Source::open(&args.input, args.input_format)?;
+  let name = source.name.clone();
+
+  let options = SplitOptions {
+    output_format: args
+      .output_format
+      .unwrap_or(source.format)
+      .stream_output()?,
+    checksum: args.checksum,
+    split_by: args.split.split_by(),
+    template: args.output,
+    stem: input_stem(&args.input),
+    max_open_parts: MAX_OPEN_PARTS,
+  };
+
+  let parts =
+    split(source.records(), &options).map_err(|err| err.for_source(&name))?;
+
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+  for part in parts {
+    writeln!(
+      buf_writer,
+      "{}: {} records",
+      part.path.display(),
+      part.records
+    )?;
+  }
+  buf_writer.flush()?;
+
+  Ok(())
+}
+
+fn input_stem(input: &InputSource) -> String {
+  let InputSource::File(path) = input else {
+    return String::from("stdin");
+  };
+
+  path
+    .file_name()
+    .map(|name| name.to_string_lossy())
+    .and_then(|name| name.split('.').next().map(str::to_string))
+    .unwrap_or_else(|| String::from("report"))
+}
+
+/// Distributes records of the report into output parts, parts are created
+/// in order of their first record
+fn split(
+  records: impl IntoIterator<Item = Result<BankRecord, ReportsError>>,
+  options: &SplitOptions,
+) -> Result<Vec<PartSummary>, ReportsError> {
+  let mut parts: Vec<Part> = vec![];
+  let mut keyed_parts: HashMap<String, usize> = HashMap::new();
+  // Open keyed parts, the least recently written first
+  let mut open_parts: VecDeque<usize> = VecDeque::new();
+  // Records are serialized before the part is selected, so its size is known
+  let mut record_buf: Vec<u8> = vec![];
+
+  for record in records {
+    let record = record?;
+    // Statement lines move the balance of the part they are written to
+    let line = Mt940Balance::of(&record);
+    let key = match options.split_by {
+      SplitBy::Period(period) => Some(period.key(record.timestamp)),
+      SplitBy::Field(field) => Some(field.key(&record)),
+      SplitBy::Records(_) | SplitBy::Bytes(_) => None,
+    };
+
+    record_buf.clear();
+    options.output_format.write_record(
+      &mut record_buf,
+      record,
+      options.checksum,
+    )?;
+
+    let part_index = match key {
+      Some(key) => {
+        let part_index = keyed_parts.get(&key).copied();
+        // A part is closed before another one is opened, so there is no
+        // limit on the number of keys
+        if part_index.is_none_or(|index| !open_parts.contains(&index))
+          && open_parts.len() >= options.max_open_parts.get()
+          && let Some(index) = open_parts.pop_front()
+        {
+          parts[index].close()?;
+        }
+
+        let part_index = match part_index {
+          Some(part_index) => part_index,
+          None => {
+            let part_index = parts.len();
+            parts.push(Part::create(&key, part_index + 1, options)?);
+            keyed_parts.insert(key, part_index);
+            part_index
+          }
+        };
+        open_parts.retain(|index| *index != part_index);
+        open_parts.push_back(part_index);
+        part_index
+      }
+      None => {
+        let next_part = match (parts.last(), options.split_by) {
+          (None, _) => true,
+          (Some(part), SplitBy::Records(records)) => {
+            part.records >= records.get()
+          }
+          (Some(part), SplitBy::Bytes(bytes)) => {
+            part.records > 0 && part.bytes + record_buf.len() as u64 > bytes
+          }
+          (Some(_), SplitBy::Period(_) | SplitBy::Field(_)) => {
+            unreachable!("Keyed parts are selected by the key")
+          }
+        };
+
+        if next_part {
+          // Only one part is open at a time
+          if let Some(part) = parts.last_mut() {
+            part.finish()?;
+          }
+          let index = parts.len() + 1;
+          parts.push(Part::create(&index.to_string(), index, options)?);
+        }
+
+        parts.len() - 1
+      }
+    };
+
+    parts[part_index].write_record(&record_buf, line)?;
+  }
+
+  let mut summaries = Vec::with_capacity(parts.len());
+  for mut part in parts {
+    part.finish()?;
+    summaries.push(PartSummary {
+      path: part.path,
+      records: part.records,
+    });
+  }
+
+  Ok(summaries)
+}
+
+impl Part {
+  fn create(
+    key: &str,
+    index: usize,
+    options: &SplitOptions,
+  ) -> Result<Self, ReportsError> {
+    let path = PathBuf::from(
+      options
+        .template
+        .replace("{key}", key)
+        .replace("{index}", &index.to_string())
+        .replace("{ext}", options.output_format.extension())
+        .replace("{stem}", &options.stem),
+    );
+
+    if let Some(parent) = path.parent()
+      && !parent.as_os_str().is_empty()
+    {
+      fs::create_dir_all(parent)?;
+    }
+
+    let mut part = Self {
+      writer: Some(BufWriter::new(File::create(&path)?)),
+      path,
+      report_writer: ReportWriter::new(options.output_format, options.checksum),
+      finished: false,
+      records: 0,
+      bytes: 0,
+    };
+
+    // Every part is a complete report with headers line
+    let mut header = vec![];
+    part.report_writer.write_header(&mut header)?;
+    part.write(&header)?;
+
+    Ok(part)
+  }
+
+  fn writer(&mut self) -> io::Result<&mut BufWriter<File>> {
+    let writer = match self.writer.take() {
+      Some(writer) => writer,
+      None => BufWriter::new(OpenOptions::new().append(true).open(&self.path)?),
+    };
+
+    Ok(self.writer.insert(writer))
+  }
+
+  fn write(&mut self, data: &[u8]) -> io::Result<()> {
+    self.writer()?.write_all(data)?;
+    self.bytes += data.len() as u64;
+
+    Ok(())
+  }
+
+  /// Writes the serialized record, `line` is the balance of its statement
+  /// line
+  fn write_record(
+    &mut self,
+    record: &[u8],
+    line: Mt940Balance,
+  ) -> io::Result<()> {
+    let mut header = vec![];
+    self.report_writer.start_record(&mut header, line)?;
+    self.write(&header)?;
+    self.write(record)?;
+    self.records += 1;
+
+    Ok(())
+  }
+
+  /// Flushes and closes the file, it's reopened by the next write
+  fn close(&mut self) -> io::Result<()> {
+    if let Some(mut writer) = self.writer.take() {
+      writer.flush()?;
+    }
+
+    Ok(())
+  }
+
+  fn finish(&mut self) -> io::Result<()> {
+    if !self.finished {
+      let mut footer = vec![];
+      self.report_writer.write_footer(&mut footer)?;
+      self.writer()?.write_all(&footer)?;
+      self.close()?;
+      self.finished = true;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod split_test {
+  use crate::commands::split::{
+    MAX_OPEN_PARTS, PartSummary, Period, SplitArgs, SplitBy, SplitField,
+    SplitOptions, split,
+  };
+  use crate::errors::ReportsError;
+  use crate::formats::{DataFormat, ReportWriter};
+  use crate::test_utils::{record, source};
+  use clap::{Args, Command, FromArgMatches};
+  use parser::parsers::BinRecord;
+  use parser::record::{BankRecord, BankRecordParser, Status};
+  use std::fs;
+  use std::io::BufReader;
+  use std::num::NonZeroUsize;
+  use std::path::Path;
+
+  const SOURCE: &str =
+    "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,FAILURE,\"Record number 1\"
+2,TRANSFER,2,3,200,1635724800000,SUCCESS,\"Record number 2\"
+3,WITHDRAWAL,3,0,300,1633046400000,SUCCESS,\"Record number 3\"
+4,DEPOSIT,0,3,400,1635724799999,PENDING,\"Record number 4\"
+5,TRANSFER,3,2,500,1633036980000,SUCCESS,\"Record number 5\"
+";
+
+  fn options(
+    dir: &Path,
+    output_format: DataFormat,
+    split_by: SplitBy,
+  ) -> SplitOptions {
+    SplitOptions {
+      output_format,
+      checksum: false,
+      split_by,
+      template: format!("{}/{{stem}}/{{key}}.{{ext}}", dir.display()),
+      stem: String::from("records"),
+      max_open_parts: MAX_OPEN_PARTS,
+    }
+  }
+
+  fn records(parts: &[PartSummary]) -> Vec<usize> {
+    parts.iter().map(|part| part.records).collect()
+  }
+
+  #[test]
+  fn test_split_by_records() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let options = options(
+      dir.path(),
+      DataFormat::Csv,
+      SplitBy::Records(NonZeroUsize::new(2).unwrap()),
+    );
+
+    let parts = split(source(SOURCE, DataFormat::Csv).records(), &options)?;
+
+    assert_eq!(records(&parts), vec![2, 2, 1]);
+    assert_eq!(parts[2].path, dir.path().join("records/3.csv"));
+    assert_eq!(
+      fs::read_to_string(&parts[2].path)?,
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+5,TRANSFER,3,2,500,1633036980000,SUCCESS,\"Record number 5\"
+"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_split_by_period() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let options =
+      options(dir.path(), DataFormat::Bin, SplitBy::Period(Period::Month));
+
+    let parts = split(source(SOURCE, DataFormat::Csv).records(), &options)?;
+    let paths: Vec<_> = parts.iter().map(|part| part.path.clone()).collect();
+
+    assert_eq!(
+      paths,
+      vec![
+        dir.path().join("records/2021-09.bin"),
+        dir.path().join("records/2021-11.bin"),
+        dir.path().join("records/2021-10.bin"),
+      ]
+    );
+    assert_eq!(records(&parts), vec![2, 1, 2]);
+
+    let mut reader = BufReader::new(fs::File::open(&parts[2].path)?);
+    let tx_ids: Vec<u64> =
+      std::iter::from_fn(|| BinRecord::from_read(&mut reader).ok())
+        .map(|record| record.tx_id)
+        .collect();
+    assert_eq!(tx_ids, vec![3, 4]);
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_split_by_field_and_bytes() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let by_status = options(
+      dir.path(),
+      DataFormat::Csv,
+      SplitBy::Field(SplitField::Status),
+    );
+
+    let parts = split(source(SOURCE, DataFormat::Csv).records(), &by_status)?;
+
+    assert_eq!(parts[1].path, dir.path().join("records/SUCCESS.csv"));
+    assert_eq!(records(&parts), vec![1, 3, 1]);
+
+    let mut record_size = vec![];
+    BinRecord(BankRecord {
+      status: Status::Failure,
+      ..record(1)
+    })
+    .write_to(&mut record_size)?;
+    let by_bytes = options(
+      dir.path(),
+      DataFormat::Bin,
+      SplitBy::Bytes(record_size.len() as u64 * 2),
+    );
+
+    let parts = split(source(SOURCE, DataFormat::Csv).records(), &by_bytes)?;
+
+    assert_eq!(records(&parts), vec![2, 2, 1]);
+    for part in parts {
+      assert!(fs::metadata(&part.path)?.len() <= record_size.len() as u64 * 2);
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_split_with_closed_parts() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let by_type = SplitOptions {
+      max_open_parts: NonZeroUsize::MIN,
+      ..options(
+        dir.path(),
+        DataFormat::Csv,
+        SplitBy::Field(SplitField::TxType),
+      )
+    };
+
+    let parts = split(source(SOURCE, DataFormat::Csv).records(), &by_type)?;
+
+    assert_eq!(records(&parts), vec![2, 2, 1]);
+    assert_eq!(
+      fs::read_to_string(&parts[0].path)?,
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,FAILURE,\"Record number 1\"
+4,DEPOSIT,0,3,400,1635724799999,PENDING,\"Record number 4\"
+"
+    );
+
+    // Footer is written once, after the last record of the part
+    let by_type = SplitOptions {
+      max_open_parts: NonZeroUsize::MIN,
+      ..options(
+        dir.path(),
+        DataFormat::Camt,
+        SplitBy::Field(SplitField::TxType),
+      )
+    };
+    let parts = split(source(SOURCE, DataFormat::Csv).records(), &by_type)?;
+    let mut footer = vec![];
+    ReportWriter::new(DataFormat::Camt, false).write_footer(&mut footer)?;
+    let footer = String::from_utf8(footer).unwrap();
+
+    for part in parts {
+      let written = fs::read_to_string(&part.path)?;
+      assert_eq!(written.matches(&footer).count(), 1);
+      assert!(written.ends_with(&footer));
+      assert_eq!(written.matches("<Ntry>").count(), part.records);
+    }
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_cli_args() {
+    let parse = |args: &[&str]| {
+      let command = SplitArgs::augment_args(Command::new("split"));
+      command
+        .try_get_matches_from(["split", "--input", "-"].iter().chain(args))
+        .and_then(|matches| SplitArgs::from_arg_matches(&matches))
+    };
+
+    let cli = parse(&["--bytes", "10M", "--output", "out/{index}.{ext}"]);
+    assert_eq!(
+      cli.unwrap().split.split_by(),
+      SplitBy::Bytes(10 * 1024 * 1024)
+    );
+    assert!(parse(&["--bytes", "10X"]).is_err());
+    assert!(parse(&["--records", "10", "--output", "{stem}.{ext}"]).is_err());
+    assert!(parse(&["--records", "10", "--output", "{month}.{ext}"]).is_err());
+    // Split rule is required and only one rule is allowed
+    assert!(parse(&[]).is_err());
+    assert!(parse(&["--records", "10", "--by", "status"]).is_err());
+  }
+}
@@ -0,0 +1,267 @@
+use crate::args::{InputSources, flatten_sources, input_validation};
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use crate::sources::Source;
+use crate::table;
+use crate::table::{Align, TableCell};
+use clap::{Args, ValueEnum};
+use parser::stats::{GroupBy, Stats, Summary};
+use std::io;
+use std::io::{BufWriter, Write};
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum StatsBy {
+  #[value(name = "tx_type", alias = "tx-type")]
+  TxType,
+  Status,
+  /// Sender and receiver, a transfer is counted for both users
+  #[value(name = "user_id", alias = "user-id")]
+  UserId,
+  /// Calendar day of the timestamp in UTC
+  Day,
+  /// Hour of the timestamp in UTC
+  Hour,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum StatsFormat {
+  /// Aligned table for every breakdown
+  Text,
+  Csv,
+  Json,
+}
+
+impl From<StatsBy> for GroupBy {
+  fn from(by: StatsBy) -> Self {
+    match by {
+      StatsBy::TxType => GroupBy::TxType,
+      StatsBy::Status => GroupBy::Status,
+      StatsBy::UserId => GroupBy::UserId,
+      StatsBy::Day => GroupBy::Day,
+      StatsBy::Hour => GroupBy::Hour,
+    }
+  }
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+  /// Report files, directories or glob patterns, `-` reads stdin
+  #[arg(short = 'i', long, value_name = "File path", required = true, num_args = 1.., value_parser = input_validation)]
+  pub input: Vec<InputSources>,
+  /// Format of all input reports, detected for every file if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  /// Comma separated record properties statistics are broken down by
+  #[arg(
+    long,
+    value_enum,
+    value_name = "Field",
+    value_delimiter = ',',
+    default_value = "tx_type,status,day"
+  )]
+  pub by: Vec<StatsBy>,
+  /// Statistics output format
+  #[arg(long, value_enum, value_name = "Format", default_value = "text")]
+  pub format: StatsFormat,
+}
+
+const CSV_HEADER: &str = "GROUP,KEY,COUNT,SUM,MIN,MAX,MEAN";
+
+/// Computes statistics of all input reports and writes them to stdout
+pub fn run(args: StatsArgs) -> Result<(), ReportsError> {
+  let by: Vec<GroupBy> = args.by.into_iter().map(GroupBy::from).collect();
+  let mut stats = Stats::new(&by);
+
+  // Records are aggregated as they are read, reports are never kept in memory
+  for input in flatten_sources(args.input) {
+    let source = Source::open(&input, args.input_format)?;
+    collect(source, &mut stats)?;
+  }
+
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+
+  match args.format {
+    StatsFormat::Text => write_text(&mut buf_writer, &stats)?,
+    StatsFormat::Csv => write_csv(&mut buf_writer, &stats)?,
+    StatsFormat::Json => {
+      serde_json::to_writer_pretty(&mut buf_writer, &stats)
+        .map_err(io::Error::from)?;
+      writeln!(buf_writer)?;
+    }
+  }
+
+  buf_writer.flush()?;
+
+  Ok(())
+}
+
+/// Adds every record of the source to the statistics
+fn collect(source: Source, stats: &mut Stats) -> Result<(), ReportsError> {
+  for record in source.records() {
+    stats.add(&record?);
+  }
+
+  Ok(())
+}
+
+/// Summary values in the order of `CSV_HEADER` columns, missing values of
+/// empty summaries are empty strings
+fn summary_columns(summary: &Summary) -> [String; 5] {
+  let optional = |value: Option<u64>| value.map(|value| value.to_string());
+
+  [
+    summary.count.to_string(),
+    summary.sum.to_string(),
+    optional(summary.min).unwrap_or_default(),
+    optional(summary.max).unwrap_or_default(),
+    summary
+      .mean()
+      .map(|mean| format!("{mean:.2}"))
+      .unwrap_or_default(),
+  ]
+}
+
+fn write_csv(writer: &mut impl Write, stats: &Stats) -> io::Result<()> {
+  writeln!(writer, "{CSV_HEADER}")?;
+  writeln!(writer, "total,,{}", summary_columns(&stats.total).join(","))?;
+
+  for breakdown in &stats.breakdowns {
+    for (key, summary) in &breakdown.groups {
+      writeln!(
+        writer,
+        "{},{key},{}",
+        breakdown.by.name(),
+        summary_columns(summary).join(",")
+      )?;
+    }
+  }
+
+  Ok(())
+}
+
+fn write_text(writer: &mut impl Write, stats: &Stats) -> io::Result<()> {
+  write_table(
+    writer,
+    "total",
+    vec![(String::from("all"), summary_columns(&stats.total))],
+  )?;
+
+  for breakdown in &stats.breakdowns {
+    writeln!(writer)?;
+    write_table(
+      writer,
+      breakdown.by.name(),
+      breakdown
+        .groups
+        .iter()
+        .map(|(key, summary)| (key.to_string(), summary_columns(summary)))
+        .collect(),
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Writes the table with keys aligned left and numbers aligned right
+fn write_table(
+  writer: &mut impl Write,
+  title: &str,
+  rows: Vec<(String, [String; 5])>,
+) -> io::Result<()> {
+  let row = |key: String, columns: Vec<String>| -> Vec<TableCell> {
+    std::iter::once((key, Align::Left))
+      .chain(columns.into_iter().map(|column| (column, Align::Right)))
+      .collect()
+  };
+  let mut headers = CSV_HEADER.split(',').skip(1).map(String::from);
+  let key_header = headers.next().unwrap_or_default();
+
+  writeln!(writer, "{title}")?;
+  table::write_table(
+    writer,
+    row(key_header, headers.collect()),
+    rows
+      .into_iter()
+      .map(|(key, columns)| row(key, columns.into()))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod stats_test {
+  use crate::commands::stats::{collect, write_csv, write_text};
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::test_utils::source;
+  use parser::stats::{GroupBy, Stats};
+
+  const SOURCE: &str =
+    "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+2,TRANSFER,2,3,40,1633046400000,FAILURE,\"Record number 2\"
+3,DEPOSIT,0,3,300,1633046460000,SUCCESS,\"Record number 3\"
+";
+
+  fn stats(by: &[GroupBy]) -> Result<Stats, ReportsError> {
+    let mut stats = Stats::new(by);
+    collect(source(SOURCE, DataFormat::Csv), &mut stats)?;
+
+    Ok(stats)
+  }
+
+  #[test]
+  fn test_stats_csv() -> Result<(), ReportsError> {
+    let mut output_buffer: Vec<u8> = vec![];
+
+    write_csv(
+      &mut output_buffer,
+      &stats(&[GroupBy::TxType, GroupBy::Day])?,
+    )?;
+
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "GROUP,KEY,COUNT,SUM,MIN,MAX,MEAN
+total,,3,440,40,300,146.67
+tx_type,DEPOSIT,2,400,100,300,200.00
+tx_type,TRANSFER,1,40,40,40,40.00
+day,2021-09-30,1,100,100,100,100.00
+day,2021-10-01,2,340,40,300,170.00
+"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_stats_text() -> Result<(), ReportsError> {
+    let mut output_buffer: Vec<u8> = vec![];
+
+    write_text(&mut output_buffer, &stats(&[GroupBy::Status])?)?;
+
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "total
+KEY  COUNT  SUM  MIN  MAX    MEAN
+all      3  440   40  300  146.67
+
+status
+KEY      COUNT  SUM  MIN  MAX    MEAN
+FAILURE      1   40   40   40   40.00
+SUCCESS      2  400  100  300  200.00
+"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_stats_parsing_error() {
+    let source = source(format!("{SOURCE}4,REFUND\n"), DataFormat::Csv);
+
+    assert!(matches!(
+      collect(source, &mut Stats::new(&[])),
+      Err(ReportsError::Source { name, .. }) if name == "input"
+    ));
+  }
+}
@@ -0,0 +1,765 @@
+use crate::args::{InputSources, flatten_sources, input_validation};
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use crate::sources::{LocatedRecord, Location, Source, SourceReader};
+use clap::Args;
+use parser::errors::{ColumnarError, ParsingError, SqliteError};
+use parser::parsers::{
+  BIN_CHECKED_RECORD_HEADER, BIN_RECORD_HEADER, BinRecord, CVS_RECORD_HEADER,
+};
+use parser::record::{BankRecord, BankRecordParser, TxType};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::{BufRead, BufWriter, Cursor, Write};
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+  /// Report files, directories or glob patterns, `-` reads stdin
+  #[arg(short = 'i', long, value_name = "File path", required = true, num_args = 1.., value_parser = input_validation)]
+  pub input: Vec<InputSources>,
+  /// Format of all input reports, detected for every file if omitted
+  #[arg(long, value_enum, value_name = "File Format")]
+  pub input_format: Option<DataFormat>,
+  /// Fail on warnings as well as on errors
+  #[arg(long)]
+  pub deny_warnings: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  Warning,
+  Error,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+  pub location: Location,
+  pub severity: Severity,
+  /// Short name of the failed check, e.g. `duplicate-tx-id`
+  pub code: &'static str,
+  pub message: String,
+}
+
+/// Problems found in a single report
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+  pub records: usize,
+  pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Warning => write!(f, "warning"),
+      Self::Error => write!(f, "error"),
+    }
+  }
+}
+
+impl ValidationReport {
+  pub fn count(&self, severity: Severity) -> usize {
+    self
+      .diagnostics
+      .iter()
+      .filter(|diagnostic| diagnostic.severity == severity)
+      .count()
+  }
+}
+
+/// Validates input reports and writes found problems to stdout, fails if
+/// any report has errors
+pub fn run(args: ValidateArgs) -> Result<(), ReportsError> {
+  let stdout = io::stdout().lock();
+  let mut buf_writer = BufWriter::new(stdout);
+  let (mut records, mut errors, mut warnings) = (0, 0, 0);
+
+  for input in flatten_sources(args.input) {
+    let source = Source::open(&input, args.input_format)?;
+    let name = source.name.clone();
+    let report = validate(source).map_err(|err| err.for_source(&name))?;
+
+    for diagnostic in &report.diagnostics {
+      writeln!(
+        buf_writer,
+        "{name}:{}: {}[{}]: {}",
+        diagnostic.location,
+        diagnostic.severity,
+        diagnostic.code,
+        diagnostic.message
+      )?;
+    }
+
+    records += report.records;
+    errors += report.count(Severity::Error);
+    warnings += report.count(Severity::Warning);
+  }
+
+  writeln!(
+    buf_writer,
+    "Checked {records} records: {errors} errors, {warnings} warnings"
+  )?;
+  buf_writer.flush()?;
+
+  if errors > 0 || (args.deny_warnings && warnings > 0) {
+    return Err(ReportsError::Invalid { errors, warnings });
+  }
+
+  Ok(())
+}
+
+/// Parses every record of the source and checks records consistency.
+/// Parsing continues after a broken record, so all problems are reported.
+pub fn validate(source: Source) -> Result<ValidationReport, ReportsError> {
+  let Source { reader, format, .. } = source;
+  let mut validator = Validator::default();
+
+  let mut reader = match reader {
+    SourceReader::Data(reader) => reader,
+    SourceReader::Records(records) => {
+      validate_records(records, &mut validator);
+      return Ok(validator.finish());
+    }
+  };
+
+  match format {
+    DataFormat::Bin => validate_bin(&mut reader, &mut validator)?,
+    DataFormat::Csv | DataFormat::Json => {
+      validate_lines(&mut reader, format, &mut validator)?
+    }
+    DataFormat::Txt => validate_blocks(&mut reader, &mut validator)?,
+    DataFormat::Camt => validate_entries(&mut reader, &mut validator)?,
+    DataFormat::Mt940
+    | DataFormat::Sqlite
+    | DataFormat::Arrow
+    | DataFormat::Parquet
+    | DataFormat::Ofx
+    | DataFormat::OfxXml
+    | DataFormat::Qif => {
+      return Err(ReportsError::IO(format.unsupported_read()));
+    }
+  }
+
+  Ok(validator.finish())
+}
+
+/// Records of formats read by their own readers are located by the readers,
+/// e.g. MT940 records by their lines, SQLite records by their rows and
+/// columnar records by their batches
+fn validate_records(
+  records: impl Iterator<Item = LocatedRecord>,
+  validator: &mut Validator,
+) {
+  for (location, record) in records {
+    match record {
+      Ok(record) => validator.check(location, record),
+      Err(err) => validator.failed(location, err),
+    }
+  }
+}
+
+/// Binary records are located by their headers, bytes between records are
+/// reported as garbage
+fn validate_bin(
+  reader: &mut impl BufRead,
+  validator: &mut Validator,
+) -> Result<(), ReportsError> {
+  let mut data = vec![];
+  reader.read_to_end(&mut data)?;
+
+  let next_header = |from: usize| {
+    (from..data.len().saturating_sub(3)).find(|start| {
+      let header = &data[*start..*start + 4];
+      header == BIN_RECORD_HEADER || header == BIN_CHECKED_RECORD_HEADER
+    })
+  };
+  let mut offset = 0;
+
+  while offset < data.len() {
+    let location = Location::Offset(offset as u64);
+    let Some(start) = next_header(offset) else {
+      validator.push(
+        location,
+        Severity::Error,
+        "syntax",
+        format!("{} bytes without record header", data.len() - offset),
+      );
+      break;
+    };
+
+    if start > offset {
+      validator.push(
+        location,
+        Severity::Error,
+        "syntax",
+        format!("{} bytes without record header", start - offset),
+      );
+    }
+
+    let mut cursor = Cursor::new(&data[start..]);
+    let location = Location::Offset(start as u64);
+    match BinRecord::from_read(&mut cursor) {
+      Ok(record) => {
+        validator.check(location, record);
+        offset = start + cursor.position() as usize;
+      }
+      Err(err) => {
+        validator.parsing_failed(location, err);
+        // Intact records may follow the broken record header
+        offset = next_header(start + 1).unwrap_or(data.len());
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// Every line holds a single record, csv reports start with the headers line
+fn validate_lines(
+  reader: &mut impl BufRead,
+  format: DataFormat,
+  validator: &mut Validator,
+) -> Result<(), ReportsError> {
+  for (index, line) in reader.split(b'\n').enumerate() {
+    let line = line?;
+    let line = line.strip_suffix(b"\r").unwrap_or(&line);
+    let location = Location::Line(index + 1);
+
+    if format == DataFormat::Csv && index == 0 {
+      if line != CVS_RECORD_HEADER.as_bytes() {
+        validator.push(
+          location,
+          Severity::Error,
+          "csv-header",
+          format!("headers line {CVS_RECORD_HEADER:?} expected"),
+        );
+      }
+      continue;
+    }
+
+    if line.iter().all(u8::is_ascii_whitespace) {
+      validator.push(
+        location,
+        Severity::Warning,
+        "empty-line",
+        String::from("empty line"),
+      );
+      continue;
+    }
+
+    validator.parsed(location, format.read_record(&mut Cursor::new(line)));
+  }
+
+  Ok(())
+}
+
+/// Text records are blocks of lines separated by empty lines, comment lines
+/// start with `#`
+fn validate_blocks(
+  reader: &mut impl BufRead,
+  validator: &mut Validator,
+) -> Result<(), ReportsError> {
+  let mut block: Vec<u8> = vec![];
+  let mut block_start = None;
+
+  for (index, line) in reader.split(b'\n').enumerate() {
+    let line = line?;
+    let line = line.strip_suffix(b"\r").unwrap_or(&line);
+
+    if line.iter().all(u8::is_ascii_whitespace) {
+      if let Some(start) = block_start.take() {
+        validator.parse_block(start, &block);
+      }
+      block.clear();
+      continue;
+    }
+
+    if !line.starts_with(b"#") {
+      block_start.get_or_insert(Location::Line(index + 1));
+    }
+    block.extend_from_slice(line);
+    block.push(b'\n');
+  }
+
+  if let Some(start) = block_start {
+    validator.parse_block(start, &block);
+  }
+
+  Ok(())
+}
+
+/// camt.053 entries are located by their `<Ntry>` elements, parsing resumes
+/// at the next entry after a broken one
+fn validate_entries(
+  reader: &mut impl BufRead,
+  validator: &mut Validator,
+) -> Result<(), ReportsError> {
+  let mut data = vec![];
+  reader.read_to_end(&mut data)?;
+
+  let next_entry = |from: usize| {
+    (from..data.len().saturating_sub(5)).find(|start| {
+      data[*start..].starts_with(b"<Ntry")
+        && matches!(data[*start + 5], b'>' | b' ' | b'\t' | b'\r' | b'\n')
+    })
+  };
+  let mut offset = 0;
+  // Line breaks are counted once, up to the last entry start
+  let (mut counted, mut line) = (0, 0);
+
+  while let Some(start) = next_entry(offset) {
+    line += data[counted..start]
+      .iter()
+      .filter(|byte| **byte == b'\n')
+      .count();
+    counted = start;
+    let mut cursor = Cursor::new(&data[start..]);
+    let record = DataFormat::Camt.read_record(&mut cursor);
+
+    offset = match record {
+      Ok(_) => start + cursor.position() as usize,
+      Err(_) => start + 1,
+    };
+    validator.parsed(Location::Line(line + 1), record);
+  }
+
+  Ok(())
+}
+
+/// Checks of records which depend on the preceding records
+#[derive(Default)]
+struct Validator {
+  report: ValidationReport,
+  /// Location of the first record with the transaction id
+  tx_ids: HashMap<u64, Location>,
+  last_timestamp: Option<u64>,
+}
+
+impl Validator {
+  /// Completes the report, reports without records are reported as empty
+  fn finish(mut self) -> ValidationReport {
+    if self.report.records == 0 && self.report.diagnostics.is_empty() {
+      self.push(
+        Location::Line(1),
+        Severity::Warning,
+        "empty",
+        String::from("report has no records"),
+      );
+    }
+
+    self.report
+  }
+
+  fn push(
+    &mut self,
+    location: Location,
+    severity: Severity,
+    code: &'static str,
+    message: String,
+  ) {
+    self.report.diagnostics.push(Diagnostic {
+      location,
+      severity,
+      code,
+      message,
+    });
+  }
+
+  fn parse_block(&mut self, location: Location, block: &[u8]) {
+    let mut data = block.to_vec();
+    // Record is terminated by an empty line
+    data.push(b'\n');
+
+    self.parsed(
+      location,
+      DataFormat::Txt.read_record(&mut Cursor::new(data)),
+    );
+  }
+
+  fn parsed(
+    &mut self,
+    location: Location,
+    record: Result<BankRecord, ParsingError>,
+  ) {
+    match record {
+      Ok(record) => self.check(location, record),
+      Err(err) => self.parsing_failed(location, err),
+    }
+  }
+
+  /// Reports errors of record readers, e.g. SQLite values which can't be
+  /// converted
+  fn failed(&mut self, location: Location, err: ReportsError) {
+    match err {
+      ReportsError::Parsing(err) => self.parsing_failed(location, err),
+      ReportsError::Sqlite(err @ SqliteError::InvalidValue { .. }) => {
+        self.push(location, Severity::Error, "unknown-value", err.to_string())
+      }
+      ReportsError::Columnar(err @ ColumnarError::InvalidValue { .. }) => {
+        self.push(location, Severity::Error, "unknown-value", err.to_string())
+      }
+      err => self.push(location, Severity::Error, "syntax", err.to_string()),
+    }
+  }
+
+  fn parsing_failed(&mut self, location: Location, err: ParsingError) {
+    let (code, message) = match err {
+      ParsingError::ParseTxType(err) => ("unknown-value", err.to_string()),
+      ParsingError::ParseStatus(err) => ("unknown-value", err.to_string()),
+      ParsingError::Checksum { .. } => ("checksum", err.to_string()),
+      ParsingError::IO(err) => ("syntax", err.to_string()),
+      err => ("syntax", err.to_string()),
+    };
+
+    self.push(location, Severity::Error, code, message);
+  }
+
+  fn check(&mut self, location: Location, record: BankRecord) {
+    self.report.records += 1;
+    self.check_accounts(location, &record);
+
+    if let Some(first) = self.tx_ids.get(&record.tx_id) {
+      let message = format!(
+        "transaction {} is already defined at {}",
+        record.tx_id,
+        match first {
+          Location::Line(line) => format!("line {line}"),
+          Location::Offset(offset) => format!("byte {offset}"),
+          location => location.to_string(),
+        }
+      );
+      self.push(location, Severity::Error, "duplicate-tx-id", message);
+    } else {
+      self.tx_ids.insert(record.tx_id, location);
+    }
+
+    if let Some(last_timestamp) = self.last_timestamp
+      && record.timestamp < last_timestamp
+    {
+      self.push(
+        location,
+        Severity::Warning,
+        "timestamp-order",
+        format!(
+          "timestamp {} precedes timestamp {last_timestamp} of the previous record",
+          record.timestamp
+        ),
+      );
+    }
+    self.last_timestamp = Some(record.timestamp);
+  }
+
+  /// Deposits are received from the bank account `0`, withdrawals are sent
+  /// to it, transfers are made between user accounts
+  fn check_accounts(&mut self, location: Location, record: &BankRecord) {
+    let (from, to) = (record.from_user_id, record.to_user_id);
+    let (severity, message) = match record.tx_type {
+      TxType::Deposit if to == 0 => {
+        (Severity::Error, String::from("deposit has no receiver"))
+      }
+      TxType::Deposit if from != 0 => (
+        Severity::Warning,
+        format!("deposit is sent by user {from}, not by the bank"),
+      ),
+      TxType::Withdrawal if from == 0 => {
+        (Severity::Error, String::from("withdrawal has no sender"))
+      }
+      TxType::Withdrawal if to != 0 => (
+        Severity::Warning,
+        format!("withdrawal is received by user {to}, not by the bank"),
+      ),
+      TxType::Transfer if from == 0 || to == 0 => (
+        Severity::Error,
+        String::from("transfer has to be made between user accounts"),
+      ),
+      TxType::Transfer if from == to => (
+        Severity::Warning,
+        format!("transfer is sent by user {from} to themselves"),
+      ),
+      _ => return,
+    };
+
+    self.push(location, severity, "account-mismatch", message);
+  }
+}
+
+#[cfg(test)]
+mod validate_test {
+  use crate::commands::validate::{Diagnostic, Severity, validate};
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use crate::sources::{InputSource, Location, Source, SourceOptions};
+  use crate::test_utils::{record, source};
+  use parser::columnar::ColumnarWriter;
+  use parser::parsers::{BinRecord, CheckedBinRecord};
+  use parser::record::{BankRecord, BankRecordParser};
+  use parser::sqlite::{SQLITE_HEADER, SQLITE_TABLE, SqliteWriter};
+  use std::fs;
+
+  fn codes(
+    diagnostics: &[Diagnostic],
+  ) -> Vec<(Location, Severity, &'static str)> {
+    diagnostics
+      .iter()
+      .map(|diagnostic| {
+        (diagnostic.location, diagnostic.severity, diagnostic.code)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_validate_csv() -> Result<(), ReportsError> {
+    let report = validate(source(
+      "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,2,100,1633036860000,SUCCESS,\"Record number 1\"
+2,TRANSFER,2,0,200,1633036920000,SUCCESS,\"Record number 2\"
+3,REFUND,2,3,300,1633036980000,SUCCESS,\"Record number 3\"
+1,WITHDRAWAL,2,0,400,1633036800000,DONE,\"Record number 4\"
+4,WITHDRAWAL,2,0,400,1633036800000,SUCCESS,\"Record number 4\"
+
+5,DEPOSIT,0,2,abc,1633036990000,SUCCESS,\"Record number 5\"
+",
+      DataFormat::Csv,
+    ))?;
+
+    assert_eq!(report.records, 3);
+    assert_eq!(
+      codes(&report.diagnostics),
+      vec![
+        (Location::Line(3), Severity::Error, "account-mismatch"),
+        (Location::Line(4), Severity::Error, "unknown-value"),
+        (Location::Line(5), Severity::Error, "unknown-value"),
+        (Location::Line(6), Severity::Warning, "timestamp-order"),
+        (Location::Line(7), Severity::Warning, "empty-line"),
+        (Location::Line(8), Severity::Error, "syntax"),
+      ]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate_txt() -> Result<(), ReportsError> {
+    let report = validate(source(
+      "# Record 1 (DEPOSIT)
+TX_TYPE: DEPOSIT
+TO_USER_ID: 2
+FROM_USER_ID: 0
+TIMESTAMP: 1633036860000
+DESCRIPTION: \"Record number 1\"
+TX_ID: 1
+AMOUNT: 100
+STATUS: SUCCESS
+
+# Record 2 (TRANSFER)
+TX_TYPE: TRANSFER
+TO_USER_ID: 2
+FROM_USER_ID: 2
+TIMESTAMP: 1633036920000
+DESCRIPTION: \"Record number 2\"
+TX_ID: 1
+AMOUNT: 200
+STATUS: SUCCESS
+",
+      DataFormat::Txt,
+    ))?;
+
+    assert_eq!(report.records, 2);
+    assert_eq!(
+      codes(&report.diagnostics),
+      vec![
+        (Location::Line(12), Severity::Warning, "account-mismatch"),
+        (Location::Line(12), Severity::Error, "duplicate-tx-id"),
+      ]
+    );
+    assert_eq!(
+      report.diagnostics[1].message,
+      "transaction 1 is already defined at line 2"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate_bin() -> Result<(), ReportsError> {
+    let ordered_record = |tx_id: u64| BankRecord {
+      timestamp: 1633036860000 + tx_id,
+      ..record(tx_id)
+    };
+    let mut data = vec![];
+    BinRecord(ordered_record(1)).write_to(&mut data)?;
+    let garbage_offset = data.len();
+    data.extend_from_slice(b"junk");
+    let damaged_offset = data.len();
+    CheckedBinRecord(ordered_record(2)).write_to(&mut data)?;
+    // Damage the checksum trailer
+    *data.last_mut().unwrap() ^= 0xff;
+    BinRecord(ordered_record(3)).write_to(&mut data)?;
+
+    let report = validate(source(data, DataFormat::Bin))?;
+
+    assert_eq!(report.records, 2);
+    assert_eq!(
+      codes(&report.diagnostics),
+      vec![
+        (
+          Location::Offset(garbage_offset as u64),
+          Severity::Error,
+          "syntax"
+        ),
+        (
+          Location::Offset(damaged_offset as u64),
+          Severity::Error,
+          "checksum"
+        ),
+      ]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate_camt() -> Result<(), ReportsError> {
+    let entry = |tx_id: u64, amount: &str, account: &str| {
+      format!(
+        "<Ntry><NtryRef>{tx_id}</NtryRef><Amt>{amount}</Amt>\
+         <CdtDbtInd>CRDT</CdtDbtInd><Sts><Cd>BOOK</Cd></Sts>\
+         <BookgDt><Dt>2021-10-01</Dt></BookgDt><NtryDtls><TxDtls>\
+         <RltdPties><CdtrAcct><Id><Othr><Id>{account}</Id></Othr></Id>\
+         </CdtrAcct></RltdPties></TxDtls></NtryDtls></Ntry>\n"
+      )
+    };
+    let report = validate(source(
+      [
+        String::from("<Document><BkToCstmrStmt><Stmt>\n"),
+        entry(1, "1.00", "2"),
+        entry(2, "1.001", "2"),
+        entry(1, "2.00", "2"),
+        entry(3, "3.00", "0"),
+        String::from("</Stmt></BkToCstmrStmt></Document>\n"),
+      ]
+      .concat(),
+      DataFormat::Camt,
+    ))?;
+
+    assert_eq!(report.records, 3);
+    assert_eq!(
+      codes(&report.diagnostics),
+      vec![
+        (Location::Line(3), Severity::Error, "syntax"),
+        (Location::Line(4), Severity::Error, "duplicate-tx-id"),
+        (Location::Line(5), Severity::Error, "account-mismatch"),
+      ]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate_sqlite() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let database = dir.path().join("records.sqlite");
+    let mut writer = SqliteWriter::create(&database, SQLITE_TABLE)?;
+    for tx_id in [1, 2, 1] {
+      writer.write(&record(tx_id))?;
+    }
+    writer.finish()?;
+
+    let database = Source::open_with(
+      &InputSource::File(database),
+      None,
+      &SourceOptions {
+        sqlite_query: String::from(
+          "SELECT tx_id, CASE tx_id WHEN 2 THEN 'REFUND' ELSE tx_type END \
+           AS tx_type, from_user_id, to_user_id, amount, timestamp, status, \
+           description FROM transactions ORDER BY rowid",
+        ),
+        ..SourceOptions::default()
+      },
+    )?;
+    let report = validate(database)?;
+
+    assert_eq!(report.records, 2);
+    assert_eq!(
+      codes(&report.diagnostics),
+      vec![
+        (Location::Row(2), Severity::Error, "unknown-value"),
+        (Location::Row(3), Severity::Error, "duplicate-tx-id"),
+      ]
+    );
+    assert_eq!(
+      report.diagnostics[1].message,
+      "transaction 1 is already defined at row 1"
+    );
+
+    // SQLite data which isn't a file can't be read
+    assert!(validate(source(SQLITE_HEADER, DataFormat::Sqlite)).is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate_mt940() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("records.sta");
+    fs::write(
+      &path,
+      ":25:1\n\
+       :61:211001C1,00NMSC7\n:86:Deposit\n\
+       :61:211301C1,00NMSC8\n:86:Invalid date\n\
+       :61:211001C2,00NMSC7\n:86:Duplicate\n-\n",
+    )?;
+
+    let report = validate(Source::open(&InputSource::File(path), None)?)?;
+
+    assert_eq!(report.records, 2);
+    assert_eq!(
+      codes(&report.diagnostics),
+      vec![
+        (Location::Line(4), Severity::Error, "syntax"),
+        (Location::Line(6), Severity::Error, "duplicate-tx-id"),
+      ]
+    );
+    assert_eq!(
+      report.diagnostics[1].message,
+      "transaction 7 is already defined at line 2"
+    );
+
+    // MT940 data isn't read without its statement
+    assert!(validate(source(b":25:1\n", DataFormat::Mt940)).is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_validate_arrow() -> Result<(), ReportsError> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("records.arrow");
+    let mut writer = ColumnarWriter::ipc(Vec::new())?;
+    for tx_id in [1, 2, 1] {
+      writer.write(&record(tx_id))?;
+    }
+    fs::write(&path, writer.finish()?)?;
+
+    let report = validate(Source::open(&InputSource::File(path), None)?)?;
+
+    assert_eq!(report.records, 3);
+    assert_eq!(
+      codes(&report.diagnostics),
+      vec![(
+        Location::Batch {
+          batch: 1,
+          row: Some(3)
+        },
+        Severity::Error,
+        "duplicate-tx-id"
+      )]
+    );
+    assert_eq!(
+      report.diagnostics[0].message,
+      "transaction 1 is already defined at batch 1 row 1"
+    );
+
+    Ok(())
+  }
+}
@@ -0,0 +1,191 @@
+use crate::formats::DataFormat;
+use parser::errors::{
+  ColumnarError, ParsingError, QueryError, SerializeError, SqliteError,
+};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+#[derive(Debug)]
+pub enum ReportsError {
+  IO(io::Error),
+  Parsing(ParsingError),
+  Query(QueryError),
+  Sqlite(SqliteError),
+  Columnar(ColumnarError),
+  InvalidSourceFile,
+  UnknownFormat,
+  /// Query has neither FROM clause nor input reports
+  NoInput,
+  InvalidTemplate(String),
+  InvalidSize(String),
+  InvalidDuration(String),
+  /// MT940 account mapping which isn't `ACCOUNT=USER_ID`
+  InvalidAccount(String),
+  /// Record of a sorted report precedes the previous one
+  Unsorted {
+    tx_id: u64,
+  },
+  /// Validated reports have problems
+  Invalid {
+    errors: usize,
+    warnings: usize,
+  },
+  /// Reconciled reports have issues
+  Unreconciled {
+    issues: usize,
+  },
+  /// Sidecar index does not match its binary report
+  InvalidIndex,
+  /// Error of a particular input source
+  Source {
+    name: String,
+    source: Box<ReportsError>,
+  },
+}
+
+impl ReportsError {
+  pub fn for_source(self, name: &str) -> Self {
+    match self {
+      Self::Source { .. } => self,
+      err => Self::Source {
+        name: name.to_string(),
+        source: Box::new(err),
+      },
+    }
+  }
+}
+
+impl Display for ReportsError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::IO(err) => write!(f, "{:?}", err),
+      Self::Parsing(err) => write!(f, "{err}"),
+      Self::Query(err) => write!(f, "{err}"),
+      Self::Sqlite(err) => write!(f, "{err}"),
+      Self::Columnar(err) => write!(f, "{err}"),
+      Self::InvalidSourceFile => {
+        write!(
+          f,
+          "Only following file types are supported: {:?}",
+          DataFormat::extensions()
+        )
+      }
+      Self::UnknownFormat => {
+        write!(
+          f,
+          "Failed detecting data format, please set the report format"
+        )
+      }
+      Self::NoInput => {
+        write!(f, "Query has no FROM clause, please set the input reports")
+      }
+      Self::InvalidTemplate(template) => {
+        write!(
+          f,
+          "Invalid output template {template:?}, it has to contain {{key}} or {{index}} and only following placeholders: {:?}",
+          crate::commands::split::TEMPLATE_PLACEHOLDERS
+        )
+      }
+      Self::InvalidSize(size) => {
+        write!(
+          f,
+          "Invalid size {size:?}, number of bytes with optional K, M or G suffix expected"
+        )
+      }
+      Self::InvalidDuration(duration) => {
+        write!(
+          f,
+          "Invalid duration {duration:?}, number with s, m, h or d suffix expected"
+        )
+      }
+      Self::InvalidAccount(account) => {
+        write!(
+          f,
+          "Invalid account mapping {account:?}, ACCOUNT=USER_ID expected"
+        )
+      }
+      Self::Unsorted { tx_id } => {
+        write!(
+          f,
+          "Report is not sorted by the merge key, record {tx_id} is out of order"
+        )
+      }
+      Self::Invalid { errors, warnings } => {
+        write!(
+          f,
+          "Validation failed with {errors} errors and {warnings} warnings"
+        )
+      }
+      Self::Unreconciled { issues } => {
+        write!(f, "Reconciliation failed with {issues} issues")
+      }
+      Self::InvalidIndex => {
+        write!(f, "Index does not match the report, please rebuild it")
+      }
+      Self::Source { name, source } => write!(f, "{name}: {source}"),
+    }
+  }
+}
+
+impl Error for ReportsError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      Self::IO(err) => Some(err),
+      Self::Parsing(err) => Some(err),
+      Self::Query(err) => Some(err),
+      Self::Sqlite(err) => Some(err),
+      Self::Columnar(err) => Some(err),
+      Self::InvalidSourceFile
+      | Self::UnknownFormat
+      | Self::NoInput
+      | Self::InvalidTemplate(_)
+      | Self::InvalidSize(_)
+      | Self::InvalidDuration(_)
+      | Self::InvalidAccount(_)
+      | Self::Unsorted { .. }
+      | Self::Invalid { .. }
+      | Self::Unreconciled { .. }
+      | Self::InvalidIndex => None,
+      Self::Source { source, .. } => Some(source.as_ref()),
+    }
+  }
+}
+
+impl From<io::Error> for ReportsError {
+  fn from(err: io::Error) -> Self {
+    Self::IO(err)
+  }
+}
+
+impl From<ParsingError> for ReportsError {
+  fn from(err: ParsingError) -> Self {
+    Self::Parsing(err)
+  }
+}
+
+impl From<QueryError> for ReportsError {
+  fn from(err: QueryError) -> Self {
+    Self::Query(err)
+  }
+}
+
+impl From<SqliteError> for ReportsError {
+  fn from(err: SqliteError) -> Self {
+    Self::Sqlite(err)
+  }
+}
+
+impl From<ColumnarError> for ReportsError {
+  fn from(err: ColumnarError) -> Self {
+    Self::Columnar(err)
+  }
+}
+
+impl From<SerializeError> for ReportsError {
+  fn from(value: SerializeError) -> Self {
+    match value {
+      SerializeError::IO(err) => Self::IO(err),
+    }
+  }
+}
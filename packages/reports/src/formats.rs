@@ -0,0 +1,490 @@
+//! Registry of report data formats shared by all commands. Every format is
+//! detected, read and written here, so a new format is added in one place.
+
+use crate::errors::ReportsError;
+use clap::ValueEnum;
+use parser::columnar::{ARROW_IPC_HEADER, PARQUET_HEADER};
+use parser::compression::data_extension;
+use parser::errors::{ParsingError, SerializeError};
+use parser::parallel::{ChunkBoundary, try_parse_parallel};
+use parser::parsers::{
+  BIN_CHECKED_RECORD_HEADER, BIN_RECORD_HEADER, BinRecord, CVS_RECORD_HEADER,
+  CamtRecord, CheckedBinRecord, CsvRecord, JsonLines, Mt940Balance,
+  Mt940Record, SerdeRecord, TxtRecord,
+};
+use parser::record::{BankRecord, BankRecordParser};
+use parser::sqlite::SQLITE_HEADER;
+use std::io;
+use std::io::{BufRead, ErrorKind, Write};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq)]
+pub enum DataFormat {
+  Bin,
+  Csv,
+  Txt,
+  /// JSON Lines, one record per line
+  Json,
+  /// SQLite database with a `transactions` table, opened sources read its
+  /// records row by row
+  Sqlite,
+  /// Arrow IPC file, opened sources read its records batch by batch
+  Arrow,
+  /// Parquet file, opened sources read its records batch by batch
+  Parquet,
+  /// ISO 20022 camt.053 XML statement, one entry per record
+  Camt,
+  /// SWIFT MT940 statements, one statement line per record, opened sources
+  /// read them statement by statement
+  Mt940,
+  /// OFX 1.0.2 SGML statement of a single user, written only
+  Ofx,
+  /// OFX 2.2 XML statement of a single user, written only
+  OfxXml,
+  /// QIF transactions of a single user, written only
+  Qif,
+}
+
+impl DataFormat {
+  pub const ALL: [DataFormat; 12] = [
+    DataFormat::Bin,
+    DataFormat::Csv,
+    DataFormat::Txt,
+    DataFormat::Json,
+    DataFormat::Sqlite,
+    DataFormat::Arrow,
+    DataFormat::Parquet,
+    DataFormat::Camt,
+    DataFormat::Mt940,
+    DataFormat::Ofx,
+    DataFormat::OfxXml,
+    DataFormat::Qif,
+  ];
+
+  /// File extension of reports in the format
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Self::Bin => "bin",
+      Self::Csv => "csv",
+      Self::Txt => "txt",
+      Self::Json => "jsonl",
+      Self::Sqlite => "sqlite",
+      Self::Arrow => "arrow",
+      Self::Parquet => "parquet",
+      Self::Camt => "xml",
+      Self::Mt940 => "sta",
+      Self::Ofx | Self::OfxXml => "ofx",
+      Self::Qif => "qif",
+    }
+  }
+
+  /// Extensions of report files which can be read, write-only formats are
+  /// skipped
+  pub fn extensions() -> Vec<&'static str> {
+    let mut extensions: Vec<&'static str> = vec![];
+    for format in Self::ALL {
+      if format.readable().is_ok() && !extensions.contains(&format.extension())
+      {
+        extensions.push(format.extension());
+      }
+    }
+
+    extensions
+  }
+
+  pub fn from_extension(extension: &str) -> Option<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|format| format.extension() == extension)
+  }
+
+  /// Detects format by the file extension, compression extension is skipped
+  pub fn from_path(path: &Path) -> Option<Self> {
+    Self::from_extension(data_extension(path)?)
+  }
+
+  /// Detects format by the beginning of the report data
+  pub fn sniff(data: &[u8]) -> Option<Self> {
+    if data.starts_with(SQLITE_HEADER) {
+      Some(Self::Sqlite)
+    } else if data.starts_with(ARROW_IPC_HEADER) {
+      Some(Self::Arrow)
+    } else if data.starts_with(PARQUET_HEADER) {
+      Some(Self::Parquet)
+    } else if data.starts_with(b"<?xml") || data.starts_with(b"<Document") {
+      Some(Self::Camt)
+    } else if data.starts_with(b":20:") || data.starts_with(b"{1:") {
+      Some(Self::Mt940)
+    } else if data.starts_with(BIN_RECORD_HEADER)
+      || data.starts_with(BIN_CHECKED_RECORD_HEADER)
+    {
+      Some(Self::Bin)
+    } else if data.starts_with(CVS_RECORD_HEADER.as_bytes()) {
+      Some(Self::Csv)
+    } else if data.starts_with(b"{") {
+      Some(Self::Json)
+    } else if data.starts_with(b"#") || data.starts_with(b"TX_") {
+      Some(Self::Txt)
+    } else {
+      None
+    }
+  }
+
+  /// Boundary records can be split at for parallel parsing, multi-line text
+  /// records are parsed sequentially
+  pub(crate) fn chunk_boundary(&self) -> Option<ChunkBoundary> {
+    match self {
+      Self::Bin => Some(ChunkBoundary::BinRecord),
+      Self::Csv | Self::Json => Some(ChunkBoundary::Line),
+      Self::Txt
+      | Self::Mt940
+      | Self::Sqlite
+      | Self::Arrow
+      | Self::Parquet
+      | Self::Camt
+      | Self::Ofx
+      | Self::OfxXml
+      | Self::Qif => None,
+    }
+  }
+
+  /// Formats records can be read from, OFX and QIF statements are only
+  /// written
+  pub fn readable(self) -> Result<Self, ReportsError> {
+    match self {
+      Self::Ofx | Self::OfxXml | Self::Qif => {
+        Err(ReportsError::IO(self.unsupported_read()))
+      }
+      format => Ok(format),
+    }
+  }
+
+  pub(crate) fn unsupported_read(self) -> io::Error {
+    let message = match self {
+      Self::Sqlite => "SQLite databases can only be read from a file",
+      Self::Arrow | Self::Parquet => {
+        "Arrow and Parquet files can't be read record by record"
+      }
+      Self::Mt940 => "MT940 statement lines are read with their statement",
+      _ => "OFX and QIF statements can't be read",
+    };
+
+    io::Error::new(ErrorKind::Unsupported, message)
+  }
+
+  /// Formats written record by record, SQLite databases, Arrow and Parquet
+  /// files, OFX and QIF statements are written only by the convert command
+  pub fn stream_output(self) -> Result<Self, ReportsError> {
+    match self {
+      Self::Sqlite
+      | Self::Arrow
+      | Self::Parquet
+      | Self::Ofx
+      | Self::OfxXml
+      | Self::Qif => Err(ReportsError::IO(self.unsupported_stream())),
+      format => Ok(format),
+    }
+  }
+
+  fn unsupported_stream(self) -> io::Error {
+    let message = match self {
+      Self::Sqlite => {
+        "SQLite databases can only be written to a file with convert --output"
+      }
+      Self::Ofx | Self::OfxXml | Self::Qif => {
+        "OFX and QIF statements can only be written with convert --for-user"
+      }
+      _ => "Arrow and Parquet files can only be written with convert",
+    };
+
+    io::Error::new(ErrorKind::Unsupported, message)
+  }
+
+  pub fn skip_header(&self, reader: &mut impl BufRead) -> io::Result<()> {
+    if *self == Self::Csv {
+      reader.read_line(&mut String::new())?;
+    }
+
+    Ok(())
+  }
+
+  pub fn read_record(
+    &self,
+    reader: &mut impl BufRead,
+  ) -> Result<BankRecord, ParsingError> {
+    match self {
+      Self::Bin => BinRecord::from_read(reader),
+      Self::Csv => CsvRecord::from_read(reader),
+      Self::Txt => TxtRecord::from_read(reader),
+      Self::Json => SerdeRecord::<JsonLines>::from_read(reader),
+      Self::Camt => CamtRecord::from_read(reader),
+      // Statements, database rows and columnar files are read by the source
+      Self::Mt940
+      | Self::Sqlite
+      | Self::Arrow
+      | Self::Parquet
+      | Self::Ofx
+      | Self::OfxXml
+      | Self::Qif => Err(ParsingError::IO(self.unsupported_read())),
+    }
+  }
+
+  /// Reads records until the end of data, headers line has to be skipped
+  /// already. Records are parsed by `jobs` threads if the format allows it.
+  pub fn read_records(
+    &self,
+    reader: &mut impl BufRead,
+    jobs: NonZeroUsize,
+  ) -> Result<Vec<BankRecord>, ReportsError> {
+    if jobs.get() > 1
+      && let Some(boundary) = self.chunk_boundary()
+    {
+      let mut data = vec![];
+      reader.read_to_end(&mut data)?;
+
+      return Ok(match self {
+        Self::Bin => try_parse_parallel::<BinRecord>(&data, boundary, jobs)?,
+        Self::Csv => try_parse_parallel::<CsvRecord>(&data, boundary, jobs)?,
+        Self::Json => {
+          try_parse_parallel::<SerdeRecord<JsonLines>>(&data, boundary, jobs)?
+        }
+        Self::Txt
+        | Self::Mt940
+        | Self::Sqlite
+        | Self::Arrow
+        | Self::Parquet
+        | Self::Camt
+        | Self::Ofx
+        | Self::OfxXml
+        | Self::Qif => {
+          unreachable!("Formats without chunk boundaries are read sequentially")
+        }
+      });
+    }
+
+    let mut records = vec![];
+    loop {
+      match self.read_record(reader) {
+        Ok(record) => records.push(record),
+        Err(ParsingError::IO(err))
+          if err.kind() == ErrorKind::UnexpectedEof =>
+        {
+          return Ok(records);
+        }
+        Err(err) => return Err(err.into()),
+      }
+    }
+  }
+
+  /// Writes the record, `checksum` appends CRC32C checksum to binary records
+  pub fn write_record(
+    &self,
+    writer: &mut impl Write,
+    record: BankRecord,
+    checksum: bool,
+  ) -> Result<(), SerializeError> {
+    match self {
+      Self::Bin if checksum => CheckedBinRecord(record).write_to(writer),
+      Self::Bin => BinRecord(record).write_to(writer),
+      Self::Csv => CsvRecord(record).write_to(writer),
+      Self::Txt => TxtRecord(record).write_to(writer),
+      Self::Json => SerdeRecord::<JsonLines>::new(record).write_to(writer),
+      Self::Camt => CamtRecord(record).write_to(writer),
+      Self::Mt940 => Mt940Record(record).write_to(writer),
+      Self::Sqlite
+      | Self::Arrow
+      | Self::Parquet
+      | Self::Ofx
+      | Self::OfxXml
+      | Self::Qif => Err(SerializeError::IO(self.unsupported_stream())),
+    }
+  }
+}
+
+/// Writes a report record by record. MT940 statements are opened with the
+/// value date of the first record and closed with the balance moved by all
+/// records, so every report is written by its own writer.
+pub struct ReportWriter {
+  format: DataFormat,
+  checksum: bool,
+  /// Balance of the MT940 statement, set once its header is written
+  balance: Option<Mt940Balance>,
+}
+
+impl ReportWriter {
+  /// `checksum` appends CRC32C checksum to binary records
+  pub fn new(format: DataFormat, checksum: bool) -> Self {
+    Self {
+      format,
+      checksum,
+      balance: None,
+    }
+  }
+
+  /// Writes the report header, MT940 statements are opened by the first
+  /// record instead
+  pub fn write_header(&mut self, writer: &mut impl Write) -> io::Result<()> {
+    match self.format {
+      DataFormat::Csv => writeln!(writer, "{}", CVS_RECORD_HEADER)?,
+      // Statements are identified by their creation time
+      DataFormat::Camt => {
+        let created = now();
+        CamtRecord::write_header(writer, &format!("YPBANK-{created}"), created)
+          .map_err(|SerializeError::IO(err)| err)?;
+      }
+      _ => {}
+    }
+
+    Ok(())
+  }
+
+  pub fn write_record(
+    &mut self,
+    writer: &mut impl Write,
+    record: BankRecord,
+  ) -> Result<(), SerializeError> {
+    self.start_record(writer, Mt940Balance::of(&record))?;
+    self.format.write_record(writer, record, self.checksum)
+  }
+
+  /// Counts the record which is written by `DataFormat::write_record`,
+  /// `line` is the balance of the record statement line. MT940 statement is
+  /// opened before the first record.
+  pub fn start_record(
+    &mut self,
+    writer: &mut impl Write,
+    line: Mt940Balance,
+  ) -> io::Result<()> {
+    if self.format != DataFormat::Mt940 {
+      return Ok(());
+    }
+
+    match &mut self.balance {
+      Some(balance) => balance.add(line),
+      None => {
+        let mut balance = self.open_statement(writer, line.date)?;
+        balance.add(line);
+        self.balance = Some(balance);
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Completes the report after the last record
+  pub fn write_footer(&mut self, writer: &mut impl Write) -> io::Result<()> {
+    match self.format {
+      DataFormat::Camt => CamtRecord::write_footer(writer),
+      DataFormat::Mt940 => {
+        let balance = match self.balance.take() {
+          Some(balance) => balance,
+          None => self.open_statement(writer, now())?,
+        };
+        Mt940Record::write_footer(writer, &balance)
+      }
+      _ => Ok(()),
+    }
+    .map_err(|SerializeError::IO(err)| err)
+  }
+
+  /// Writes the MT940 statement header, returns its opening balance
+  fn open_statement(
+    &self,
+    writer: &mut impl Write,
+    date: u64,
+  ) -> io::Result<Mt940Balance> {
+    // Statement references are limited to 16 characters
+    Mt940Record::write_header(writer, &format!("YP{}", now()), date)
+      .map_err(|SerializeError::IO(err)| err)?;
+
+    Ok(Mt940Balance::new(date))
+  }
+}
+
+/// Current time in milliseconds since the epoch
+pub(crate) fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod formats_test {
+  use crate::errors::ReportsError;
+  use crate::formats::DataFormat;
+  use parser::errors::ParsingError;
+  use parser::parsers::CVS_RECORD_HEADER;
+  use std::io::Cursor;
+  use std::num::NonZeroUsize;
+  use std::path::Path;
+
+  #[test]
+  fn test_detect_data_format() {
+    assert_eq!(
+      DataFormat::from_path(Path::new("records.csv.gz")),
+      Some(DataFormat::Csv)
+    );
+    assert_eq!(
+      DataFormat::from_path(Path::new("records.jsonl")),
+      Some(DataFormat::Json)
+    );
+    assert_eq!(
+      DataFormat::from_path(Path::new("records.xml")),
+      Some(DataFormat::Camt)
+    );
+    assert_eq!(DataFormat::from_path(Path::new("records.xlsx")), None);
+    assert!(DataFormat::Qif.readable().is_err());
+    assert_eq!(
+      DataFormat::extensions(),
+      [
+        "bin", "csv", "txt", "jsonl", "sqlite", "arrow", "parquet", "xml",
+        "sta"
+      ]
+    );
+    assert!(DataFormat::Ofx.stream_output().is_err());
+
+    assert_eq!(DataFormat::sniff(b"YPBN"), Some(DataFormat::Bin));
+    assert_eq!(DataFormat::sniff(b"YPBC"), Some(DataFormat::Bin));
+    assert_eq!(
+      DataFormat::sniff(CVS_RECORD_HEADER.as_bytes()),
+      Some(DataFormat::Csv)
+    );
+    assert_eq!(DataFormat::sniff(b"# Record 1"), Some(DataFormat::Txt));
+    assert_eq!(DataFormat::sniff(b"{\"TX_ID\":1}"), Some(DataFormat::Json));
+    assert_eq!(
+      DataFormat::sniff(b"SQLite format 3\0"),
+      Some(DataFormat::Sqlite)
+    );
+    assert_eq!(DataFormat::sniff(b"ARROW1\0\0"), Some(DataFormat::Arrow));
+    assert_eq!(DataFormat::sniff(b"PAR1"), Some(DataFormat::Parquet));
+    assert_eq!(
+      DataFormat::sniff(b"<?xml version=\"1.0\"?>"),
+      Some(DataFormat::Camt)
+    );
+    assert_eq!(DataFormat::sniff(b":20:STMT1"), Some(DataFormat::Mt940));
+    assert_eq!(DataFormat::sniff(b"{1:F01"), Some(DataFormat::Mt940));
+    assert_eq!(DataFormat::sniff(b"junk"), None);
+  }
+
+  #[test]
+  fn test_read_records_until_error() {
+    let csv = "1000000000000000,DEPOSIT,0,9223372036854775807,100,1633036860000,SUCCESS,\"Record number 1\"
+1000000000000001,REFUND,0,9223372036854775807,100,1633036860000,SUCCESS,\"Record number 2\"
+";
+
+    for jobs in [1, 4] {
+      let jobs = NonZeroUsize::new(jobs).unwrap();
+      let records =
+        DataFormat::Csv.read_records(&mut Cursor::new(&csv[..91]), jobs);
+      assert_eq!(records.unwrap().len(), 1);
+
+      let records = DataFormat::Csv.read_records(&mut Cursor::new(csv), jobs);
+      assert!(matches!(
+        records,
+        Err(ReportsError::Parsing(ParsingError::ParseTxType(..)))
+      ));
+    }
+  }
+}
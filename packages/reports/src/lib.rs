@@ -0,0 +1,13 @@
+//! Shared implementation of the `reports` commands. Standalone `converter`,
+//! `comparer`, `splitter`, `merger`, `repairer` and `indexer` binaries are
+//! thin wrappers around them.
+
+pub mod args;
+pub mod commands;
+pub mod errors;
+pub mod formats;
+pub mod sources;
+mod table;
+
+#[cfg(test)]
+mod test_utils;
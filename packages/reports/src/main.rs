@@ -0,0 +1,73 @@
+use clap::{Parser, Subcommand};
+use reports::commands::compare::{self, CompareArgs};
+use reports::commands::convert::{self, ConvertArgs};
+use reports::commands::index::{self, IndexArgs};
+use reports::commands::ledger::{self, LedgerArgs};
+use reports::commands::merge::{self, MergeArgs};
+use reports::commands::query::{self, QueryArgs};
+use reports::commands::reconcile::{self, ReconcileArgs};
+use reports::commands::repair::{self, RepairArgs};
+use reports::commands::split::{self, SplitArgs};
+use reports::commands::stats::{self, StatsArgs};
+use reports::commands::validate::{self, ValidateArgs};
+use reports::errors::ReportsError;
+
+#[derive(Debug, Parser)]
+#[command(version, about, next_line_help = true)]
+struct CliArgs {
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// Convert reports into a single report of another format
+  Convert(Box<ConvertArgs>),
+  /// Compare records of two reports
+  Compare(CompareArgs),
+  /// Split a report into parts by size, period or field
+  Split(SplitArgs),
+  /// Merge reports sorted by the same key into a single sorted report
+  Merge(MergeArgs),
+  /// Check reports for broken and inconsistent records
+  Validate(ValidateArgs),
+  /// Summarize record counts and amounts by type, status, user and time
+  Stats(StatsArgs),
+  /// Replay successful transactions into per user balances
+  Ledger(LedgerArgs),
+  /// Track status lifecycle of transactions across a series of reports
+  Reconcile(ReconcileArgs),
+  /// Run an SQL-like query over reports
+  Query(QueryArgs),
+  /// Salvage intact records of a damaged binary report
+  Repair(RepairArgs),
+  /// Build, verify and look up sidecar indexes of binary reports
+  Index(IndexArgs),
+}
+
+fn main() -> Result<(), ReportsError> {
+  match CliArgs::parse().command {
+    Command::Convert(args) => convert::run(*args),
+    Command::Compare(args) => compare::run(args),
+    Command::Split(args) => split::run(args),
+    Command::Merge(args) => merge::run(args),
+    Command::Validate(args) => validate::run(args),
+    Command::Stats(args) => stats::run(args),
+    Command::Ledger(args) => ledger::run(args),
+    Command::Reconcile(args) => reconcile::run(args),
+    Command::Query(args) => query::run(args),
+    Command::Repair(args) => repair::run(args),
+    Command::Index(args) => index::run(args),
+  }
+}
+
+#[cfg(test)]
+mod test_reports {
+  use crate::CliArgs;
+  use clap::CommandFactory;
+
+  #[test]
+  fn test_cli_args() {
+    CliArgs::command().debug_assert();
+  }
+}
@@ -0,0 +1,356 @@
+use crate::errors::ReportsError;
+use crate::formats::DataFormat;
+use bytes::Bytes;
+use parser::columnar::{
+  BatchPosition, BatchRecords, ipc_records, parquet_records,
+};
+use parser::compression::DecompressedReader;
+use parser::errors::{ColumnarError, ParsingError};
+use parser::parsers::{Mt940Accounts, Mt940Reader};
+use parser::record::BankRecord;
+use parser::sqlite::{SQLITE_TABLE, SqliteReader, table_query};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Cursor, ErrorKind, Read};
+use std::iter;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::vec;
+
+/// Report source, `-` stands for stdin
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+  Stdin,
+  File(PathBuf),
+}
+
+/// Settings of formats which aren't described by the report data alone
+#[derive(Debug, Clone)]
+pub struct SourceOptions {
+  /// Query SQLite records are read with
+  pub sqlite_query: String,
+  /// User ids of MT940 statement accounts
+  pub mt940_accounts: Mt940Accounts,
+}
+
+impl Default for SourceOptions {
+  fn default() -> Self {
+    Self {
+      sqlite_query: table_query(SQLITE_TABLE),
+      mt940_accounts: Mt940Accounts::default(),
+    }
+  }
+}
+
+/// Place of the record in the report, lines and rows are counted from 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+  Line(usize),
+  /// Byte offset of the binary record
+  Offset(u64),
+  /// Row of the SQLite query result
+  Row(usize),
+  /// Record batch of the columnar file and the row in it, errors of the
+  /// whole batch have no row
+  Batch {
+    batch: usize,
+    row: Option<usize>,
+  },
+}
+
+/// Record read by the reader of its format with its place in the report
+pub type LocatedRecord = (Location, Result<BankRecord, ReportsError>);
+
+/// Data of the opened source
+pub enum SourceReader<'a> {
+  /// Report data parsed record by record by its format
+  Data(Box<dyn BufRead + 'a>),
+  /// Records of formats read by their own readers, e.g. database rows
+  Records(Box<dyn Iterator<Item = LocatedRecord> + 'a>),
+}
+
+/// Opened report source with detected data format
+pub struct Source<'a> {
+  pub name: String,
+  pub reader: SourceReader<'a>,
+  pub format: DataFormat,
+}
+
+/// Records of the source in report order, see `Source::records`
+pub struct Records<'a> {
+  name: String,
+  state: RecordsState<'a>,
+}
+
+enum RecordsState<'a> {
+  /// Report data before the headers line is skipped
+  Start {
+    reader: Box<dyn BufRead + 'a>,
+    format: DataFormat,
+    jobs: NonZeroUsize,
+  },
+  /// Report data parsed record by record
+  Data {
+    reader: Box<dyn BufRead + 'a>,
+    format: DataFormat,
+  },
+  /// Records parsed by multiple threads
+  Parsed(vec::IntoIter<BankRecord>),
+  Records(Box<dyn Iterator<Item = LocatedRecord> + 'a>),
+  Finished,
+}
+
+impl Display for Location {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Line(line) => write!(f, "{line}"),
+      Self::Offset(offset) => write!(f, "@{offset}"),
+      Self::Row(row) => write!(f, "row {row}"),
+      Self::Batch { batch, row: None } => write!(f, "batch {batch}"),
+      Self::Batch {
+        batch,
+        row: Some(row),
+      } => write!(f, "batch {batch} row {row}"),
+    }
+  }
+}
+
+impl Display for InputSource {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Stdin => write!(f, "stdin"),
+      Self::File(path) => write!(f, "{}", path.display()),
+    }
+  }
+}
+
+impl Source<'static> {
+  /// Opens the input, compressed reports are decompressed transparently.
+  /// Format is detected by the file extension or the report data if it's not
+  /// set, errors are attributed to the input.
+  pub fn open(
+    input: &InputSource,
+    format: Option<DataFormat>,
+  ) -> Result<Self, ReportsError> {
+    Self::open_with(input, format, &SourceOptions::default())
+  }
+
+  /// Opens the input as `open` does with non-default format settings, e.g.
+  /// SQLite databases are read with the query instead of the default table
+  pub fn open_with(
+    input: &InputSource,
+    format: Option<DataFormat>,
+    options: &SourceOptions,
+  ) -> Result<Self, ReportsError> {
+    let name = input.to_string();
+
+    let open = || -> Result<Self, ReportsError> {
+      let reader: Box<dyn BufRead> = match input {
+        InputSource::Stdin => Box::new(io::stdin().lock()),
+        InputSource::File(path) => Box::new(BufReader::new(File::open(path)?)),
+      };
+      let mut reader = DecompressedReader::new(reader)?;
+
+      let format = match (format, input) {
+        (Some(format), _) => Some(format),
+        (None, InputSource::File(path)) => DataFormat::from_path(path),
+        (None, InputSource::Stdin) => None,
+      };
+      let format = match format {
+        Some(format) => format,
+        None => DataFormat::sniff(reader.fill_buf()?)
+          .ok_or(ReportsError::UnknownFormat)?,
+      }
+      .readable()?;
+
+      match format {
+        DataFormat::Sqlite => {
+          return match input {
+            InputSource::File(path) => {
+              Self::open_sqlite(path, &options.sqlite_query)
+            }
+            InputSource::Stdin => Err(ReportsError::IO(io::Error::new(
+              ErrorKind::InvalidInput,
+              "SQLite databases can't be read from stdin",
+            ))),
+          };
+        }
+        // Columnar files are read as a whole, their footer comes last.
+        // Records are converted batch by batch.
+        DataFormat::Arrow | DataFormat::Parquet => {
+          let mut data = vec![];
+          reader.read_to_end(&mut data)?;
+          let records: Box<dyn Iterator<Item = LocatedRecord>> = match format {
+            DataFormat::Arrow => {
+              Box::new(batch_locations(ipc_records(Cursor::new(data))?))
+            }
+            _ => Box::new(batch_locations(parquet_records(Bytes::from(data))?)),
+          };
+
+          return Ok(Source {
+            name: name.clone(),
+            reader: SourceReader::Records(records),
+            format,
+          });
+        }
+        // Statement lines depend on the account of their statement
+        DataFormat::Mt940 => {
+          let mut records =
+            Mt940Reader::new(reader, options.mt940_accounts.clone());
+          let records = iter::from_fn(move || {
+            let record = records.next()?;
+            let location = Location::Line(records.line());
+
+            Some((location, record.map_err(ReportsError::from)))
+          });
+
+          return Ok(Source {
+            name: name.clone(),
+            reader: SourceReader::Records(Box::new(records)),
+            format,
+          });
+        }
+        _ => {}
+      }
+
+      Ok(Source::new(name.clone(), reader, format))
+    };
+
+    open().map_err(|err| err.for_source(&name))
+  }
+
+  /// Reads records returned by the query row by row
+  fn open_sqlite(path: &Path, query: &str) -> Result<Self, ReportsError> {
+    let records =
+      SqliteReader::open(path, query)?
+        .enumerate()
+        .map(|(index, record)| {
+          (Location::Row(index + 1), record.map_err(ReportsError::from))
+        });
+
+    Ok(Source {
+      name: path.display().to_string(),
+      reader: SourceReader::Records(Box::new(records)),
+      format: DataFormat::Sqlite,
+    })
+  }
+}
+
+/// Locates records of columnar files by their batch and row, counted from 1
+fn batch_locations<I>(
+  mut records: BatchRecords<I>,
+) -> impl Iterator<Item = LocatedRecord>
+where
+  BatchRecords<I>: Iterator<Item = Result<BankRecord, ColumnarError>>,
+{
+  iter::from_fn(move || {
+    let record = records.next()?;
+    let BatchPosition { batch, row } = records.position();
+    let location = Location::Batch {
+      batch: batch + 1,
+      row: row.map(|row| row + 1),
+    };
+
+    Some((location, record.map_err(ReportsError::from)))
+  })
+}
+
+impl<'a> Source<'a> {
+  /// Source of the report data in the format
+  pub fn new(
+    name: impl Into<String>,
+    reader: impl BufRead + 'a,
+    format: DataFormat,
+  ) -> Self {
+    Self {
+      name: name.into(),
+      reader: SourceReader::Data(Box::new(reader)),
+      format,
+    }
+  }
+
+  /// Reads records of the source until the end of data, the headers line is
+  /// skipped. Parsing errors are attributed to the source and end the records.
+  pub fn records(self) -> Records<'a> {
+    self.parallel_records(NonZeroUsize::MIN)
+  }
+
+  /// Reads records as `records` does, records of formats which can be split
+  /// into chunks are parsed by `jobs` threads. The whole report data is read
+  /// into memory for that, so only sequential reading keeps memory bounded.
+  pub fn parallel_records(self, jobs: NonZeroUsize) -> Records<'a> {
+    let state = match self.reader {
+      SourceReader::Data(reader) => RecordsState::Start {
+        reader,
+        format: self.format,
+        jobs,
+      },
+      SourceReader::Records(records) => RecordsState::Records(records),
+    };
+
+    Records {
+      name: self.name,
+      state,
+    }
+  }
+}
+
+impl Records<'_> {
+  fn read(&mut self) -> Result<Option<BankRecord>, ReportsError> {
+    if matches!(self.state, RecordsState::Start { .. })
+      && let RecordsState::Start {
+        mut reader,
+        format,
+        jobs,
+      } = mem::replace(&mut self.state, RecordsState::Finished)
+    {
+      format.skip_header(&mut reader)?;
+
+      self.state = match format.chunk_boundary() {
+        Some(_) if jobs.get() > 1 => RecordsState::Parsed(
+          format.read_records(&mut reader, jobs)?.into_iter(),
+        ),
+        _ => RecordsState::Data { reader, format },
+      };
+    }
+
+    match &mut self.state {
+      RecordsState::Data { reader, format } => {
+        match format.read_record(reader) {
+          Ok(record) => Ok(Some(record)),
+          Err(ParsingError::IO(err))
+            if err.kind() == ErrorKind::UnexpectedEof =>
+          {
+            Ok(None)
+          }
+          Err(err) => Err(err.into()),
+        }
+      }
+      RecordsState::Parsed(records) => Ok(records.next()),
+      RecordsState::Records(records) => {
+        records.next().map(|(_, record)| record).transpose()
+      }
+      RecordsState::Start { .. } | RecordsState::Finished => Ok(None),
+    }
+  }
+}
+
+impl Iterator for Records<'_> {
+  type Item = Result<BankRecord, ReportsError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.read() {
+      Ok(Some(record)) => Some(Ok(record)),
+      Ok(None) => {
+        self.state = RecordsState::Finished;
+        None
+      }
+      Err(err) => {
+        self.state = RecordsState::Finished;
+        Some(Err(err.for_source(&self.name)))
+      }
+    }
+  }
+}
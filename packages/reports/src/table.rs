@@ -0,0 +1,79 @@
+//! Aligned text tables of the command outputs
+
+use std::io;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+  Left,
+  Right,
+}
+
+/// Cell text with its alignment
+pub type TableCell = (String, Align);
+
+/// Writes the header and the rows with columns separated by two spaces.
+/// Column widths are counted in chars, left aligned cells of the last column
+/// are not padded.
+pub fn write_table(
+  writer: &mut impl Write,
+  header: Vec<TableCell>,
+  rows: Vec<Vec<TableCell>>,
+) -> io::Result<()> {
+  let mut widths: Vec<usize> = vec![0; header.len()];
+  for row in std::iter::once(&header).chain(&rows) {
+    for (width, (text, _)) in widths.iter_mut().zip(row) {
+      *width = (*width).max(text.chars().count());
+    }
+  }
+
+  let last = widths.len().saturating_sub(1);
+  for row in std::iter::once(header).chain(rows) {
+    let mut line = String::new();
+    for (index, ((text, align), width)) in row.iter().zip(&widths).enumerate() {
+      if index > 0 {
+        line.push_str("  ");
+      }
+      match align {
+        Align::Right => line.push_str(&format!("{text:>width$}")),
+        Align::Left if index == last => line.push_str(text),
+        Align::Left => line.push_str(&format!("{text:<width$}")),
+      }
+    }
+    writeln!(writer, "{line}")?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod table_test {
+  use crate::table::{Align, TableCell, write_table};
+
+  fn cell(text: &str, align: Align) -> TableCell {
+    (String::from(text), align)
+  }
+
+  #[test]
+  fn test_write_table_counts_chars() {
+    let mut output_buffer: Vec<u8> = vec![];
+
+    write_table(
+      &mut output_buffer,
+      vec![cell("name", Align::Left), cell("sum", Align::Right)],
+      vec![
+        vec![cell("café", Align::Left), cell("1", Align::Right)],
+        vec![cell("tea", Align::Left), cell("1000", Align::Right)],
+      ],
+    )
+    .unwrap();
+
+    assert_eq!(
+      String::from_utf8(output_buffer).unwrap(),
+      "name   sum
+café     1
+tea   1000
+"
+    );
+  }
+}
@@ -0,0 +1,36 @@
+//! Fixtures shared by the tests of the commands
+
+use crate::formats::DataFormat;
+use crate::sources::Source;
+use parser::parsers::BinRecord;
+use parser::record::{BankRecord, BankRecordParser, Status, TxType};
+use std::io::Cursor;
+
+/// Source named `input` which reads a copy of the data
+pub fn source(data: impl AsRef<[u8]>, format: DataFormat) -> Source<'static> {
+  Source::new("input", Cursor::new(data.as_ref().to_vec()), format)
+}
+
+/// Successful deposit of user 2 described by its transaction id
+pub fn record(tx_id: u64) -> BankRecord {
+  BankRecord {
+    tx_id,
+    tx_type: TxType::Deposit,
+    from_user_id: 0,
+    to_user_id: 2,
+    amount: 100,
+    timestamp: 1633036860000,
+    status: Status::Success,
+    description: format!("Record number {tx_id}"),
+  }
+}
+
+/// Binary report of the records
+pub fn bin_report(records: impl IntoIterator<Item = BankRecord>) -> Vec<u8> {
+  let mut data = vec![];
+  for record in records {
+    BinRecord(record).write_to(&mut data).unwrap();
+  }
+
+  data
+}
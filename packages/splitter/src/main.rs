@@ -0,0 +1,27 @@
+//! Compatibility binary, same as `reports split`
+
+use clap::Parser;
+use reports::commands::split::{SplitArgs, run};
+use reports::errors::ReportsError;
+
+#[derive(Debug, Parser)]
+#[command(version, about, next_line_help = true)]
+struct CliArgs {
+  #[command(flatten)]
+  args: SplitArgs,
+}
+
+fn main() -> Result<(), ReportsError> {
+  run(CliArgs::parse().args)
+}
+
+#[cfg(test)]
+mod test_splitter {
+  use crate::CliArgs;
+  use clap::CommandFactory;
+
+  #[test]
+  fn test_cli_args() {
+    CliArgs::command().debug_assert();
+  }
+}